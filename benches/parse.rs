@@ -0,0 +1,70 @@
+//! Parsing benchmarks for large responses.
+//!
+//! Run with `cargo bench --profile perf`.
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use searxng_client::SearchResponse;
+use searxng_client::parse::ParseMode;
+
+/// Builds a response body with `results` modern results, as sent by recent instances.
+fn response_body(results: usize) -> String {
+    let results: Vec<_> = (0..results)
+        .map(|i| {
+            serde_json::json!({
+                "url": format!("https://example.org/page/{i}"),
+                "template": "default.html",
+                "engine": "duckduckgo",
+                "parsed_url": ["https", "example.org", format!("/page/{i}"), "", "", ""],
+                "title": format!("Result number {i} about the Rust programming language"),
+                "content": "Rust is a general-purpose programming language emphasizing performance, type safety and concurrency.".repeat(2),
+                "img_src": "",
+                "thumbnail": "",
+                "priority": "",
+                "engines": ["duckduckgo", "brave", "startpage"],
+                "positions": [i + 1, i + 2, i + 3],
+                "score": 3.0 / (i + 1) as f64,
+                "category": "general",
+                "publishedDate": "2024-05-01T08:30:00",
+                "pubdate": "2024-05-01 08:30:00",
+                "iframe_src": "",
+                "audio_src": "",
+                "views": "",
+                "author": "",
+                "metadata": "",
+                "open_group": false,
+                "close_group": false,
+                "length": null,
+            })
+        })
+        .collect();
+    serde_json::json!({
+        "query": "rust",
+        "number_of_results": 0,
+        "results": results,
+        "answers": [],
+        "corrections": [],
+        "infoboxes": [],
+        "suggestions": ["rust language", "rust game"],
+        "unresponsive_engines": [],
+    })
+    .to_string()
+}
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for results in [10, 100, 1000] {
+        let body = response_body(results);
+        group.throughput(Throughput::Bytes(body.len() as u64));
+        for mode in [ParseMode::Strict, ParseMode::Salvage] {
+            group.bench_with_input(
+                BenchmarkId::new(format!("{mode:?}"), results),
+                &body,
+                |b, body| b.iter(|| SearchResponse::from_json_str(body, mode).unwrap()),
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
@@ -0,0 +1,312 @@
+//! Parallel, same-query search across several instances.
+//!
+//! Unlike failover, aggregation sends the same request to every instance at once and
+//! merges the deduplicated results. This increases recall, since different instances
+//! often have different engines enabled, and tolerates individual instances failing.
+
+use std::time::Duration;
+
+use crate::blend::ScoreBlender;
+use crate::client::{SearXNGClient, SearchParams};
+use crate::dedup::{Deduplicator, MergePolicy};
+use crate::error::Error;
+use crate::latency::AdaptiveTimeout;
+use crate::response::{SearchResponse, SearchResult};
+use crate::rt::Instant;
+use crate::task::{TaskReport, TaskSet};
+
+/// The default per-instance timeout used by an [`Aggregator`].
+pub const DEFAULT_INSTANCE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The default consensus boost used by an [`Aggregator`].
+pub const DEFAULT_CONSENSUS_BOOST: f64 = 0.5;
+
+/// The outcome of the request sent to one instance.
+#[derive(Debug)]
+pub struct InstanceResponse {
+    /// The root URL of the instance.
+    pub instance: String,
+    /// How long the instance took to answer or fail.
+    pub latency: Duration,
+    /// The response, or the error the request failed with.
+    pub response: Result<SearchResponse, Error>,
+}
+
+/// A result merged from the responses of one or more instances.
+#[derive(Debug, Clone)]
+pub struct MergedResult {
+    /// The merged result, with its score combined according to the [`MergePolicy`].
+    pub result: SearchResult,
+    /// The number of instances that returned this result.
+    pub agreement: usize,
+    /// The merged score after applying the consensus boost.
+    pub consensus_score: f64,
+    /// The score the results are sorted by: the blended score if the aggregator has a
+    /// [`ScoreBlender`], the consensus score otherwise.
+    pub blended_score: f64,
+}
+
+/// The merged result of an aggregated search.
+#[derive(Debug)]
+pub struct AggregatedResponse {
+    /// The deduplicated results of all instances, sorted by blended score (highest first).
+    pub results: Vec<MergedResult>,
+    /// The per-instance outcomes, in the order the instances were configured.
+    pub instances: Vec<InstanceResponse>,
+    /// The requests spawned, one per instance.
+    pub tasks: TaskReport,
+}
+
+impl AggregatedResponse {
+    /// Returns the number of instances that answered successfully.
+    pub fn successful_instances(&self) -> usize {
+        self.instances.iter().filter(|i| i.response.is_ok()).count()
+    }
+}
+
+/// Queries several instances simultaneously and merges their results.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use searxng_client::{SearXNGClient, ResponseFormat};
+/// use searxng_client::aggregate::Aggregator;
+/// use searxng_client::dedup::MergePolicy;
+/// # tokio_test::block_on(async {
+/// let aggregator = Aggregator::new([
+///     SearXNGClient::new("https://searx.be", ResponseFormat::Json),
+///     SearXNGClient::new("https://search.example.org", ResponseFormat::Json),
+/// ])
+/// .with_merge_policy(MergePolicy::Sum);
+/// let response = aggregator.search("rust").await;
+/// println!("{} results from {} instances", response.results.len(), response.successful_instances());
+/// # });
+/// ```
+#[derive(Debug, Clone)]
+pub struct Aggregator {
+    clients: Vec<SearXNGClient>,
+    policy: MergePolicy,
+    timeout: Duration,
+    adaptive_timeout: Option<AdaptiveTimeout>,
+    consensus_boost: f64,
+    blender: Option<ScoreBlender>,
+}
+
+impl Aggregator {
+    /// Creates an aggregator over the given clients.
+    pub fn new(clients: impl IntoIterator<Item = SearXNGClient>) -> Self {
+        Aggregator {
+            clients: clients.into_iter().collect(),
+            policy: MergePolicy::default(),
+            timeout: DEFAULT_INSTANCE_TIMEOUT,
+            adaptive_timeout: None,
+            consensus_boost: DEFAULT_CONSENSUS_BOOST,
+            blender: None,
+        }
+    }
+
+    /// Sets how the scores of results returned by several instances are combined.
+    pub fn with_merge_policy(mut self, policy: MergePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Sets the timeout applied to the request sent to each instance.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Derives each instance's timeout from its observed latencies instead of using the
+    /// static timeout set by [`with_timeout`](Self::with_timeout).
+    pub fn with_adaptive_timeout(mut self, adaptive: AdaptiveTimeout) -> Self {
+        self.adaptive_timeout = Some(adaptive);
+        self
+    }
+
+    /// Sets how strongly results returned by several instances are boosted.
+    ///
+    /// The consensus score of a result returned by `n` instances is its merged score
+    /// multiplied by `1 + boost * (n - 1)`. A boost of `0.0` disables consensus weighting.
+    pub fn with_consensus_boost(mut self, boost: f64) -> Self {
+        self.consensus_boost = boost;
+        self
+    }
+
+    /// Orders the merged results by the score `blender` combines from the SearXNG score,
+    /// the consensus and other rankers, instead of by consensus score alone.
+    pub fn with_score_blender(mut self, blender: ScoreBlender) -> Self {
+        self.blender = Some(blender);
+        self
+    }
+
+    /// Searches every instance for `query` and merges the results.
+    pub async fn search(&self, query: impl Into<String>) -> AggregatedResponse {
+        let query = query.into();
+        self.search_with_params(|client| SearchParams::new(query.clone(), client.format()))
+            .await
+    }
+
+    /// Searches every instance with the parameters built by `params` and merges the results.
+    ///
+    /// Each instance is queried by its own task, see [`task`](crate::task) for how they
+    /// are cancelled and how their panics propagate.
+    pub async fn search_with_params(
+        &self,
+        params: impl Fn(&SearXNGClient) -> SearchParams,
+    ) -> AggregatedResponse {
+        let mut query = None;
+        let mut tasks =
+            TaskSet::new(|(_, instance): &(usize, InstanceResponse)| instance.response.is_err());
+        for (i, client) in self.clients.iter().enumerate() {
+            let timeout = self
+                .adaptive_timeout
+                .map_or(self.timeout, |adaptive| adaptive.timeout(client.latency()));
+            let params = params(client);
+            query.get_or_insert_with(|| params.api.q.clone());
+            let client = client.clone();
+            tasks.spawn(async move {
+                let started = Instant::now();
+                let response = client
+                    .search("")
+                    .set_params(params)
+                    .set_timeout(timeout)
+                    .send()
+                    .await;
+                let instance = InstanceResponse {
+                    instance: client.instance_url().to_string(),
+                    latency: started.elapsed(),
+                    response,
+                };
+                (i, instance)
+            });
+        }
+        let mut instances = tasks.join_all().await;
+        instances.sort_by_key(|(i, _)| *i);
+        let instances: Vec<InstanceResponse> = instances
+            .into_iter()
+            .map(|(_, instance)| instance)
+            .collect();
+
+        let mut deduplicator = Deduplicator::new(self.policy);
+        let mut agreement: Vec<usize> = Vec::new();
+        let mut last_instance: Vec<usize> = Vec::new();
+        for (n, instance) in instances.iter().enumerate() {
+            let Ok(response) = &instance.response else {
+                continue;
+            };
+            for result in response.results.iter().cloned() {
+                let i = deduplicator.insert(result);
+                if i == agreement.len() {
+                    agreement.push(1);
+                    last_instance.push(n);
+                } else if last_instance[i] != n {
+                    agreement[i] += 1;
+                    last_instance[i] = n;
+                }
+            }
+        }
+
+        let mut results: Vec<MergedResult> = deduplicator
+            .into_results()
+            .into_iter()
+            .zip(agreement)
+            .map(|(result, agreement)| {
+                let consensus_score =
+                    result.score() * (1.0 + self.consensus_boost * (agreement - 1) as f64);
+                MergedResult {
+                    result,
+                    agreement,
+                    consensus_score,
+                    blended_score: consensus_score,
+                }
+            })
+            .collect();
+        match &self.blender {
+            Some(blender) => blender.blend(query.as_deref().unwrap_or_default(), &mut results),
+            None => results.sort_by(|a, b| b.consensus_score.total_cmp(&a.consensus_score)),
+        }
+
+        AggregatedResponse {
+            results,
+            instances,
+            tasks: tasks.report(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::ResponseFormat;
+    use crate::retry::RetryPolicy;
+    use crate::test::empty_response_json;
+    use crate::transport::MockTransport;
+
+    fn instance(root: &str, results: &[(&str, f64)]) -> SearXNGClient {
+        let mut body = empty_response_json("rust");
+        body["results"] = results
+            .iter()
+            .map(|(url, score)| {
+                serde_json::json!({
+                    "url": url,
+                    "template": "default.html",
+                    "engine": "duckduckgo",
+                    "title": url,
+                    "content": "",
+                    "img_src": "",
+                    "thumbnail": "",
+                    "priority": "",
+                    "engines": ["duckduckgo"],
+                    "positions": [1],
+                    "score": score,
+                    "category": "general"
+                })
+            })
+            .collect();
+        SearXNGClient::new(root, ResponseFormat::Json)
+            .with_retry_policy(RetryPolicy::none())
+            .with_transport(MockTransport::new().with_fixture("rust", body.to_string()))
+    }
+
+    #[tokio::test]
+    async fn test_aggregate_consensus() {
+        let aggregator = Aggregator::new([
+            // The duplicate within one instance does not count towards agreement.
+            instance(
+                "https://a.example.org",
+                &[
+                    ("https://x.org/", 2.0),
+                    ("https://y.org", 1.0),
+                    ("https://www.x.org", 1.5),
+                ],
+            ),
+            instance(
+                "https://b.example.org",
+                &[("https://x.org", 1.0), ("https://z.org", 2.5)],
+            ),
+        ]);
+        let response = aggregator.search("rust").await;
+        assert_eq!(response.successful_instances(), 2);
+
+        let merged: Vec<_> = response
+            .results
+            .iter()
+            .map(|r| (r.result.url().unwrap(), r.agreement, r.consensus_score))
+            .collect();
+        assert_eq!(
+            merged,
+            [
+                ("https://x.org/", 2, 3.0),
+                ("https://z.org", 1, 2.5),
+                ("https://y.org", 1, 1.0),
+            ]
+        );
+        assert!(
+            response
+                .results
+                .iter()
+                .all(|r| r.blended_score == r.consensus_score)
+        );
+    }
+}
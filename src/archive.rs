@@ -0,0 +1,284 @@
+//! Long-term storage of collected results, behind the `archive` feature.
+//!
+//! A [`ResultArchive`] appends batches of results, one per query run, to a single file.
+//! Each batch is stored as a zstd-compressed, length-prefixed JSON record, and an index
+//! of all records is written at the end of the file when the archive is finished. An
+//! [`ArchiveReader`] uses the index to iterate over the batches, or to read those of one
+//! query without decompressing the others.
+//!
+//! The layout is:
+//!
+//! ```text
+//! "SXRA" version:u8
+//! (length:u32le record)*
+//! length:u32le index
+//! index_offset:u64le "SXRA"
+//! ```
+
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::provenance::SourcedResult;
+use crate::sink::ResultSink;
+
+const MAGIC: &[u8; 4] = b"SXRA";
+const VERSION: u8 = 1;
+/// The size of the header, magic and version.
+const HEADER_LEN: u64 = 5;
+/// The size of the footer, index offset and magic.
+const FOOTER_LEN: u64 = 12;
+
+/// The zstd compression level used for records unless set with
+/// [`ResultArchive::with_level`].
+pub const DEFAULT_LEVEL: i32 = 9;
+
+/// One batch of results, as stored in an archive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveEntry {
+    /// The query the results were collected for.
+    pub query: String,
+    /// When the batch was appended to the archive.
+    pub archived_at: DateTime<Utc>,
+    pub results: Vec<SourcedResult>,
+}
+
+/// The location of one [`ArchiveEntry`] in an archive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub query: String,
+    pub archived_at: DateTime<Utc>,
+    /// The number of results in the entry.
+    pub results: usize,
+    /// The position of the record's length prefix in the file.
+    pub offset: u64,
+}
+
+/// Writes results to an archive, see the [module documentation](self).
+///
+/// The archive is only readable once [`finish`](Self::finish) wrote the index.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use searxng_client::{SearXNGClient, ResponseFormat};
+/// use searxng_client::archive::ResultArchive;
+/// # tokio_test::block_on(async {
+/// # let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json);
+/// let file = std::io::BufWriter::new(std::fs::File::create("crawl.sxra")?);
+/// let mut archive = ResultArchive::new(file)?;
+/// for query in ["rust", "cargo"] {
+///     let results = client.search(query).send_get_num_with_provenance(100).await?;
+///     archive.append(query, &results)?;
+/// }
+/// archive.finish()?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// # });
+/// ```
+#[derive(Debug)]
+pub struct ResultArchive<W: Write> {
+    writer: W,
+    level: i32,
+    position: u64,
+    index: Vec<IndexEntry>,
+}
+
+impl<W: Write> ResultArchive<W> {
+    /// Starts an archive, writing its header to `writer`.
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        Ok(ResultArchive {
+            writer,
+            level: DEFAULT_LEVEL,
+            position: HEADER_LEN,
+            index: Vec::new(),
+        })
+    }
+
+    /// Sets the zstd compression level of the records written from now on.
+    pub fn with_level(mut self, level: i32) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Appends the results collected for `query` as one entry.
+    pub fn append(&mut self, query: &str, results: &[SourcedResult]) -> io::Result<()> {
+        let entry = ArchiveEntry {
+            query: query.to_string(),
+            archived_at: Utc::now(),
+            results: results.to_vec(),
+        };
+        let offset = self.write_record(&entry)?;
+        self.index.push(IndexEntry {
+            query: entry.query,
+            archived_at: entry.archived_at,
+            results: entry.results.len(),
+            offset,
+        });
+        Ok(())
+    }
+
+    /// Writes the index and the footer, returning the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        let index = std::mem::take(&mut self.index);
+        let offset = self.write_record(&index)?;
+        self.writer.write_all(&offset.to_le_bytes())?;
+        self.writer.write_all(MAGIC)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    /// Writes a length-prefixed, compressed record, returning its offset.
+    fn write_record<T: Serialize>(&mut self, value: &T) -> io::Result<u64> {
+        let json = serde_json::to_vec(value)?;
+        let compressed = zstd::encode_all(&json[..], self.level)?;
+        let len = u32::try_from(compressed.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "record too large"))?;
+        self.writer.write_all(&len.to_le_bytes())?;
+        self.writer.write_all(&compressed)?;
+        let offset = self.position;
+        self.position += 4 + u64::from(len);
+        Ok(offset)
+    }
+}
+
+/// Archives every page of a crawl as one entry, under the query of its first result.
+impl<W: Write> ResultSink for ResultArchive<W> {
+    fn write_page(&mut self, results: &[SourcedResult]) -> io::Result<()> {
+        match results.first() {
+            Some(first) => {
+                let query = first.provenance.query.clone();
+                self.append(&query, results)
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+/// Reads an archive written by a [`ResultArchive`].
+#[derive(Debug)]
+pub struct ArchiveReader<R: Read + Seek> {
+    reader: R,
+    index: Vec<IndexEntry>,
+}
+
+impl<R: Read + Seek> ArchiveReader<R> {
+    /// Opens an archive, reading its index.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidData`](io::ErrorKind::InvalidData) if `reader`
+    /// is not a finished archive of a supported version.
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let mut header = [0; HEADER_LEN as usize];
+        reader.read_exact(&mut header)?;
+        if &header[..4] != MAGIC || header[4] != VERSION {
+            return Err(invalid("not a result archive of a supported version"));
+        }
+        reader.seek(SeekFrom::End(-(FOOTER_LEN as i64)))?;
+        let mut footer = [0; FOOTER_LEN as usize];
+        reader.read_exact(&mut footer)?;
+        if &footer[8..] != MAGIC {
+            return Err(invalid("the archive was not finished"));
+        }
+        let offset = u64::from_le_bytes(footer[..8].try_into().expect("8 bytes"));
+        let index = read_record(&mut reader, offset)?;
+        Ok(ArchiveReader { reader, index })
+    }
+
+    /// The entries of the archive, in the order they were appended.
+    pub fn index(&self) -> &[IndexEntry] {
+        &self.index
+    }
+
+    /// Reads the entry at position `i` of the [`index`](Self::index).
+    pub fn get(&mut self, i: usize) -> io::Result<ArchiveEntry> {
+        let offset = self
+            .index
+            .get(i)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no such entry"))?
+            .offset;
+        read_record(&mut self.reader, offset)
+    }
+
+    /// Iterates over every entry, in the order they were appended.
+    pub fn entries(&mut self) -> impl Iterator<Item = io::Result<ArchiveEntry>> + '_ {
+        (0..self.index.len()).map(|i| self.get(i))
+    }
+
+    /// Iterates over the entries archived for `query`, reading only those.
+    pub fn entries_for(
+        &mut self,
+        query: &str,
+    ) -> impl Iterator<Item = io::Result<ArchiveEntry>> + '_ {
+        let matches: Vec<usize> = (0..self.index.len())
+            .filter(|&i| self.index[i].query == query)
+            .collect();
+        matches.into_iter().map(|i| self.get(i))
+    }
+}
+
+fn read_record<R: Read + Seek, T: DeserializeOwned>(reader: &mut R, offset: u64) -> io::Result<T> {
+    reader.seek(SeekFrom::Start(offset))?;
+    let mut len = [0; 4];
+    reader.read_exact(&mut len)?;
+    let compressed = reader.take(u64::from(u32::from_le_bytes(len)));
+    let json = zstd::decode_all(compressed)?;
+    serde_json::from_slice(&json).map_err(|e| invalid(e.to_string()))
+}
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provenance::Provenance;
+    use crate::test::sample_result;
+    use std::io::Cursor;
+
+    fn sourced(query: &str, url: &str) -> SourcedResult {
+        SourcedResult {
+            result: sample_result(url, "r", ""),
+            provenance: Provenance {
+                instance: "https://searx.be/".into(),
+                pageno: 1,
+                query: query.into(),
+                fetched_at: Utc::now(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_archive_round_trip() {
+        let mut archive = ResultArchive::new(Vec::new()).unwrap();
+        archive
+            .append("rust", &[sourced("rust", "https://a.org")])
+            .unwrap();
+        archive
+            .write_page(&[
+                sourced("cargo", "https://b.org"),
+                sourced("cargo", "https://c.org"),
+            ])
+            .unwrap();
+        archive
+            .append("rust", &[sourced("rust", "https://d.org")])
+            .unwrap();
+        let bytes = archive.finish().unwrap();
+
+        let mut reader = ArchiveReader::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(reader.index().len(), 3);
+        assert_eq!(reader.index()[1].results, 2);
+        assert_eq!(reader.get(1).unwrap().query, "cargo");
+        let rust: Vec<ArchiveEntry> = reader.entries_for("rust").map(Result::unwrap).collect();
+        assert_eq!(rust.len(), 2);
+        assert_eq!(reader.entries().count(), 3);
+
+        let unfinished = ResultArchive::new(Vec::new()).unwrap().writer;
+        assert!(ArchiveReader::new(Cursor::new(unfinished)).is_err());
+    }
+}
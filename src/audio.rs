@@ -0,0 +1,203 @@
+//! Music and audio results, and M3U playlist export.
+//!
+//! Music engines report the stream, artist and duration of a track in fields that are
+//! empty strings for every other result. [`AudioResult`] collects them with proper types,
+//! and [`to_m3u`] turns a set of tracks into a playlist any media player can open.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::response::{
+    LegacySearchResult, MainSearchResult, ResultRef, SearchResponse, SearchResult,
+};
+
+/// The typed view of a music or audio result.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AudioResult {
+    pub title: String,
+    /// The page of the track.
+    pub url: Option<String>,
+    /// A directly playable stream, if the engine reported one.
+    pub audio_src: Option<String>,
+    /// An embeddable player, if the engine reported one.
+    pub iframe_src: Option<String>,
+    /// The artist, falling back to the result author.
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub duration: Option<Duration>,
+    pub thumbnail: Option<String>,
+}
+
+impl AudioResult {
+    /// The location a player should open: the stream if there is one, else the page.
+    pub fn playable_url(&self) -> Option<&str> {
+        self.audio_src.as_deref().or(self.url.as_deref())
+    }
+}
+
+/// Parses a track duration such as `"3:45"`, `"1:02:03"`, `"PT3M45S"` or `"225"` (seconds).
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use searxng_client::audio::parse_duration;
+/// assert_eq!(parse_duration("3:45"), Some(Duration::from_secs(225)));
+/// assert_eq!(parse_duration("1:02:03"), Some(Duration::from_secs(3723)));
+/// assert_eq!(parse_duration("PT3M45S"), Some(Duration::from_secs(225)));
+/// assert_eq!(parse_duration("soon"), None);
+/// ```
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    let s = s.trim();
+    if s.starts_with('P') {
+        return iso8601::duration(s).ok().map(Duration::from);
+    }
+    let mut seconds = 0.0;
+    for part in s.split(':') {
+        let value: f64 = part.trim().parse().ok()?;
+        if value.is_nan() || value < 0.0 {
+            return None;
+        }
+        seconds = seconds * 60.0 + value;
+    }
+    // Infinite and overlong values do not fit a `Duration`.
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+impl ResultRef<'_> {
+    pub(crate) fn audio(self) -> Option<AudioResult> {
+        let audio_src = self.audio_src();
+        if audio_src.is_none() && self.category() != "music" {
+            return None;
+        }
+        let fields = self.template_fields();
+        Some(AudioResult {
+            title: self.title().to_string(),
+            url: self.url().map(str::to_string),
+            audio_src,
+            iframe_src: self.player(),
+            artist: fields.artist.clone().or_else(|| self.author_name()),
+            album: fields.album.clone(),
+            duration: self.duration(),
+            thumbnail: self.thumbnail(),
+        })
+    }
+}
+
+impl SearchResult {
+    /// Returns the typed view of this result, if it is a music or audio result.
+    ///
+    /// Results with an `audio_src`, or in the music category, are considered audio.
+    pub fn audio(&self) -> Option<AudioResult> {
+        self.by_ref().audio()
+    }
+}
+
+impl MainSearchResult {
+    /// Same as [`SearchResult::audio`].
+    pub fn audio(&self) -> Option<AudioResult> {
+        ResultRef::Main(self).audio()
+    }
+}
+
+impl LegacySearchResult {
+    /// Same as [`SearchResult::audio`].
+    pub fn audio(&self) -> Option<AudioResult> {
+        ResultRef::Legacy(self).audio()
+    }
+}
+
+/// Renders `tracks` as an extended M3U playlist.
+///
+/// Tracks without a [playable URL](AudioResult::playable_url) are skipped.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use searxng_client::audio::{AudioResult, to_m3u};
+/// let track = AudioResult {
+///     title: "Clair de lune".into(),
+///     url: None,
+///     audio_src: Some("https://example.org/clair.mp3".into()),
+///     iframe_src: None,
+///     artist: Some("Debussy".into()),
+///     album: None,
+///     duration: Some(Duration::from_secs(300)),
+///     thumbnail: None,
+/// };
+/// assert_eq!(
+///     to_m3u([&track]),
+///     "#EXTM3U\n#EXTINF:300,Debussy - Clair de lune\nhttps://example.org/clair.mp3\n"
+/// );
+/// ```
+pub fn to_m3u<'a>(tracks: impl IntoIterator<Item = &'a AudioResult>) -> String {
+    let mut m3u = String::from("#EXTM3U\n");
+    for track in tracks {
+        let Some(location) = track.playable_url() else {
+            continue;
+        };
+        let location = location.replace(['\r', '\n'], "");
+        let seconds = track.duration.map_or(-1, |d| d.as_secs() as i64);
+        // Line breaks would end the directive early, or add lines to the playlist.
+        let title = match &track.artist {
+            Some(artist) => format!("{artist} - {}", track.title),
+            None => track.title.clone(),
+        }
+        .replace(['\r', '\n'], " ");
+        m3u.push_str(&format!("#EXTINF:{seconds},{title}\n{location}\n"));
+    }
+    m3u
+}
+
+impl SearchResponse {
+    /// Collects the music and audio results of this response.
+    pub fn audio_results(&self) -> Vec<AudioResult> {
+        self.results
+            .iter()
+            .filter_map(SearchResult::audio)
+            .collect()
+    }
+
+    /// Renders the music and audio results of this response as an M3U playlist.
+    pub fn to_m3u(&self) -> String {
+        to_m3u(&self.audio_results())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration_rejects_non_finite() {
+        assert_eq!(
+            parse_duration("1:30.5"),
+            Some(Duration::from_secs_f64(90.5))
+        );
+        for s in ["NaN", "inf", "-inf", "1e400", "1:NaN", "-3", "1e300:0"] {
+            assert_eq!(parse_duration(s), None, "{s}");
+        }
+    }
+
+    #[test]
+    fn test_to_m3u_strips_line_breaks() {
+        let track = AudioResult {
+            title: "Clair\nde lune".into(),
+            url: None,
+            audio_src: Some(
+                "https://example.org/a.mp3\n#EXTINF:1,Injected\r\nhttps://evil.org".into(),
+            ),
+            iframe_src: None,
+            artist: None,
+            album: None,
+            duration: None,
+            thumbnail: None,
+        };
+        assert_eq!(
+            to_m3u([&track]),
+            "#EXTM3U\n#EXTINF:-1,Clair de lune\nhttps://example.org/a.mp3#EXTINF:1,Injectedhttps://evil.org\n"
+        );
+    }
+}
@@ -0,0 +1,121 @@
+//! Audit logging of outbound requests.
+//!
+//! When an [`AuditSink`] is configured on the client, one [`AuditRecord`] is emitted for
+//! every request sent to an instance. Records never contain the query itself, only its
+//! [`query_hash`](crate::redact::query_hash).
+
+use std::fmt;
+use std::io::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::client::SearchParams;
+use crate::redact::query_hash;
+
+/// A single outbound request, as recorded in the audit log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// When the request was sent.
+    pub timestamp: DateTime<Utc>,
+    /// The URL of the endpoint that was called.
+    pub instance: String,
+    /// The hashed query, see [`query_hash`].
+    pub query_hash: String,
+    /// The remaining request parameters, with the query removed.
+    pub params: serde_json::Value,
+    /// The HTTP status code, if a response was received.
+    pub status: Option<u16>,
+    /// The time until the response headers were received, in milliseconds.
+    pub latency_ms: u64,
+    /// The transport error, if the request failed before a response was received. Its
+    /// URL is shown without the query, which carries the search terms of `GET` requests.
+    pub error: Option<String>,
+}
+
+impl AuditRecord {
+    pub(crate) fn new(
+        timestamp: DateTime<Utc>,
+        instance: &str,
+        params: &SearchParams,
+        pageno: Option<u32>,
+        status: Option<u16>,
+        latency: Duration,
+        error: Option<String>,
+    ) -> Self {
+        let mut params_json = serde_json::to_value(params).unwrap_or_default();
+        if let Some(map) = params_json.as_object_mut() {
+            map.remove("q");
+            if let Some(pageno) = pageno {
+                map.insert("pageno".into(), pageno.into());
+            }
+            map.retain(|_, v| !v.is_null());
+        }
+        AuditRecord {
+            timestamp,
+            instance: instance.to_string(),
+            query_hash: query_hash(&params.api.q),
+            params: params_json,
+            status,
+            latency_ms: latency.as_millis() as u64,
+            error,
+        }
+    }
+}
+
+/// A destination for [`AuditRecord`]s.
+pub trait AuditSink: fmt::Debug + Send + Sync {
+    /// Records a request. Implementations should not block for long, as this is called
+    /// inline after each request.
+    fn record(&self, record: &AuditRecord);
+}
+
+/// An append-only audit log writing one JSON object per line.
+pub struct JsonlAuditLog<W> {
+    writer: Mutex<W>,
+}
+
+impl<W: Write> JsonlAuditLog<W> {
+    /// Creates an audit log writing to `writer`, typically a file opened in append mode.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::fs::OpenOptions;
+    /// use searxng_client::{SearXNGClient, ResponseFormat};
+    /// use searxng_client::audit::JsonlAuditLog;
+    /// let file = OpenOptions::new().create(true).append(true).open("audit.jsonl")?;
+    /// let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json)
+    ///     .with_audit_log(JsonlAuditLog::new(file));
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn new(writer: W) -> Self {
+        JsonlAuditLog {
+            writer: Mutex::new(writer),
+        }
+    }
+
+    /// Consumes the log, returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<W> fmt::Debug for JsonlAuditLog<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonlAuditLog").finish_non_exhaustive()
+    }
+}
+
+impl<W: Write + Send> AuditSink for JsonlAuditLog<W> {
+    fn record(&self, record: &AuditRecord) {
+        let Ok(line) = serde_json::to_string(record) else {
+            return;
+        };
+        let mut writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        // Audit logging must never make a search fail, so write errors are ignored.
+        let _ = writeln!(writer, "{line}").and_then(|_| writer.flush());
+    }
+}
@@ -0,0 +1,40 @@
+//! Query suggestions from the `/autocompleter` endpoint.
+//!
+//! Instances answer in the OpenSearch suggestions format, `["partial", ["a", "b"]]`, or
+//! with a bare list of suggestions when the request looks like it comes from their own
+//! search form. Both are accepted, see
+//! [`SearXNGClient::autocomplete`](crate::SearXNGClient::autocomplete).
+
+use serde::Deserialize;
+use serde::de::IgnoredAny;
+
+use crate::parse::ParseError;
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Suggestions {
+    OpenSearch(IgnoredAny, Vec<String>),
+    Bare(Vec<String>),
+}
+
+/// Parses an `/autocompleter` response body into its suggestions.
+pub(crate) fn parse_suggestions(body: &[u8]) -> Result<Vec<String>, ParseError> {
+    match serde_json::from_slice(body).map_err(ParseError::Response)? {
+        Suggestions::OpenSearch(_, suggestions) | Suggestions::Bare(suggestions) => Ok(suggestions),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_suggestions() {
+        let expected = ["rust book", "rust lang"];
+        let open_search = br#"["rust", ["rust book", "rust lang"]]"#;
+        assert_eq!(parse_suggestions(open_search).unwrap(), expected);
+        let bare = br#"["rust book", "rust lang"]"#;
+        assert_eq!(parse_suggestions(bare).unwrap(), expected);
+        assert!(parse_suggestions(b"{}").is_err());
+    }
+}
@@ -0,0 +1,196 @@
+//! Running many queries at once.
+//!
+//! Pipelines such as retrieval-augmented generation often need dozens of related
+//! queries answered together. A [`BatchSearch`], created with
+//! [`SearXNGClient::search_many`], sends them with a bounded number in flight and
+//! returns every response keyed by its query. Every search goes through the same
+//! client: they share its [`RateLimiter`](crate::ratelimit::RateLimiter), retry policy
+//! and cache, so a batch stays within the client's rate limit however many queries it
+//! sends.
+//!
+//! Each query runs in its own task, see [`task`](crate::task) for how they are cancelled
+//! and how their panics propagate.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+
+use crate::client::{SearXNGClient, SearchBuilder};
+use crate::error::Error;
+use crate::response::SearchResponse;
+use crate::task::{TaskReport, TaskSet};
+
+/// The number of queries a [`BatchSearch`] sends at the same time, unless set with
+/// [`BatchSearch::with_concurrency`].
+pub const DEFAULT_BATCH_CONCURRENCY: usize = 4;
+
+type Configure = dyn for<'a> Fn(SearchBuilder<'a>) -> SearchBuilder<'a> + Send + Sync;
+
+/// The responses of a [`BatchSearch`], keyed by query.
+pub type BatchResponses = HashMap<String, Result<SearchResponse, Error>>;
+
+/// Many queries sent together, see the [module documentation](self).
+pub struct BatchSearch<'a> {
+    client: &'a SearXNGClient,
+    queries: Vec<String>,
+    concurrency: usize,
+    configure: Option<Arc<Configure>>,
+}
+
+impl fmt::Debug for BatchSearch<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BatchSearch")
+            .field("client", &self.client)
+            .field("queries", &self.queries.len())
+            .field("concurrency", &self.concurrency)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SearXNGClient {
+    /// Prepares a search for each of `queries`, see [`BatchSearch`].
+    ///
+    /// Repeated queries are only sent once.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use searxng_client::{SearXNGClient, ResponseFormat};
+    /// # tokio_test::block_on(async {
+    /// let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json);
+    /// let responses = client
+    ///     .search_many(["rust async", "rust tokio", "rust futures"])
+    ///     .with_concurrency(2)
+    ///     .send()
+    ///     .await;
+    /// for (query, response) in &responses {
+    ///     match response {
+    ///         Ok(response) => println!("{query}: {} results", response.results.len()),
+    ///         Err(e) => println!("{query} failed: {e}"),
+    ///     }
+    /// }
+    /// # });
+    /// ```
+    pub fn search_many(
+        &self,
+        queries: impl IntoIterator<Item = impl Into<String>>,
+    ) -> BatchSearch<'_> {
+        let mut seen = HashSet::new();
+        let queries = queries
+            .into_iter()
+            .map(Into::into)
+            .filter(|query| seen.insert(query.clone()))
+            .collect();
+        BatchSearch {
+            client: self,
+            queries,
+            concurrency: DEFAULT_BATCH_CONCURRENCY,
+            configure: None,
+        }
+    }
+}
+
+impl BatchSearch<'_> {
+    /// Sets how many queries are sent at the same time. `0` is treated as `1`.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Configures the search of every query, e.g. to set its categories.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use searxng_client::{SearXNGClient, ResponseFormat, Category};
+    /// # let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json);
+    /// let batch = client
+    ///     .search_many(["rust", "go"])
+    ///     .with_search(|search| search.set_categories([Category::It]));
+    /// ```
+    pub fn with_search(
+        mut self,
+        configure: impl for<'a> Fn(SearchBuilder<'a>) -> SearchBuilder<'a> + Send + Sync + 'static,
+    ) -> Self {
+        self.configure = Some(Arc::new(configure));
+        self
+    }
+
+    /// The queries to send, without repetitions, in the order given.
+    pub fn queries(&self) -> &[String] {
+        &self.queries
+    }
+
+    /// Sends the first page of every query, and returns the responses keyed by query.
+    ///
+    /// A failed query does not stop the others; its error is returned in its place.
+    pub async fn send(self) -> BatchResponses {
+        self.send_with_report().await.0
+    }
+
+    /// Like [`send`](Self::send), but also reports the searches spawned.
+    pub async fn send_with_report(self) -> (BatchResponses, TaskReport) {
+        let mut tasks = TaskSet::new(|(_, response): &(String, Result<SearchResponse, Error>)| {
+            response.is_err()
+        });
+        let mut responses = HashMap::with_capacity(self.queries.len());
+        for query in self.queries {
+            if tasks.len() >= self.concurrency
+                && let Some((query, response)) = tasks.join_next().await
+            {
+                responses.insert(query, response);
+            }
+            let client = self.client.clone();
+            let configure = self.configure.clone();
+            tasks.spawn(async move {
+                let mut search = client.search(query.as_str());
+                if let Some(configure) = &configure {
+                    search = configure(search);
+                }
+                let response = search.send().await;
+                (query, response)
+            });
+        }
+        responses.extend(tasks.join_all().await);
+        (responses, tasks.report())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{Category, ResponseFormat};
+    use crate::retry::RetryPolicy;
+    use crate::test::empty_response_json;
+    use crate::transport::MockTransport;
+
+    #[tokio::test]
+    async fn test_search_many() {
+        let body = |query: &str| empty_response_json(query).to_string();
+        let transport = MockTransport::new()
+            .with_fixture("rust", body("rust"))
+            .with_fixture("go", body("go"));
+        let client = SearXNGClient::new("https://searx.example.org", ResponseFormat::Json)
+            .with_retry_policy(RetryPolicy::none())
+            .with_transport(transport.clone());
+
+        let batch = client
+            .search_many(["rust", "go", "rust", "zig"])
+            .with_concurrency(2)
+            .with_search(|search| search.set_categories([Category::It]));
+        assert_eq!(batch.queries(), ["rust", "go", "zig"]);
+        let (responses, report) = batch.send_with_report().await;
+        assert_eq!(responses.len(), 3);
+        assert_eq!(responses["go"].as_ref().unwrap().query, "go");
+        assert!(matches!(responses["zig"], Err(Error::Http { .. })));
+        assert_eq!((report.spawned, report.failed), (3, 1));
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(
+            requests
+                .iter()
+                .all(|r| r.params.api.categories == Some(vec![Category::It]))
+        );
+    }
+}
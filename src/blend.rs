@@ -0,0 +1,283 @@
+//! Blending several relevance signals into one score.
+//!
+//! Merged results carry more than one notion of relevance: the score SearXNG computed,
+//! the number of instances that agreed on the result, and whatever the caller ranks
+//! them by, such as [`Bm25`] term matching or the similarity of embeddings. A
+//! [`ScoreBlender`] set with
+//! [`Aggregator::with_score_blender`](crate::aggregate::Aggregator::with_score_blender)
+//! rescales each signal to `0.0..=1.0` over the merged results and sums them by weight
+//! into [`MergedResult::blended_score`], which the results are then sorted by.
+//!
+//! Since every signal is rescaled, the weights only say how much the signals count
+//! relative to each other, and the same responses always produce the same order.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::aggregate::MergedResult;
+use crate::response::SearchResult;
+use crate::text::clean_snippet;
+
+/// Scores results for a query, e.g. by term matching or semantic similarity.
+///
+/// Scores may be on any scale, as the [`ScoreBlender`] rescales them.
+pub trait Ranker: fmt::Debug + Send + Sync {
+    /// Returns one score per result, higher meaning more relevant to `query`.
+    fn scores(&self, query: &str, results: &[&SearchResult]) -> Vec<f64>;
+}
+
+/// Ranks results by Okapi BM25 over their titles and contents, taking the results
+/// themselves as the corpus.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bm25 {
+    /// How quickly repeated terms stop adding to the score.
+    pub k1: f64,
+    /// How strongly long documents are penalized, from `0.0` to `1.0`.
+    pub b: f64,
+}
+
+impl Default for Bm25 {
+    fn default() -> Self {
+        Bm25 { k1: 1.2, b: 0.75 }
+    }
+}
+
+impl Ranker for Bm25 {
+    fn scores(&self, query: &str, results: &[&SearchResult]) -> Vec<f64> {
+        let documents: Vec<Vec<String>> = results
+            .iter()
+            .map(|result| {
+                let text = format!("{} {}", result.title(), result.content());
+                tokens(&clean_snippet(&text))
+            })
+            .collect();
+        if documents.is_empty() {
+            return Vec::new();
+        }
+        let mut terms = tokens(query);
+        let mut seen = HashSet::new();
+        terms.retain(|term| seen.insert(term.clone()));
+
+        let n = documents.len() as f64;
+        let average_len = documents.iter().map(Vec::len).sum::<usize>() as f64 / n;
+        let idf: Vec<f64> = terms
+            .iter()
+            .map(|term| {
+                let df = documents.iter().filter(|d| d.contains(term)).count() as f64;
+                ((n - df + 0.5) / (df + 0.5)).ln_1p()
+            })
+            .collect();
+        documents
+            .iter()
+            .map(|document| {
+                let norm = if average_len > 0.0 {
+                    1.0 - self.b + self.b * document.len() as f64 / average_len
+                } else {
+                    1.0
+                };
+                terms
+                    .iter()
+                    .zip(&idf)
+                    .map(|(term, idf)| {
+                        let tf = document.iter().filter(|t| *t == term).count() as f64;
+                        idf * tf * (self.k1 + 1.0) / (tf + self.k1 * norm)
+                    })
+                    .sum()
+            })
+            .collect()
+    }
+}
+
+/// Lowercased alphanumeric words.
+fn tokens(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Combines relevance signals into [`MergedResult::blended_score`], see the
+/// [module documentation](self).
+///
+/// By default only the SearXNG score counts, with a weight of `1.0`.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use searxng_client::{SearXNGClient, ResponseFormat};
+/// use searxng_client::aggregate::Aggregator;
+/// use searxng_client::blend::ScoreBlender;
+/// # tokio_test::block_on(async {
+/// let blender = ScoreBlender::new()
+///     .with_searxng_weight(0.5)
+///     .with_consensus_weight(0.3)
+///     .with_bm25_weight(0.2);
+/// let aggregator = Aggregator::new([
+///     SearXNGClient::new("https://searx.be", ResponseFormat::Json),
+///     SearXNGClient::new("https://search.example.org", ResponseFormat::Json),
+/// ])
+/// .with_score_blender(blender);
+/// let response = aggregator.search("rust").await;
+/// # });
+/// ```
+#[derive(Debug, Clone)]
+pub struct ScoreBlender {
+    searxng: f64,
+    consensus: f64,
+    rankers: Vec<(Arc<dyn Ranker>, f64)>,
+}
+
+impl Default for ScoreBlender {
+    fn default() -> Self {
+        ScoreBlender {
+            searxng: 1.0,
+            consensus: 0.0,
+            rankers: Vec::new(),
+        }
+    }
+}
+
+impl ScoreBlender {
+    /// Creates a blender that only counts the SearXNG score.
+    pub fn new() -> Self {
+        ScoreBlender::default()
+    }
+
+    /// Sets the weight of the SearXNG score, merged according to the aggregator's
+    /// [`MergePolicy`](crate::dedup::MergePolicy).
+    pub fn with_searxng_weight(mut self, weight: f64) -> Self {
+        self.searxng = weight;
+        self
+    }
+
+    /// Sets the weight of the number of instances that returned a result.
+    pub fn with_consensus_weight(mut self, weight: f64) -> Self {
+        self.consensus = weight;
+        self
+    }
+
+    /// Adds [`Bm25`] with its default parameters as a signal with `weight`.
+    pub fn with_bm25_weight(self, weight: f64) -> Self {
+        self.with_ranker(Bm25::default(), weight)
+    }
+
+    /// Adds the scores of `ranker` as a signal with `weight`.
+    pub fn with_ranker(mut self, ranker: impl Ranker + 'static, weight: f64) -> Self {
+        self.rankers.push((Arc::new(ranker), weight));
+        self
+    }
+
+    /// Sets the blended score of each of `results` for `query` and sorts them by it,
+    /// highest first.
+    ///
+    /// Results with equal blended scores keep their order.
+    pub fn blend(&self, query: &str, results: &mut [MergedResult]) {
+        let mut blended = vec![0.0; results.len()];
+        let mut add = |weight: f64, scores: Vec<f64>| {
+            for (total, score) in blended.iter_mut().zip(rescale(scores)) {
+                *total += weight * score;
+            }
+        };
+        add(
+            self.searxng,
+            results.iter().map(|r| r.result.score()).collect(),
+        );
+        add(
+            self.consensus,
+            results.iter().map(|r| r.agreement as f64).collect(),
+        );
+        let plain: Vec<&SearchResult> = results.iter().map(|r| &r.result).collect();
+        for (ranker, weight) in &self.rankers {
+            add(*weight, ranker.scores(query, &plain));
+        }
+
+        for (result, blended) in results.iter_mut().zip(blended) {
+            result.blended_score = blended;
+        }
+        results.sort_by(|a, b| b.blended_score.total_cmp(&a.blended_score));
+    }
+}
+
+/// Rescales `scores` linearly to `0.0..=1.0`. If all scores are equal, they all
+/// become `1.0`.
+fn rescale(mut scores: Vec<f64>) -> Vec<f64> {
+    let finite = || scores.iter().copied().filter(|s| s.is_finite());
+    let min = finite().fold(f64::INFINITY, f64::min);
+    let max = finite().fold(f64::NEG_INFINITY, f64::max);
+    for score in &mut scores {
+        *score = if !score.is_finite() {
+            0.0
+        } else if max > min {
+            (*score - min) / (max - min)
+        } else {
+            1.0
+        };
+    }
+    scores
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(url: &str, title: &str, content: &str, score: f64) -> SearchResult {
+        serde_json::from_value(serde_json::json!({
+            "url": url,
+            "template": "default.html",
+            "engine": "duckduckgo",
+            "title": title,
+            "content": content,
+            "img_src": "",
+            "thumbnail": "",
+            "priority": "",
+            "engines": ["duckduckgo"],
+            "positions": [1],
+            "score": score,
+            "category": "general"
+        }))
+        .unwrap()
+    }
+
+    fn merged(url: &str, title: &str, score: f64, agreement: usize) -> MergedResult {
+        MergedResult {
+            result: result(url, title, "", score),
+            agreement,
+            consensus_score: score,
+            blended_score: score,
+        }
+    }
+
+    #[test]
+    fn test_score_blender() {
+        let a = result("https://a.org", "Rust programming language", "", 1.0);
+        let b = result(
+            "https://b.org",
+            "Cooking with cast iron",
+            "rust removal",
+            1.0,
+        );
+        let c = result("https://c.org", "Gardening", "", 1.0);
+        let scores = Bm25::default().scores("rust language", &[&a, &b, &c]);
+        assert!(scores[0] > scores[1] && scores[1] > 0.0);
+        assert_eq!(scores[2], 0.0);
+
+        let mut results = vec![
+            merged("https://a.org", "Gardening", 4.0, 1),
+            merged("https://b.org", "Rust", 2.0, 3),
+            merged("https://c.org", "Rust", 1.0, 1),
+        ];
+        ScoreBlender::new().blend("rust", &mut results);
+        assert_eq!(results[0].blended_score, 1.0);
+        assert_eq!(results[2].blended_score, 0.0);
+
+        ScoreBlender::new()
+            .with_searxng_weight(0.2)
+            .with_consensus_weight(0.5)
+            .with_bm25_weight(0.3)
+            .blend("rust", &mut results);
+        let urls: Vec<_> = results.iter().map(|r| r.result.url().unwrap()).collect();
+        assert_eq!(urls, ["https://b.org", "https://c.org", "https://a.org"]);
+        assert_eq!(rescale(vec![2.0, 2.0, f64::NAN]), [1.0, 1.0, 0.0]);
+    }
+}
@@ -0,0 +1,329 @@
+//! A blocking client, behind the `blocking` feature.
+//!
+//! [`SearXNGClient`] mirrors the async [`SearXNGClient`](crate::SearXNGClient) for
+//! programs without an async runtime. It runs the async client on a private
+//! single-threaded tokio runtime, so every configuration of the async client, from
+//! retries to caching, applies unchanged.
+//!
+//! The blocking methods must not be called, and the last clone of a client must not be
+//! dropped, from within an async runtime, where they panic; use the async client there.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::runtime::{Builder, Runtime};
+
+use crate::client::{self, ResponseFormat, SearchParams};
+use crate::config::InstanceConfig;
+use crate::error::Error;
+use crate::filter::ContentFilter;
+use crate::plan::SearchPlan;
+use crate::project::Field;
+use crate::provenance::SourcedResult;
+use crate::rank::Ranker;
+use crate::resolve::PartialParams;
+use crate::response::{SearchResponse, SearchResult, WebResult};
+
+/// A blocking client for a SearXNG instance, see the [module documentation](self).
+///
+/// Clones share the runtime and, like clones of the async client, the connection pool.
+///
+/// # Panics
+///
+/// Dropping the last clone from within an async runtime panics, as it shuts down the
+/// private runtime.
+///
+/// # Examples
+///
+/// ```no_run
+/// use searxng_client::ResponseFormat;
+/// use searxng_client::blocking::SearXNGClient;
+/// let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json);
+/// for result in client.search("rust").send_get_num(20)? {
+///     println!("{result:?}");
+/// }
+/// # Ok::<(), searxng_client::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct SearXNGClient {
+    inner: client::SearXNGClient,
+    runtime: Arc<Runtime>,
+}
+
+impl SearXNGClient {
+    /// Creates a client with the defaults of
+    /// [`SearXNGClient::new`](crate::SearXNGClient::new).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the runtime cannot be started.
+    pub fn new(base_url: impl Into<String>, format: ResponseFormat) -> Self {
+        SearXNGClient::from_async(client::SearXNGClient::new(base_url, format))
+    }
+
+    /// Wraps a configured async client.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the runtime cannot be started.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use searxng_client::{ResponseFormat, retry::RetryPolicy};
+    /// let client = searxng_client::SearXNGClient::new("https://searx.be", ResponseFormat::Json)
+    ///     .with_retry_policy(RetryPolicy::new(5));
+    /// let client = searxng_client::blocking::SearXNGClient::from_async(client);
+    /// assert_eq!(client.as_async().retry_policy().max_attempts(), 5);
+    /// ```
+    pub fn from_async(inner: client::SearXNGClient) -> Self {
+        let runtime = Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("failed to start the runtime of the blocking client");
+        SearXNGClient {
+            inner,
+            runtime: Arc::new(runtime),
+        }
+    }
+
+    /// The wrapped async client.
+    pub fn as_async(&self) -> &client::SearXNGClient {
+        &self.inner
+    }
+
+    /// The root URL of the instance.
+    pub fn instance_url(&self) -> &str {
+        self.inner.instance_url()
+    }
+
+    /// Fetches the instance's configuration, see
+    /// [`SearXNGClient::config`](crate::SearXNGClient::config).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or the body is not a configuration.
+    ///
+    /// # Panics
+    ///
+    /// Panics when called from within an async runtime.
+    pub fn config(&self) -> Result<InstanceConfig, reqwest::Error> {
+        self.runtime.block_on(self.inner.config())
+    }
+
+    /// Completes a partial query, see
+    /// [`SearXNGClient::autocomplete`](crate::SearXNGClient::autocomplete).
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if the request fails or the body is not a list of suggestions.
+    ///
+    /// # Panics
+    ///
+    /// Panics when called from within an async runtime.
+    pub fn autocomplete(
+        &self,
+        partial_query: &str,
+        backend: Option<&str>,
+    ) -> Result<Vec<String>, Error> {
+        self.runtime
+            .block_on(self.inner.autocomplete(partial_query, backend))
+    }
+
+    /// Starts a new search query.
+    pub fn search(&self, query: impl Into<String>) -> SearchBuilder<'_> {
+        SearchBuilder {
+            inner: self.inner.search(query),
+            runtime: &self.runtime,
+        }
+    }
+}
+
+impl From<client::SearXNGClient> for SearXNGClient {
+    fn from(inner: client::SearXNGClient) -> Self {
+        SearXNGClient::from_async(inner)
+    }
+}
+
+/// A blocking builder for a search, mirroring
+/// [`client::SearchBuilder`](crate::client::SearchBuilder).
+#[derive(Debug, Clone)]
+pub struct SearchBuilder<'a> {
+    inner: client::SearchBuilder<'a>,
+    runtime: &'a Runtime,
+}
+
+impl<'a> SearchBuilder<'a> {
+    pub fn set_params(self, params: SearchParams) -> Self {
+        self.map(|inner| inner.set_params(params))
+    }
+
+    /// See [`client::SearchBuilder::set_preset`].
+    pub fn set_preset(self, preset: PartialParams) -> Self {
+        self.map(|inner| inner.set_preset(preset))
+    }
+
+    pub fn set_pageno(self, pageno: u32) -> Self {
+        self.map(|inner| inner.set_pageno(pageno))
+    }
+
+    /// See [`client::SearchBuilder::set_timeout`].
+    pub fn set_timeout(self, timeout: Duration) -> Self {
+        self.map(|inner| inner.set_timeout(timeout))
+    }
+
+    /// See [`client::SearchBuilder::set_filter`].
+    pub fn set_filter(self, filter: impl ContentFilter + 'static) -> Self {
+        self.map(|inner| inner.set_filter(filter))
+    }
+
+    /// See [`client::SearchBuilder::tolerate_empty_pages`].
+    pub fn tolerate_empty_pages(self, k: u32) -> Self {
+        self.map(|inner| inner.tolerate_empty_pages(k))
+    }
+
+    /// See [`client::SearchBuilder::no_cache`].
+    pub fn no_cache(self) -> Self {
+        self.map(|inner| inner.no_cache())
+    }
+
+    /// See [`client::SearchBuilder::set_max_age`].
+    pub fn set_max_age(self, max_age: Duration) -> Self {
+        self.map(|inner| inner.set_max_age(max_age))
+    }
+
+    /// See [`client::SearchBuilder::keep_duplicates`].
+    pub fn keep_duplicates(self) -> Self {
+        self.map(|inner| inner.keep_duplicates())
+    }
+
+    /// See [`client::SearchBuilder::project`].
+    pub fn project(self, fields: &[Field]) -> Self {
+        self.map(|inner| inner.project(fields))
+    }
+
+    /// See [`client::SearchBuilder::set_ranker`].
+    pub fn set_ranker(self, ranker: impl Ranker + 'static) -> Self {
+        self.map(|inner| inner.set_ranker(ranker))
+    }
+
+    /// Configures the underlying async builder, for options without a blocking
+    /// counterpart.
+    pub fn map(
+        self,
+        f: impl FnOnce(client::SearchBuilder<'a>) -> client::SearchBuilder<'a>,
+    ) -> Self {
+        SearchBuilder {
+            inner: f(self.inner),
+            runtime: self.runtime,
+        }
+    }
+
+    /// Executes the search request, see [`client::SearchBuilder::send`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if the request fails, the instance answers with an error
+    /// status, or the body cannot be decoded.
+    ///
+    /// # Panics
+    ///
+    /// Panics when called from within an async runtime.
+    pub fn send(&self) -> Result<SearchResponse, Error> {
+        self.runtime.block_on(self.inner.send())
+    }
+
+    /// Executes the search request and returns its results as [`WebResult`]s, see
+    /// [`client::SearchBuilder::send_web`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] under the same conditions as [`send`](Self::send).
+    ///
+    /// # Panics
+    ///
+    /// Panics when called from within an async runtime.
+    pub fn send_web(&self) -> Result<Vec<WebResult>, Error> {
+        self.runtime.block_on(self.inner.send_web())
+    }
+
+    /// Fetches pages until at least `num` results are collected, see
+    /// [`client::SearchBuilder::send_get_num`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if any of the underlying requests fail after retries.
+    ///
+    /// # Panics
+    ///
+    /// Panics when called from within an async runtime.
+    pub fn send_get_num(self, num: usize) -> Result<Vec<SearchResult>, Error> {
+        self.runtime.block_on(self.inner.send_get_num(num))
+    }
+
+    /// Like [`send_get_num`](Self::send_get_num), but requests up to `parallelism` pages
+    /// at once, see [`client::SearchBuilder::send_get_num_concurrent`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if any of the underlying requests fail after retries.
+    ///
+    /// # Panics
+    ///
+    /// Panics when called from within an async runtime.
+    pub fn send_get_num_concurrent(
+        self,
+        num: usize,
+        parallelism: usize,
+    ) -> Result<Vec<SearchResult>, Error> {
+        self.runtime
+            .block_on(self.inner.send_get_num_concurrent(num, parallelism))
+    }
+
+    /// Like [`send_get_num`](Self::send_get_num), but returns each result with its
+    /// [`Provenance`](crate::provenance::Provenance).
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if any of the underlying requests fail after retries.
+    ///
+    /// # Panics
+    ///
+    /// Panics when called from within an async runtime.
+    pub fn send_get_num_with_provenance(self, num: usize) -> Result<Vec<SourcedResult>, Error> {
+        self.runtime
+            .block_on(self.inner.send_get_num_with_provenance(num))
+    }
+
+    /// Executes a [`SearchPlan`], see [`client::SearchBuilder::send_plan`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if any of the underlying requests fail after retries.
+    ///
+    /// # Panics
+    ///
+    /// Panics when called from within an async runtime.
+    pub fn send_plan(&self, plan: &mut SearchPlan) -> Result<Vec<SearchResult>, Error> {
+        self.runtime.block_on(self.inner.send_plan(plan))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::retry::RetryPolicy;
+
+    #[test]
+    fn test_blocking_send() {
+        // Nothing listens on port 9, so the request fails to connect.
+        let client = SearXNGClient::from_async(
+            client::SearXNGClient::new("http://127.0.0.1:9/", ResponseFormat::Json)
+                .with_retry_policy(RetryPolicy::none()),
+        );
+        let error = client.search("rust").set_pageno(2).send().unwrap_err();
+        assert!(matches!(error, Error::Transport(_)));
+        let error = client.search("rust").send_get_num(5).unwrap_err();
+        assert!(matches!(error, Error::Transport(_)));
+    }
+}
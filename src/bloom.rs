@@ -0,0 +1,138 @@
+//! A bloom filter, for deduplicating crawls too large to remember every URL.
+//!
+//! A [`BloomFilter`] answers whether an item may have been inserted before, using a fixed
+//! number of bits sized for an expected number of items and false-positive rate. It never
+//! forgets an item, but may report an item it never saw as seen. Crawls use it instead of
+//! an exact set with [`SeenSet::Bloom`](crate::sink::SeenSet::Bloom).
+
+use std::f64::consts::LN_2;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// A set of hashed items that may report false positives, see the
+/// [module documentation](self).
+///
+/// # Examples
+///
+/// ```
+/// use searxng_client::bloom::BloomFilter;
+/// let mut seen = BloomFilter::new(1_000_000, 0.001);
+/// assert!(seen.insert("https://www.rust-lang.org/"));
+/// assert!(!seen.insert("https://www.rust-lang.org/"));
+/// assert!(seen.contains("https://www.rust-lang.org/"));
+/// // About 1.8 MB, however many URLs are inserted.
+/// assert!(seen.bit_len() / 8 < 2_000_000);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BloomFilter {
+    bits: Vec<u64>,
+    bit_len: u64,
+    hashes: u32,
+    len: usize,
+}
+
+impl BloomFilter {
+    /// Creates a filter that reports false positives at about `false_positive_rate` once
+    /// `expected_items` items were inserted.
+    ///
+    /// The rate is clamped between `1e-9` and `0.5`. Inserting more items than expected
+    /// works, but the rate then quickly grows.
+    pub fn new(expected_items: usize, false_positive_rate: f64) -> Self {
+        let n = expected_items.max(1) as f64;
+        let p = false_positive_rate.clamp(1e-9, 0.5);
+        let bit_len = (-n * p.ln() / (LN_2 * LN_2)).ceil().max(64.0) as u64;
+        let hashes = ((bit_len as f64 / n) * LN_2).round().clamp(1.0, 32.0) as u32;
+        BloomFilter {
+            bits: vec![0; bit_len.div_ceil(64) as usize],
+            bit_len,
+            hashes,
+            len: 0,
+        }
+    }
+
+    /// Inserts `item`, returning `true` if it was definitely not inserted before.
+    pub fn insert<T: Hash + ?Sized>(&mut self, item: &T) -> bool {
+        self.insert_hash(hash_of(item))
+    }
+
+    /// Like [`insert`](Self::insert), for an item already hashed to 64 bits.
+    pub fn insert_hash(&mut self, hash: u64) -> bool {
+        let mut new = false;
+        for bit in self.bit_indices(hash) {
+            let (word, mask) = ((bit / 64) as usize, 1 << (bit % 64));
+            new |= self.bits[word] & mask == 0;
+            self.bits[word] |= mask;
+        }
+        self.len += usize::from(new);
+        new
+    }
+
+    /// Returns `true` if `item` may have been inserted before, and `false` if it
+    /// definitely was not.
+    pub fn contains<T: Hash + ?Sized>(&self, item: &T) -> bool {
+        self.contains_hash(hash_of(item))
+    }
+
+    /// Like [`contains`](Self::contains), for an item already hashed to 64 bits.
+    pub fn contains_hash(&self, hash: u64) -> bool {
+        self.bit_indices(hash)
+            .all(|bit| self.bits[(bit / 64) as usize] & (1 << (bit % 64)) != 0)
+    }
+
+    /// The number of items inserted, not counting those reported as already seen.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no item was inserted.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The number of bits of the filter.
+    pub fn bit_len(&self) -> u64 {
+        self.bit_len
+    }
+
+    /// The number of bits set per item.
+    pub fn hash_count(&self) -> u32 {
+        self.hashes
+    }
+
+    /// The bits of `hash`, derived by double hashing.
+    fn bit_indices(&self, hash: u64) -> impl Iterator<Item = u64> + use<> {
+        let h1 = hash;
+        let h2 = mix(hash) | 1;
+        let bit_len = self.bit_len;
+        (0..u64::from(self.hashes)).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % bit_len)
+    }
+}
+
+fn hash_of<T: Hash + ?Sized>(item: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    item.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The SplitMix64 finalizer, deriving a second independent hash.
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_false_positive_rate() {
+        let mut filter = BloomFilter::new(10_000, 0.01);
+        assert_eq!(filter.hash_count(), 7);
+        for i in 0..10_000 {
+            filter.insert(&i);
+        }
+        assert!((0..10_000).all(|i| filter.contains(&i)));
+        let false_positives = (10_000..20_000).filter(|i| filter.contains(i)).count();
+        assert!(false_positives < 200, "{false_positives} false positives");
+    }
+}
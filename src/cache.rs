@@ -0,0 +1,181 @@
+//! Caching of search responses.
+//!
+//! A [`ResponseCache`] set with
+//! [`SearXNGClient::with_cache`](crate::SearXNGClient::with_cache) keeps recent responses
+//! in memory, keyed on the resolved search parameters and page number. Repeating an
+//! identical search within the cache's time to live returns the cached response without
+//! sending a request. [`SearchBuilder::no_cache`](crate::client::SearchBuilder::no_cache)
+//! bypasses the cache for one search, and
+//! [`SearchBuilder::set_max_age`](crate::client::SearchBuilder::set_max_age) bounds the
+//! age of the response it accepts, from the cache and from the instance.
+//!
+//! Responses are cached before content filtering, so searches with different filters
+//! share entries. Responses without results are not cached, since empty pages are often
+//! transient and are requested again by the multi-page searches.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::response::SearchResponse;
+use crate::rt::Instant;
+
+/// A bounded cache of responses, evicting the least recently used entry when full.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use searxng_client::{SearXNGClient, ResponseFormat};
+/// let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json)
+///     .with_cache(256, Duration::from_secs(300));
+/// assert_eq!(client.cache().unwrap().len(), 0);
+/// ```
+#[derive(Debug)]
+pub struct ResponseCache {
+    capacity: usize,
+    ttl: Duration,
+    state: Mutex<CacheState>,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    entries: HashMap<String, Entry>,
+    /// Incremented on every access, to find the least recently used entry.
+    clock: u64,
+}
+
+#[derive(Debug)]
+struct Entry {
+    response: SearchResponse,
+    stored_at: Instant,
+    used_at: u64,
+}
+
+impl ResponseCache {
+    /// Creates a cache of up to `capacity` responses, each kept for `ttl`.
+    ///
+    /// A capacity of zero caches nothing.
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        ResponseCache {
+            capacity,
+            ttl,
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    /// The maximum number of cached responses.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// How long a response stays cached.
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// The number of cached responses, including expired ones not evicted yet.
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    /// Returns `true` if no response is cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes every cached response.
+    pub fn clear(&self) {
+        self.lock().entries.clear();
+    }
+
+    /// Returns the response cached under `key`, unless it expired.
+    pub(crate) fn get(&self, key: &str) -> Option<SearchResponse> {
+        self.get_fresh(key, self.ttl)
+    }
+
+    /// Like [`get`](Self::get), but also skips a response cached `max_age` ago or
+    /// earlier, without evicting it.
+    pub(crate) fn get_fresh(&self, key: &str, max_age: Duration) -> Option<SearchResponse> {
+        let mut state = self.lock();
+        state.clock += 1;
+        let clock = state.clock;
+        let entry = state.entries.get_mut(key)?;
+        if entry.stored_at.elapsed() >= self.ttl {
+            state.entries.remove(key);
+            return None;
+        }
+        if entry.stored_at.elapsed() >= max_age {
+            return None;
+        }
+        entry.used_at = clock;
+        Some(entry.response.clone())
+    }
+
+    /// Caches `response` under `key`, evicting expired entries and then the least
+    /// recently used one if the cache is full.
+    pub(crate) fn put(&self, key: String, response: &SearchResponse) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut state = self.lock();
+        state.clock += 1;
+        let clock = state.clock;
+        if state.entries.len() >= self.capacity && !state.entries.contains_key(&key) {
+            state
+                .entries
+                .retain(|_, entry| entry.stored_at.elapsed() < self.ttl);
+            if state.entries.len() >= self.capacity
+                && let Some(lru) = state
+                    .entries
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.used_at)
+                    .map(|(key, _)| key.clone())
+            {
+                state.entries.remove(&lru);
+            }
+        }
+        state.entries.insert(
+            key,
+            Entry {
+                response: response.clone(),
+                stored_at: Instant::now(),
+                used_at: clock,
+            },
+        );
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CacheState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::empty_response_json;
+
+    fn response(query: &str) -> SearchResponse {
+        serde_json::from_value(empty_response_json(query)).unwrap()
+    }
+
+    #[test]
+    fn test_cache_evicts_least_recently_used() {
+        let cache = ResponseCache::new(2, Duration::from_secs(60));
+        cache.put("a".into(), &response("a"));
+        cache.put("b".into(), &response("b"));
+        assert!(cache.get("a").is_some());
+        cache.put("c".into(), &response("c"));
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get("b").is_none());
+        assert_eq!(cache.get("a").unwrap().query, "a");
+
+        assert!(cache.get_fresh("a", Duration::ZERO).is_none());
+        assert_eq!(cache.len(), 2);
+
+        let expired = ResponseCache::new(2, Duration::ZERO);
+        expired.put("a".into(), &response("a"));
+        assert!(expired.get("a").is_none());
+        assert!(expired.is_empty());
+    }
+}
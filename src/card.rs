@@ -0,0 +1,203 @@
+//! Render-ready view-models for search results.
+//!
+//! SearXNG describes how a result should be displayed through its `template` and
+//! `category` fields. [`ResultCard`] folds that information into a small, uniform
+//! structure so UI code does not have to match on templates itself.
+
+use serde::{Deserialize, Serialize};
+
+use crate::response::{LegacySearchResult, MainSearchResult, ResultRef, SearchResult};
+
+/// The visual kind of a [`ResultCard`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CardKind {
+    /// A regular web page.
+    Web,
+    /// An image result.
+    Image,
+    /// A video result.
+    Video,
+    /// A news article.
+    News,
+    /// A downloadable file (documents, torrents, ...).
+    File,
+}
+
+impl CardKind {
+    /// Derives the kind from a result's `template` and `category`.
+    pub fn from_template(template: &str, category: &str) -> Self {
+        match template.trim_end_matches(".html") {
+            "images" => CardKind::Image,
+            "videos" => CardKind::Video,
+            "torrent" | "files" => CardKind::File,
+            _ => match category {
+                "images" => CardKind::Image,
+                "videos" => CardKind::Video,
+                "news" => CardKind::News,
+                "files" => CardKind::File,
+                _ => CardKind::Web,
+            },
+        }
+    }
+}
+
+/// A render-ready abstraction over a [`SearchResult`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResultCard {
+    /// How the card should be displayed.
+    pub kind: CardKind,
+    /// The main line of the card, usually the title.
+    pub primary_text: String,
+    /// The supporting text, usually the snippet.
+    pub secondary_text: String,
+    /// The link the card points to.
+    pub url: Option<String>,
+    /// An image, thumbnail or embeddable player associated with the result.
+    pub media_url: Option<String>,
+    /// Short labels such as engine names, authors or publication dates.
+    pub badges: Vec<String>,
+}
+
+/// Builds the card of a result of either variant.
+fn card(r: ResultRef<'_>) -> ResultCard {
+    let kind = CardKind::from_template(r.template_name(), r.category());
+    let media_url = match kind {
+        CardKind::Image => r.img_src().or_else(|| r.thumbnail()),
+        CardKind::Video => r.thumbnail().or_else(|| r.player()).or_else(|| r.img_src()),
+        _ => r.thumbnail().or_else(|| r.img_src()),
+    };
+
+    let mut badges: Vec<String> = r.engines().iter().map(ToString::to_string).collect();
+    badges.extend(r.author_name());
+    badges.extend(r.published_date().map(|d| d.date().to_string()));
+    badges.extend(r.views().map(|v| format!("{v} views")));
+
+    ResultCard {
+        kind,
+        primary_text: r.title().to_string(),
+        secondary_text: r.content().to_string(),
+        url: r.url().map(str::to_string),
+        media_url,
+        badges,
+    }
+}
+
+impl From<&MainSearchResult> for ResultCard {
+    fn from(r: &MainSearchResult) -> Self {
+        card(ResultRef::Main(r))
+    }
+}
+
+impl From<&LegacySearchResult> for ResultCard {
+    fn from(r: &LegacySearchResult) -> Self {
+        card(ResultRef::Legacy(r))
+    }
+}
+
+impl From<&SearchResult> for ResultCard {
+    fn from(result: &SearchResult) -> Self {
+        card(result.by_ref())
+    }
+}
+
+impl SearchResult {
+    /// Builds a [`ResultCard`] describing how this result should be rendered.
+    pub fn to_card(&self) -> ResultCard {
+        self.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_card_kind() {
+        assert_eq!(
+            CardKind::from_template("images.html", "general"),
+            CardKind::Image
+        );
+        assert_eq!(
+            CardKind::from_template("torrent.html", "videos"),
+            CardKind::File
+        );
+        assert_eq!(
+            CardKind::from_template("default.html", "news"),
+            CardKind::News
+        );
+        assert_eq!(
+            CardKind::from_template("default.html", "videos"),
+            CardKind::Video
+        );
+        assert_eq!(CardKind::from_template("default.html", "it"), CardKind::Web);
+    }
+
+    #[test]
+    fn test_result_cards() {
+        let legacy: SearchResult = serde_json::from_value(serde_json::json!({
+            "url": "https://example.org/talk",
+            "template": "videos.html",
+            "engine": "peertube",
+            "title": "A talk",
+            "content": "About Rust",
+            "img_src": "https://example.org/still.png",
+            "thumbnail": "",
+            "priority": "",
+            "engines": ["peertube"],
+            "positions": [1],
+            "score": 1.0,
+            "category": "videos",
+            "author": " Ferris ",
+            "publishedDate": "2024-05-01T08:30:00"
+        }))
+        .unwrap();
+        let card = legacy.to_card();
+        assert_eq!(card.kind, CardKind::Video);
+        assert_eq!(card.url.as_deref(), Some("https://example.org/talk"));
+        // Legacy results have no player, so the image is used instead.
+        assert_eq!(
+            card.media_url.as_deref(),
+            Some("https://example.org/still.png")
+        );
+        assert_eq!(card.badges, ["peertube", "Ferris", "2024-05-01"]);
+
+        let main: SearchResult = serde_json::from_value(serde_json::json!({
+            "url": "https://example.org/talk",
+            "engine": null,
+            "parsed_url": null,
+            "template": "videos.html",
+            "title": "A talk",
+            "content": "About Rust",
+            "img_src": "",
+            "iframe_src": "https://example.org/embed/talk",
+            "audio_src": "",
+            "thumbnail": " ",
+            "publishedDate": null,
+            "pubdate": null,
+            "length": null,
+            "views": "1200",
+            "author": "",
+            "metadata": "",
+            "priority": "",
+            "engines": ["youtube", "peertube"],
+            "open_group": false,
+            "close_group": false,
+            "positions": [1, 2],
+            "score": 2.0,
+            "category": "videos"
+        }))
+        .unwrap();
+        let card = main.to_card();
+        assert_eq!(card.kind, CardKind::Video);
+        assert_eq!(
+            card.media_url.as_deref(),
+            Some("https://example.org/embed/talk")
+        );
+        assert_eq!(card.badges, ["youtube", "peertube", "1200 views"]);
+        assert_eq!(
+            (card.primary_text.as_str(), card.secondary_text.as_str()),
+            ("A talk", "About Rust")
+        );
+    }
+}
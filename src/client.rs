@@ -1,29 +1,124 @@
+use chrono::{DateTime, Utc};
+use futures::future::{self, join_all};
+use futures::stream::{self, Stream, StreamExt};
 use language_tags::LanguageTag;
-use reqwest::Client;
+use reqwest::header::{
+    ACCEPT, ACCEPT_ENCODING, ACCEPT_LANGUAGE, CACHE_CONTROL, CONTENT_TYPE, COOKIE, HeaderMap,
+    HeaderName, HeaderValue, RETRY_AFTER, USER_AGENT as USER_AGENT_HEADER,
+};
+use reqwest::{Client, Method, Request, RequestBuilder, Response, StatusCode, Url};
 use serde::{Deserialize, Serialize};
-use serde_with::StringWithSeparator;
 use serde_with::formats::CommaSeparator;
-use std::sync::LazyLock;
+use serde_with::{DeserializeFromStr, SerializeDisplay, StringWithSeparator};
+use std::collections::HashSet;
+use std::convert::Infallible;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+use url::Host;
+use uuid::Uuid;
 
 use crate::SearchResponse;
-use crate::response::SearchResult;
-#[cfg(test)]
-use crate::test::SmartJsonExt;
+use crate::audit::{AuditRecord, AuditSink};
+use crate::autocomplete;
+use crate::cache::ResponseCache;
+use crate::compression::Compression;
+use crate::config::{CapabilityCache, InstanceConfig, ProbeError, ProbeReport, UnsupportedParams};
+use crate::decode::{ResponseDecoder, SerdeDecoder};
+use crate::describe::{EffectiveParam, EffectiveTimeout, SearchDescription};
+use crate::dns::IpPreference;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::dns::PreferenceResolver;
+use crate::endpoint::{self, Endpoint};
+use crate::error::{ConfigError, Error};
+use crate::filter::ContentFilter;
+use crate::formats::{CsvResponse, RssChannel};
+use crate::intern::Interner;
+use crate::latency::{AdaptiveTimeout, LatencyTracker, SlowQuery, SlowQueryHook};
+use crate::oauth::ClientCredentials;
+use crate::parse::{ParseError, ParseMode};
+use crate::plan::{Schedule, SearchPlan};
+use crate::preferences::Preferences;
+use crate::project::{Field, Projection};
+use crate::provenance::{Provenance, SourcedResult};
+use crate::query;
+use crate::rank::{self, Ranker};
+use crate::ratelimit::RateLimiter;
+use crate::redact::{QueryRedaction, query_hash};
+use crate::redirect::{self, Redirect, RedirectPolicy};
+use crate::resolve::{ParamOrigin, ParamsResolver, PartialParams, ResolvedParams};
+use crate::response::{DetailedResponse, Page, SearchOutcome, SearchResult, WebResult};
+use crate::retry::RetryPolicy;
+use crate::rt::{self, Instant};
+use crate::sign::RequestSigner;
+use crate::sink::{CrawlSummary, ResultSink, SeenSet, SeenUrls};
+use crate::timeline::EngineTimeline;
+use crate::transport::{RawResponse, Transport, TransportRequest};
+use crate::weight::EngineWeights;
+
+/// The HTTP client shared by everything that was not given its own.
+pub(crate) static GLOBAL_CLIENT: LazyLock<Client> =
+    LazyLock::new(|| build_http(true, IpPreference::Any, false));
+
+/// Like [`GLOBAL_CLIENT`], but leaves redirects to a [`RedirectPolicy`].
+static NO_REDIRECT_CLIENT: LazyLock<Client> =
+    LazyLock::new(|| build_http(false, IpPreference::Any, false));
+
+/// Builds an HTTP client with the crate's connection settings. With `https_only`,
+/// redirects it follows must lead to [secure](is_secure) URLs.
+#[cfg(not(target_arch = "wasm32"))]
+fn build_http(follow_redirects: bool, ip: IpPreference, https_only: bool) -> Client {
+    use reqwest::redirect::Policy;
 
-static GLOBAL_CLIENT: LazyLock<Client> = LazyLock::new(|| {
-    Client::builder()
+    let mut builder = Client::builder()
         .tcp_keepalive(std::time::Duration::from_secs(3600))
-        .pool_max_idle_per_host(100)
-        .build()
-        .unwrap()
-});
+        .pool_max_idle_per_host(100);
+    if !follow_redirects {
+        builder = builder.redirect(Policy::none());
+    } else if https_only {
+        builder = builder.redirect(Policy::custom(|attempt| {
+            if attempt.previous().len() >= redirect::DEFAULT_MAX_REDIRECTS as usize {
+                attempt.error("too many redirects")
+            } else if !is_secure(attempt.url()) {
+                attempt.error("refusing to follow a redirect to plain HTTP")
+            } else {
+                attempt.follow()
+            }
+        }));
+    }
+    if ip != IpPreference::Any {
+        builder = builder.dns_resolver(PreferenceResolver::new(ip));
+    }
+    builder.build().unwrap()
+}
+
+/// Builds an HTTP client on the browser's `fetch`, which manages connections, follows
+/// redirects and resolves names by itself.
+#[cfg(target_arch = "wasm32")]
+fn build_http(_follow_redirects: bool, _ip: IpPreference, _https_only: bool) -> Client {
+    Client::new()
+}
+
+pub(crate) const USER_AGENT: &str = "searxng-rust-client/0.1";
+
+/// The header carrying idempotency keys, see [`SearXNGClient::with_idempotency_keys`].
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
 
 /// Supported response formats for the SearXNG API.
+///
+/// Only JSON responses decode into a [`SearchResponse`]. CSV and RSS responses are
+/// fetched with [`SearchBuilder::send_csv`] and [`SearchBuilder::send_rss`], whatever
+/// the format of the client.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ResponseFormat {
     /// Standard JSON response format.
     Json,
+    /// Comma-separated values, see [`CsvResponse`].
+    Csv,
+    /// An OpenSearch RSS feed, see [`RssChannel`].
+    Rss,
 }
 
 /// The main entry point for the SearXNG API.
@@ -32,8 +127,41 @@ pub enum ResponseFormat {
 /// It is recommended to reuse the client instance to benefit from connection pooling.
 #[derive(Debug, Clone)]
 pub struct SearXNGClient {
-    base_url: String,
+    root: String,
+    /// The search endpoint URL, built once since it is needed for every request.
+    search_url: Arc<str>,
     format: ResponseFormat,
+    content_filter: Option<Arc<dyn ContentFilter>>,
+    redaction: QueryRedaction,
+    audit: Option<Arc<dyn AuditSink>>,
+    signer: Option<Arc<dyn RequestSigner>>,
+    credentials: Option<Arc<ClientCredentials>>,
+    capabilities: Arc<CapabilityCache>,
+    latency: Arc<LatencyTracker>,
+    first_page_latency: Arc<LatencyTracker>,
+    engine_timeline: Arc<EngineTimeline>,
+    slow_query: Option<SlowQueryHook>,
+    adaptive_timeout: Option<AdaptiveTimeout>,
+    strict_version_check: bool,
+    compression: Compression,
+    compression_fallback: bool,
+    format_downgrade: bool,
+    retry: RetryPolicy,
+    idempotency_keys: bool,
+    method: Method,
+    headers: HeaderMap,
+    redirect: Option<RedirectPolicy>,
+    ip_preference: IpPreference,
+    https_only: bool,
+    rate_limit: Option<Arc<RateLimiter>>,
+    cache: Option<Arc<ResponseCache>>,
+    parse_mode: ParseMode,
+    decoder: Arc<dyn ResponseDecoder>,
+    resolver: ParamsResolver,
+    http: Client,
+    transport: Option<Arc<dyn Transport>>,
+    interner: Option<Arc<Interner>>,
+    engine_weights: Option<Arc<EngineWeights>>,
 }
 
 impl SearXNGClient {
@@ -42,6 +170,8 @@ impl SearXNGClient {
     /// # Arguments
     ///
     /// * `base_url` - The base URL of the SearXNG instance (e.g., "<https://searx.be>").
+    ///   Pass an [`InstanceUrl`](crate::InstanceUrl) to validate it up front; other
+    ///   strings are only normalized, and a malformed URL fails at the first request.
     /// * `format` - The desired [`ResponseFormat`].
     ///
     /// # Examples
@@ -51,158 +181,3179 @@ impl SearXNGClient {
     /// let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json);
     /// ```
     pub fn new(base_url: impl Into<String>, format: ResponseFormat) -> Self {
+        let root = endpoint::normalize_root(&base_url.into());
         SearXNGClient {
-            base_url: format!("{}/search", base_url.into().trim_end_matches('/')),
+            search_url: endpoint::endpoint_url(&root, Endpoint::Search).into(),
+            root,
             format,
+            content_filter: None,
+            redaction: QueryRedaction::None,
+            audit: None,
+            signer: None,
+            credentials: None,
+            capabilities: Arc::default(),
+            latency: Arc::default(),
+            first_page_latency: Arc::default(),
+            engine_timeline: Arc::default(),
+            slow_query: None,
+            adaptive_timeout: None,
+            strict_version_check: false,
+            compression: Compression::default(),
+            compression_fallback: true,
+            format_downgrade: false,
+            retry: RetryPolicy::default(),
+            idempotency_keys: false,
+            method: Method::POST,
+            headers: HeaderMap::from_iter([(
+                USER_AGENT_HEADER,
+                HeaderValue::from_static(USER_AGENT),
+            )]),
+            redirect: None,
+            ip_preference: IpPreference::Any,
+            https_only: false,
+            rate_limit: None,
+            cache: None,
+            parse_mode: ParseMode::default(),
+            decoder: Arc::new(SerdeDecoder),
+            resolver: ParamsResolver::default(),
+            http: GLOBAL_CLIENT.clone(),
+            transport: None,
+            interner: None,
+            engine_weights: None,
         }
     }
 
-    /// Starts a new search query.
+    /// Sets the HTTP client used for every request to the instance.
     ///
-    /// Returns a [`SearchBuilder`] to configure and execute the search.
+    /// By default all `SearXNGClient`s share one connection pool. A custom client can
+    /// configure proxies, TLS settings, cookie stores or default timeouts. `reqwest`
+    /// clients are cheap to clone, so one client can be shared by several instances.
     ///
-    /// # Arguments
+    /// # Examples
     ///
-    /// * `query` - The search terms.
-    pub fn search<'a>(&'a self, query: impl Into<String>) -> SearchBuilder<'a> {
-        SearchBuilder::new(self, query)
+    /// ```
+    /// use searxng_client::{SearXNGClient, ResponseFormat};
+    /// let http = reqwest::Client::builder()
+    ///     .connect_timeout(std::time::Duration::from_secs(2))
+    ///     .build()
+    ///     .unwrap();
+    /// let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json)
+    ///     .with_http_client(http);
+    /// ```
+    pub fn with_http_client(mut self, http: Client) -> Self {
+        self.http = http;
+        self
     }
-}
 
-/// Parameters for a SearXNG search request.
-///
-/// Reference: [SearXNG Search API Documentation](https://docs.searxng.org/dev/search_api.html)
-#[serde_with::serde_as]
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SearchParams {
-    /// The search query string.
-    pub q: String,
-    pub format: ResponseFormat,
-    pub pageno: Option<u32>,
-    #[serde_as(as = "Option<StringWithSeparator<CommaSeparator, String>>")]
-    pub categories: Option<Vec<String>>,
-    #[serde_as(as = "Option<StringWithSeparator<CommaSeparator, String> >")]
-    pub engines: Option<Vec<String>>,
-    pub language: Option<LanguageTag>,
-    pub results_on_new_tab: Option<u32>,
-    pub image_proxy: Option<bool>,
-    pub autocomplete: Option<String>,
-    pub safesearch: Option<u32>,
-    pub theme: Option<String>,
-}
+    /// Follows redirects of search requests according to `policy`, recording each one in
+    /// the [`DetailedResponse`] of [`SearchBuilder::send_detailed`].
+    ///
+    /// By default the HTTP client follows up to ten redirects without reporting them.
+    /// This replaces the HTTP client with one that does not follow redirects itself; a
+    /// client set afterwards with [`with_http_client`](Self::with_http_client) should be
+    /// built with [`reqwest::redirect::Policy::none`] for the policy to apply.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use searxng_client::{SearXNGClient, ResponseFormat};
+    /// use searxng_client::redirect::RedirectPolicy;
+    /// let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json)
+    ///     .with_redirect_policy(RedirectPolicy::Limited(3));
+    /// ```
+    pub fn with_redirect_policy(mut self, policy: RedirectPolicy) -> Self {
+        self.redirect = Some(policy);
+        self.rebuild_http();
+        self
+    }
 
-impl SearchParams {
-    pub fn new(query: impl Into<String>, format: ResponseFormat) -> Self {
-        SearchParams {
-            q: query.into(),
-            format,
-            pageno: None,
-            categories: None,
-            engines: None,
-            language: None,
-            results_on_new_tab: None,
-            image_proxy: None,
-            autocomplete: None,
-            safesearch: None,
-            theme: None,
-        }
+    /// Prefers or forces an IP family for connections to the instance, see
+    /// [`IpPreference`].
+    ///
+    /// Like [`with_redirect_policy`](Self::with_redirect_policy), this replaces the HTTP
+    /// client; a client set afterwards with [`with_http_client`](Self::with_http_client)
+    /// can use a [`PreferenceResolver`] instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use searxng_client::{SearXNGClient, ResponseFormat};
+    /// use searxng_client::dns::IpPreference;
+    /// // The instance's AAAA record points nowhere.
+    /// let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json)
+    ///     .with_ip_preference(IpPreference::V4Only);
+    /// ```
+    pub fn with_ip_preference(mut self, preference: IpPreference) -> Self {
+        self.ip_preference = preference;
+        self.rebuild_http();
+        self
     }
-}
 
-/// A builder for configuring and executing a SearXNG search request.
-#[derive(Debug, Clone)]
-pub struct SearchBuilder<'a> {
-    client: &'a SearXNGClient,
-    params: SearchParams,
-}
+    /// Replaces the HTTP client with one honouring the redirect policy, IP preference
+    /// and HTTPS-only mode.
+    fn rebuild_http(&mut self) {
+        let follow_redirects = self.redirect.is_none();
+        self.http = match (follow_redirects, self.ip_preference, self.https_only) {
+            (true, IpPreference::Any, false) => GLOBAL_CLIENT.clone(),
+            // Redirects are then checked by `send_encoded`.
+            (false, IpPreference::Any, _) => NO_REDIRECT_CLIENT.clone(),
+            (_, ip, https_only) => build_http(follow_redirects, ip, https_only),
+        };
+    }
 
-impl<'a> SearchBuilder<'a> {
-    /// Creates a new `SearchBuilder` with default parameters.
-    pub fn new(client: &'a SearXNGClient, query: impl Into<String>) -> Self {
-        SearchBuilder {
-            client,
-            params: SearchParams::new(query, client.format),
-        }
+    /// The HTTP client used for requests to the instance.
+    pub fn http_client(&self) -> &Client {
+        &self.http
     }
 
-    pub fn set_params(mut self, params: SearchParams) -> Self {
-        self.params = params;
+    /// Shares the engine and category names of every parsed result through `interner`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use searxng_client::{SearXNGClient, ResponseFormat};
+    /// use searxng_client::intern::Interner;
+    /// let interner = Arc::new(Interner::new());
+    /// let a = SearXNGClient::new("https://searx.be", ResponseFormat::Json)
+    ///     .with_interner(interner.clone());
+    /// let b = SearXNGClient::new("https://search.example.org", ResponseFormat::Json)
+    ///     .with_interner(interner);
+    /// ```
+    pub fn with_interner(mut self, interner: Arc<Interner>) -> Self {
+        self.interner = Some(interner);
         self
     }
 
-    pub fn set_pageno(mut self, pageno: u32) -> Self {
-        self.params.pageno = Some(pageno);
+    /// Scales the score of every parsed result by the weight of its engines, see
+    /// [`EngineWeights`].
+    pub fn with_engine_weights(mut self, weights: impl Into<EngineWeights>) -> Self {
+        self.engine_weights = Some(Arc::new(weights.into()));
         self
     }
 
-    /// Executes the search request and returns the full [`SearchResponse`].
+    /// The [`EngineWeights`] applied by this client, if any.
+    pub fn engine_weights(&self) -> Option<&EngineWeights> {
+        self.engine_weights.as_deref()
+    }
+
+    /// Sets a [`ContentFilter`] that is applied to every parsed response.
     ///
-    /// # Errors
+    /// Results rejected by the filter are removed before they are returned, which
+    /// provides a client-side safeguard independent of the engines' `safesearch` support.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use searxng_client::{SearXNGClient, ResponseFormat};
+    /// use searxng_client::filter::SafeContentFilter;
+    /// let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json)
+    ///     .with_content_filter(SafeContentFilter::new().block_domain("example.org"));
+    /// ```
+    pub fn with_content_filter(mut self, filter: impl ContentFilter + 'static) -> Self {
+        self.content_filter = Some(Arc::new(filter));
+        self
+    }
+
+    /// Sets how query strings appear in debug output, logs and errors produced by this
+    /// crate, see [`redact`](crate::redact) for what is covered.
     ///
-    /// Returns a [`reqwest::Error`] if:
-    /// - The network request fails.
-    /// - The server returns a status code that is not 2xx.
-    /// - The response body cannot be parsed as a [`SearchResponse`].
+    /// Requests sent to the instance always carry the original query.
+    pub fn with_query_redaction(mut self, redaction: QueryRedaction) -> Self {
+        self.redaction = redaction;
+        self
+    }
+
+    /// The [`QueryRedaction`] mode used by this client.
+    pub fn query_redaction(&self) -> QueryRedaction {
+        self.redaction
+    }
+
+    /// Sets an [`AuditSink`] receiving an [`AuditRecord`] for every outbound request.
+    pub fn with_audit_log(mut self, sink: impl AuditSink + 'static) -> Self {
+        self.audit = Some(Arc::new(sink));
+        self
+    }
+
+    /// Sets a [`RequestSigner`] called on every outbound request just before it is sent.
     ///
     /// # Examples
     ///
-    /// ```no_run
-    /// # use searxng_client::{SearXNGClient, ResponseFormat};
-    /// # tokio_test::block_on(async {
-    /// # let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json);
-    /// let response = client.search("rust").send().await?;
-    /// # Ok::<(), reqwest::Error>(())
-    /// # });
     /// ```
-    pub async fn send(&self) -> Result<SearchResponse, reqwest::Error> {
-        let resp = GLOBAL_CLIENT
-            .post(&self.client.base_url)
-            .form(&self.params)
-            .header("User-Agent", "searxng-rust-client/0.1")
-            .send()
-            .await?;
+    /// use searxng_client::{SearXNGClient, ResponseFormat};
+    /// use searxng_client::sign::HmacSigner;
+    /// let client = SearXNGClient::new("https://searx.example.org", ResponseFormat::Json)
+    ///     .with_request_signer(HmacSigner::new("secret"));
+    /// ```
+    pub fn with_request_signer(mut self, signer: impl RequestSigner + 'static) -> Self {
+        self.signer = Some(Arc::new(signer));
+        self
+    }
 
-        #[cfg(not(test))]
-        let resp = resp.json::<SearchResponse>().await?;
-        #[cfg(test)]
-        let resp = resp.json_test().await.unwrap();
-        Ok(resp)
+    /// Authenticates every outbound request with an OAuth2 bearer token obtained through
+    /// the client-credentials grant.
+    ///
+    /// Clones of the client share the token. A request rejected with `401 Unauthorized`
+    /// is sent once more with a freshly fetched token. The token is only sent to the
+    /// instance's origin, never along redirects to other hosts or schemes.
+    pub fn with_client_credentials(mut self, credentials: ClientCredentials) -> Self {
+        self.credentials = Some(Arc::new(credentials));
+        self
     }
 
-    async fn send_empty_check_retry(&self) -> Result<Option<Vec<SearchResult>>, reqwest::Error> {
-        for _ in 0..3 {
-            let resp = self.send().await?;
-            if !resp.results.is_empty() {
-                return Ok(Some(resp.results));
-            }
-        }
-        Ok(None)
+    /// Sets how long the result of [`capabilities`](Self::capabilities) is cached.
+    ///
+    /// Defaults to [`DEFAULT_CAPABILITIES_TTL`](crate::config::DEFAULT_CAPABILITIES_TTL).
+    pub fn with_capabilities_ttl(mut self, ttl: Duration) -> Self {
+        self.capabilities = Arc::new(CapabilityCache::new(ttl));
+        self
     }
 
-    /// Executes the search request and automatically fetches results across multiple pages
-    /// until the specified number of results is reached.
+    /// Derives the timeout of search requests from the latencies observed on this client.
     ///
-    /// This is a convenience method that handles pagination and potential empty results.
+    /// An explicit [`SearchBuilder::set_timeout`] takes precedence.
+    pub fn with_adaptive_timeout(mut self, adaptive: AdaptiveTimeout) -> Self {
+        self.adaptive_timeout = Some(adaptive);
+        self
+    }
+
+    /// The latencies of the search requests sent by this client and its clones.
+    pub fn latency(&self) -> &LatencyTracker {
+        &self.latency
+    }
+
+    /// The times to the first page of the queries sent by this client and its clones.
     ///
-    /// # Arguments
+    /// The time to the first page is measured from sending the first page of a query
+    /// until it is decoded, retries included. Pages served from the [`ResponseCache`]
+    /// are not measured.
+    pub fn first_page_latency(&self) -> &LatencyTracker {
+        &self.first_page_latency
+    }
+
+    /// The engines reported as unresponsive to this client and its clones, see
+    /// [`EngineTimeline`].
+    pub fn engine_timeline(&self) -> &EngineTimeline {
+        &self.engine_timeline
+    }
+
+    /// Calls `hook` with every query whose [time to the first page](Self::first_page_latency)
+    /// exceeds `threshold`.
     ///
-    /// * `num` - The minimum number of results to retrieve.
+    /// The hook runs inline before the page is returned, so it should not block for long.
     ///
-    /// # Errors
+    /// # Examples
     ///
-    /// Returns a [`reqwest::Error`] if any of the underlying requests fail after retries.
-    pub async fn send_get_num(mut self, num: usize) -> Result<Vec<SearchResult>, reqwest::Error> {
-        let mut pageno = 1;
-        let mut ret = Vec::with_capacity(num + 50);
-        while ret.len() < num {
-            self.params.pageno = Some(pageno);
-            match self.send_empty_check_retry().await {
-                Ok(Some(results)) => ret.extend(results),
-                Ok(None) => break,
-                Err(_) => continue, // Retry on error
-            }
-            pageno += 1;
+    /// ```
+    /// use std::time::Duration;
+    /// use searxng_client::{SearXNGClient, ResponseFormat};
+    /// let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json)
+    ///     .with_slow_query_hook(Duration::from_secs(3), |slow| {
+    ///         eprintln!("{} took {:?} on {}", slow.query_hash, slow.latency, slow.instance);
+    ///     });
+    /// ```
+    pub fn with_slow_query_hook(
+        mut self,
+        threshold: Duration,
+        hook: impl Fn(&SlowQuery) + Send + Sync + 'static,
+    ) -> Self {
+        self.slow_query = Some(SlowQueryHook::new(threshold, hook));
+        self
+    }
+
+    /// Sets which content encodings are advertised to the instance.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use searxng_client::{SearXNGClient, ResponseFormat};
+    /// use searxng_client::compression::Compression;
+    /// // This instance's reverse proxy corrupts brotli streams.
+    /// let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json)
+    ///     .with_compression(Compression::all().without_brotli());
+    /// ```
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Sets whether a response that fails to decode is requested once more without
+    /// compression. Enabled by default.
+    ///
+    /// The retry only happens if compression was advertised for the failed request.
+    pub fn with_compression_fallback(mut self, fallback: bool) -> Self {
+        self.compression_fallback = fallback;
+        self
+    }
+
+    /// Sets when and how often failed search requests are sent again. Defaults to
+    /// [`RetryPolicy::default`].
+    ///
+    /// The policy applies to every request, and to each page of a paginated search.
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// The [`RetryPolicy`] used by this client.
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry
+    }
+
+    /// Sets whether search requests carry an [`IDEMPOTENCY_KEY_HEADER`] header. Disabled
+    /// by default.
+    ///
+    /// Each logical request, such as one page of a paginated search, gets a random UUID
+    /// that is sent again with every retry of that request. Caching proxies and WAFs in
+    /// front of an instance can use it to recognize retries instead of counting them
+    /// against the client's rate limit.
+    pub fn with_idempotency_keys(mut self, enabled: bool) -> Self {
+        self.idempotency_keys = enabled;
+        self
+    }
+
+    /// Sets the `User-Agent` header sent with every request of this client, replacing the
+    /// crate's default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reqwest::header::HeaderValue;
+    /// use searxng_client::{SearXNGClient, ResponseFormat};
+    /// let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json)
+    ///     .with_user_agent(HeaderValue::from_static("my-crawler/2.0"));
+    /// assert_eq!(client.headers()["user-agent"], "my-crawler/2.0");
+    /// ```
+    pub fn with_user_agent(self, user_agent: HeaderValue) -> Self {
+        self.with_header(USER_AGENT_HEADER, user_agent)
+    }
+
+    /// Adds a header sent with every request of this client, replacing any value it had.
+    ///
+    /// Some instances only let through requests carrying specific headers, such as
+    /// `X-Forwarded-For` or `Accept-Language`, to pass their limiter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reqwest::header::{ACCEPT_LANGUAGE, HeaderValue};
+    /// use searxng_client::{SearXNGClient, ResponseFormat};
+    /// let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json)
+    ///     .with_header(ACCEPT_LANGUAGE, HeaderValue::from_static("de-DE"));
+    /// ```
+    pub fn with_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    /// Sends `preferences` as cookies with every request of this client, so that searches
+    /// run with the same settings as the browser profile they were exported from.
+    ///
+    /// This replaces any `Cookie` header set with [`with_header`](Self::with_header).
+    pub fn with_preferences(self, preferences: Preferences) -> Self {
+        self.with_header(COOKIE, preferences.cookie_header())
+    }
+
+    /// The headers sent with every request of this client, including the `User-Agent`.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Sets the HTTP method of search requests. Defaults to [`Method::POST`].
+    ///
+    /// With [`Method::GET`] the parameters are URL-encoded into the query string of
+    /// `/search`, which some caches and proxies handle better than a form body; any other
+    /// method sends them as a form body.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reqwest::Method;
+    /// use searxng_client::{SearXNGClient, ResponseFormat};
+    /// let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json)
+    ///     .with_request_method(Method::GET);
+    /// assert_eq!(client.request_method(), &Method::GET);
+    /// ```
+    pub fn with_request_method(mut self, method: Method) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// The HTTP method of search requests.
+    pub fn request_method(&self) -> &Method {
+        &self.method
+    }
+
+    /// Throttles every request of this client to at most `requests` per `period`.
+    ///
+    /// Requests beyond the limit wait for their turn, see [`RateLimiter`]. Clones of a
+    /// client share the limit; use [`with_rate_limiter`](Self::with_rate_limiter) to share
+    /// one limit between clients of the same instance.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `requests` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use searxng_client::{SearXNGClient, ResponseFormat};
+    /// let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json)
+    ///     .with_rate_limit(5, Duration::from_secs(10));
+    /// ```
+    pub fn with_rate_limit(self, requests: u32, period: Duration) -> Self {
+        self.with_rate_limiter(Arc::new(RateLimiter::new(requests, period)))
+    }
+
+    /// Throttles every request of this client with a shared [`RateLimiter`].
+    pub fn with_rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limit = Some(limiter);
+        self
+    }
+
+    /// Caches up to `capacity` responses for `ttl`, see [`ResponseCache`].
+    ///
+    /// Identical searches, page by page, are then answered from the cache until their
+    /// response expires. Clones of a client share the cache.
+    pub fn with_cache(mut self, capacity: usize, ttl: Duration) -> Self {
+        self.cache = Some(Arc::new(ResponseCache::new(capacity, ttl)));
+        self
+    }
+
+    /// The client's response cache, if any.
+    pub fn cache(&self) -> Option<&ResponseCache> {
+        self.cache.as_deref()
+    }
+
+    /// Waits for the client's rate limit, if any.
+    async fn throttle(&self) {
+        if let Some(limiter) = &self.rate_limit {
+            limiter.acquire().await;
         }
-        Ok(ret.into_iter().take(num).collect())
+    }
+
+    /// Sets whether [`SearchBuilder::send_web`] asks for CSV once more when a JSON response
+    /// does not match the modelled schema. Disabled by default.
+    ///
+    /// CSV carries fewer fields, but its columns have not changed across SearXNG
+    /// releases, so searches keep working while this crate catches up with a new schema.
+    pub fn with_format_downgrade(mut self, downgrade: bool) -> Self {
+        self.format_downgrade = downgrade;
+        self
+    }
+
+    /// Sets how responses that do not match the modelled schema are handled. Defaults to
+    /// [`ParseMode::Lenient`], so that fields added by newer SearXNG releases end up in
+    /// the results' `extra` maps instead of failing the search.
+    ///
+    /// Strict parsing suits CI runs against a pinned instance, where a new field should
+    /// fail loudly; lenient or salvage parsing suits production.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use searxng_client::{SearXNGClient, ResponseFormat};
+    /// use searxng_client::parse::ParseMode;
+    /// let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json)
+    ///     .with_parse_mode(ParseMode::Salvage);
+    /// assert_eq!(client.parse_mode(), ParseMode::Salvage);
+    /// ```
+    pub fn with_parse_mode(mut self, mode: ParseMode) -> Self {
+        self.parse_mode = mode;
+        self
+    }
+
+    /// The [`ParseMode`] used by this client.
+    pub fn parse_mode(&self) -> ParseMode {
+        self.parse_mode
+    }
+
+    /// Sets the [`ResponseDecoder`] turning response bodies into [`SearchResponse`]s.
+    /// Defaults to [`SerdeDecoder`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use searxng_client::{SearXNGClient, ResponseFormat};
+    /// use searxng_client::decode::DebugDecoder;
+    /// // Dump the bodies this instance sends that fail to parse.
+    /// let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json)
+    ///     .with_decoder(DebugDecoder);
+    /// ```
+    pub fn with_decoder(mut self, decoder: impl ResponseDecoder + 'static) -> Self {
+        self.decoder = Arc::new(decoder);
+        self
+    }
+
+    /// Sends searches with `transport` instead of the HTTP client, e.g. a
+    /// [`MockTransport`](crate::transport::MockTransport) in tests. See the
+    /// [`transport`](crate::transport) module for what still applies.
+    ///
+    /// Other endpoints, such as [`probe`](Self::probe), still use the HTTP client.
+    pub fn with_transport(mut self, transport: impl Transport + 'static) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Makes [`probe`](Self::probe) fail when the instance version is outside the range
+    /// supported by this crate, instead of only reporting a warning.
+    pub fn with_strict_version_check(mut self, strict: bool) -> Self {
+        self.strict_version_check = strict;
+        self
+    }
+
+    /// Sets the parameters used when neither the request nor its preset sets them.
+    ///
+    /// See the [`resolve`](crate::resolve) module for the precedence rules.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use searxng_client::{SearXNGClient, ResponseFormat};
+    /// use searxng_client::SafeSearch;
+    /// use searxng_client::resolve::PartialParams;
+    /// let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json)
+    ///     .with_defaults(PartialParams {
+    ///         safesearch: Some(SafeSearch::Moderate),
+    ///         ..Default::default()
+    ///     });
+    /// ```
+    pub fn with_defaults(mut self, defaults: PartialParams) -> Self {
+        self.resolver.set_defaults(defaults);
+        self
+    }
+
+    /// Sets the engines used by default when `category` is selected.
+    ///
+    /// When a request selects categories but no explicit engines, the default engines of
+    /// all selected categories are sent, mirroring per-category engine configuration in
+    /// SearXNG's `settings.yml`. Requests that set engines explicitly are left untouched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use searxng_client::{SearXNGClient, ResponseFormat};
+    /// let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json)
+    ///     .with_category_engines("images", ["bing images", "duckduckgo images"]);
+    /// ```
+    pub fn with_category_engines(
+        mut self,
+        category: impl Into<String>,
+        engines: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.resolver.set_category_engines(
+            category.into(),
+            engines.into_iter().map(Into::into).collect(),
+        );
+        self
+    }
+
+    /// Returns the resolver merging request parameters with this client's defaults.
+    pub fn params_resolver(&self) -> &ParamsResolver {
+        &self.resolver
+    }
+
+    /// Refuses plain-HTTP instances, for deployments where query terms must never leave
+    /// the machine unencrypted.
+    ///
+    /// Loopback hosts (`localhost`, `127.0.0.0/8`, `::1`) are still allowed, since traffic
+    /// to them never leaves the machine. Redirects to other plain-HTTP URLs are refused:
+    /// they fail with [`Error::Transport`] when the HTTP client follows redirects, and are
+    /// not followed under a [`RedirectPolicy`]. Like
+    /// [`with_redirect_policy`](Self::with_redirect_policy), this replaces the HTTP
+    /// client; a client set afterwards with [`with_http_client`](Self::with_http_client)
+    /// should refuse such redirects itself.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ConfigError`] if the instance URL is not valid, or if it does not use
+    /// HTTPS and does not point at a loopback host.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use searxng_client::{SearXNGClient, ResponseFormat};
+    /// assert!(SearXNGClient::new("http://searx.be", ResponseFormat::Json).with_https_only().is_err());
+    /// assert!(SearXNGClient::new("http://localhost:8080", ResponseFormat::Json).with_https_only().is_ok());
+    /// ```
+    pub fn with_https_only(mut self) -> Result<Self, ConfigError> {
+        let url = Url::parse(&self.root).map_err(|_| ConfigError::InvalidUrl {
+            url: self.root.clone(),
+        })?;
+        if !is_secure(&url) {
+            return Err(ConfigError::InsecureScheme {
+                url: self.root.clone(),
+            });
+        }
+        self.https_only = true;
+        self.rebuild_http();
+        Ok(self)
+    }
+
+    /// The [`ResponseFormat`] requested by this client.
+    pub fn format(&self) -> ResponseFormat {
+        self.format
+    }
+
+    /// The normalized root URL of the instance, always ending with `/`.
+    pub fn instance_url(&self) -> &str {
+        &self.root
+    }
+
+    /// Sends `request` with the bearer token of the [`ClientCredentials`] if they are set
+    /// and the request goes to the instance.
+    ///
+    /// A request rejected with `401 Unauthorized` is sent once more with a new token.
+    async fn execute(&self, request: RequestBuilder) -> reqwest::Result<Response> {
+        let request = request.build()?;
+        let credentials =
+            (self.credentials.as_deref()).filter(|_| self.is_instance_origin(request.url()));
+        let Some(credentials) = credentials else {
+            return self.sign_and_send(request).await;
+        };
+        let retry = request.try_clone();
+        let token = credentials.token().await?;
+        let resp = self.sign_and_send(self.bearer(request, &token)?).await?;
+        match retry {
+            Some(retry) if resp.status() == StatusCode::UNAUTHORIZED => {
+                credentials.invalidate(&token).await;
+                let token = credentials.token().await?;
+                self.sign_and_send(self.bearer(retry, &token)?).await
+            }
+            _ => Ok(resp),
+        }
+    }
+
+    /// Adds the bearer `token` to `request`.
+    fn bearer(&self, request: Request, token: &str) -> reqwest::Result<Request> {
+        RequestBuilder::from_parts(self.http.clone(), request)
+            .bearer_auth(token)
+            .build()
+    }
+
+    /// Sends `request`, signed by the [`RequestSigner`] if one is set and the request
+    /// goes to the instance.
+    async fn sign_and_send(&self, mut request: Request) -> reqwest::Result<Response> {
+        if let Some(signer) = &self.signer
+            && self.is_instance_origin(request.url())
+        {
+            signer.sign(&mut request);
+        }
+        self.http.execute(request).await
+    }
+
+    /// Returns `true` if `url` has the origin of the instance, i.e. its scheme, host and
+    /// port. Requests to other origins, e.g. after a redirect, carry no credentials.
+    fn is_instance_origin(&self, url: &Url) -> bool {
+        Url::parse(&self.root).is_ok_and(|root| root.origin() == url.origin())
+    }
+
+    /// Builds the URL of an instance endpoint from the normalized root.
+    pub(crate) fn endpoint_url(&self, endpoint: Endpoint) -> String {
+        endpoint::endpoint_url(&self.root, endpoint)
+    }
+
+    /// Fetches the instance configuration from its `/config` endpoint.
+    ///
+    /// This always sends a request; use [`capabilities`](Self::capabilities) to benefit
+    /// from the per-client cache.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`reqwest::Error`] if the request fails, the server returns a status code
+    /// that is not 2xx, or the body cannot be parsed as an [`InstanceConfig`].
+    pub async fn config(&self) -> Result<InstanceConfig, reqwest::Error> {
+        self.throttle().await;
+        let request = self
+            .http
+            .get(self.endpoint_url(Endpoint::Config))
+            .headers(self.headers.clone());
+        self.execute(request)
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    /// Fetches query suggestions for `partial_query` from the `/autocompleter` endpoint.
+    ///
+    /// `backend` selects the autocompleter, e.g. `duckduckgo` or `wikipedia`; `None` uses
+    /// the instance default. Instances with autocompletion disabled return no suggestions.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if the request fails, the instance answers with a status code
+    /// that is not 2xx, or the body is not a list of suggestions.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use searxng_client::{SearXNGClient, ResponseFormat};
+    /// # tokio_test::block_on(async {
+    /// # let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json);
+    /// for suggestion in client.autocomplete("rust la", Some("duckduckgo")).await? {
+    ///     println!("{suggestion}");
+    /// }
+    /// # Ok::<(), searxng_client::Error>(())
+    /// # });
+    /// ```
+    pub async fn autocomplete(
+        &self,
+        partial_query: &str,
+        backend: Option<&str>,
+    ) -> Result<Vec<String>, Error> {
+        let mut query = vec![("q", partial_query)];
+        query.extend(backend.map(|backend| ("autocomplete", backend)));
+        self.throttle().await;
+        let request = self
+            .http
+            .post(self.endpoint_url(Endpoint::Autocompleter))
+            .form(&query)
+            .headers(self.headers.clone());
+        let redact = |e| self.redaction.redact_error(e, partial_query);
+        let resp = self.execute(request).await?;
+        let body = check_status(resp).await.map_err(redact)?.bytes().await?;
+        autocomplete::parse_suggestions(&body).map_err(|source| {
+            redact(Error::Decode {
+                source,
+                raw_body: String::from_utf8_lossy(&body).into_owned(),
+            })
+        })
+    }
+
+    /// Returns the instance configuration, probing `/config` only if the cached copy is
+    /// missing or older than the configured time-to-live.
+    ///
+    /// Clones of a client share the same cache.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`reqwest::Error`] if a probe was needed and failed, see [`config`](Self::config).
+    pub async fn capabilities(&self) -> Result<Arc<InstanceConfig>, reqwest::Error> {
+        if let Some(config) = self.capabilities.get() {
+            return Ok(config);
+        }
+        let config = self.config().await?;
+        Ok(self.capabilities.put(config))
+    }
+
+    /// Probes the instance capabilities and checks them for known problems.
+    ///
+    /// Uses the cache of [`capabilities`](Self::capabilities). A [`VersionSkew`] is reported
+    /// in the returned [`Diagnostics`](crate::config::Diagnostics), or returned as an error
+    /// if [`with_strict_version_check`](Self::with_strict_version_check) is enabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ProbeError`] if the probe failed or, in strict mode, if the instance
+    /// version is not supported.
+    ///
+    /// [`VersionSkew`]: crate::config::VersionSkew
+    pub async fn probe(&self) -> Result<ProbeReport, ProbeError> {
+        let config = self.capabilities().await?;
+        let diagnostics = config.diagnostics();
+        if self.strict_version_check
+            && let Some(skew) = config.version_skew()
+        {
+            return Err(ProbeError::VersionSkew(skew));
+        }
+        Ok(ProbeReport {
+            config,
+            diagnostics,
+        })
+    }
+
+    /// Discards the cached instance configuration, forcing the next call to
+    /// [`capabilities`](Self::capabilities) to probe the instance again.
+    pub fn invalidate_capabilities(&self) {
+        self.capabilities.invalidate();
+    }
+
+    /// Starts a new search query.
+    ///
+    /// Returns a [`SearchBuilder`] to configure and execute the search.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The search terms.
+    pub fn search<'a>(&'a self, query: impl Into<String>) -> SearchBuilder<'a> {
+        SearchBuilder::new(self, query)
+    }
+}
+
+/// Parameters for a SearXNG search request.
+///
+/// The parameters are split into the [`ApiParams`] relevant to every API consumer, and
+/// optional [`UiParams`] that only affect how SearXNG's own web interface would render
+/// the results. Both are sent flattened into one request.
+///
+/// Reference: [SearXNG Search API Documentation](https://docs.searxng.org/dev/search_api.html)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchParams {
+    /// The parameters controlling the search itself.
+    #[serde(flatten)]
+    pub api: ApiParams,
+    /// Presentation preferences, for emulating a browser's settings.
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub ui: Option<UiParams>,
+}
+
+impl SearchParams {
+    pub fn new(query: impl Into<String>, format: ResponseFormat) -> Self {
+        SearchParams {
+            api: ApiParams::new(query, format),
+            ui: None,
+        }
+    }
+}
+
+/// The search parameters relevant to API consumers.
+#[serde_with::serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiParams {
+    /// The search query string.
+    pub q: String,
+    pub format: ResponseFormat,
+    pub pageno: Option<u32>,
+    #[serde_as(as = "Option<StringWithSeparator<CommaSeparator, Category>>")]
+    pub categories: Option<Vec<Category>>,
+    #[serde_as(as = "Option<StringWithSeparator<CommaSeparator, String> >")]
+    pub engines: Option<Vec<String>>,
+    pub language: Option<LanguageTag>,
+    pub autocomplete: Option<String>,
+    pub safesearch: Option<SafeSearch>,
+    pub time_range: Option<TimeRange>,
+}
+
+impl ApiParams {
+    pub fn new(query: impl Into<String>, format: ResponseFormat) -> Self {
+        ApiParams {
+            q: query.into(),
+            format,
+            pageno: None,
+            categories: None,
+            engines: None,
+            language: None,
+            autocomplete: None,
+            safesearch: None,
+            time_range: None,
+        }
+    }
+}
+
+/// A search category.
+///
+/// The standard categories of SearXNG have their own variant, and instance-specific ones
+/// are kept as [`Other`](Category::Other). Categories are sent and serialized by name.
+///
+/// Names are parsed ignoring case, and `_` or `-` for spaces. Common aliases, such as
+/// `pictures` for images or `web` for general, and the names of the standard categories
+/// in a few languages are accepted too. Use [`Category::parse_standard`] to reject
+/// anything else, e.g. in user input.
+///
+/// # Examples
+///
+/// ```
+/// use searxng_client::Category;
+/// assert_eq!("social media".parse::<Category>(), Ok(Category::SocialMedia));
+/// assert_eq!("Pictures".parse::<Category>(), Ok(Category::Images));
+/// assert_eq!(Category::from("packages"), Category::Other("packages".into()));
+/// assert_eq!(Category::It.to_string(), "it");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SerializeDisplay, DeserializeFromStr)]
+pub enum Category {
+    General,
+    Images,
+    Videos,
+    News,
+    Map,
+    Music,
+    It,
+    Science,
+    Files,
+    SocialMedia,
+    /// A category that is not one of SearXNG's standard categories.
+    Other(String),
+}
+
+/// Alternative names of the standard categories, lowercased, with spaces for `_` and `-`.
+const CATEGORY_ALIASES: &[(&str, Category)] = &[
+    ("web", Category::General),
+    ("all", Category::General),
+    ("image", Category::Images),
+    ("pictures", Category::Images),
+    ("photos", Category::Images),
+    ("video", Category::Videos),
+    ("maps", Category::Map),
+    ("audio", Category::Music),
+    ("tech", Category::It),
+    ("code", Category::It),
+    ("papers", Category::Science),
+    ("academic", Category::Science),
+    ("file", Category::Files),
+    ("downloads", Category::Files),
+    ("social", Category::SocialMedia),
+    ("socialmedia", Category::SocialMedia),
+    // German
+    ("allgemein", Category::General),
+    ("bilder", Category::Images),
+    ("nachrichten", Category::News),
+    ("karte", Category::Map),
+    ("musik", Category::Music),
+    ("wissenschaft", Category::Science),
+    ("dateien", Category::Files),
+    // French
+    ("général", Category::General),
+    ("vidéos", Category::Videos),
+    ("actualités", Category::News),
+    ("carte", Category::Map),
+    ("musique", Category::Music),
+    ("fichiers", Category::Files),
+    // Spanish
+    ("imágenes", Category::Images),
+    ("noticias", Category::News),
+    ("mapa", Category::Map),
+    ("música", Category::Music),
+    ("ciencia", Category::Science),
+    ("archivos", Category::Files),
+    // Chinese
+    ("网页", Category::General),
+    ("图片", Category::Images),
+    ("视频", Category::Videos),
+    ("新闻", Category::News),
+    ("地图", Category::Map),
+    ("音乐", Category::Music),
+    ("科学", Category::Science),
+    ("文件", Category::Files),
+];
+
+/// A category name that is neither a standard category nor one of its aliases, returned
+/// by [`Category::parse_standard`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "unknown category `{name}`, expected one of: {}",
+    Category::STANDARD.iter().map(Category::as_str).collect::<Vec<_>>().join(", ")
+)]
+pub struct UnknownCategory {
+    /// The name as given.
+    pub name: String,
+}
+
+impl Category {
+    /// The standard categories of SearXNG.
+    pub const STANDARD: &[Category] = &[
+        Category::General,
+        Category::Images,
+        Category::Videos,
+        Category::News,
+        Category::Map,
+        Category::Music,
+        Category::It,
+        Category::Science,
+        Category::Files,
+        Category::SocialMedia,
+    ];
+
+    /// Parses `name` as a standard category or one of its aliases, ignoring case.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`UnknownCategory`] listing the standard categories if `name` is
+    /// neither.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use searxng_client::Category;
+    /// assert_eq!(Category::parse_standard(" Web "), Ok(Category::General));
+    /// assert_eq!(Category::parse_standard("social_media"), Ok(Category::SocialMedia));
+    /// let error = Category::parse_standard("packages").unwrap_err();
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "unknown category `packages`, expected one of: general, images, videos, news, \
+    ///      map, music, it, science, files, social media"
+    /// );
+    /// ```
+    pub fn parse_standard(name: &str) -> Result<Self, UnknownCategory> {
+        let normalized = name.trim().to_lowercase().replace(['_', '-'], " ");
+        Category::STANDARD
+            .iter()
+            .find(|category| category.as_str() == normalized)
+            .or_else(|| {
+                CATEGORY_ALIASES
+                    .iter()
+                    .find(|(alias, _)| *alias == normalized)
+                    .map(|(_, category)| category)
+            })
+            .cloned()
+            .ok_or_else(|| UnknownCategory {
+                name: name.to_string(),
+            })
+    }
+
+    /// The name of the category, as used by SearXNG.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Category::General => "general",
+            Category::Images => "images",
+            Category::Videos => "videos",
+            Category::News => "news",
+            Category::Map => "map",
+            Category::Music => "music",
+            Category::It => "it",
+            Category::Science => "science",
+            Category::Files => "files",
+            Category::SocialMedia => "social media",
+            Category::Other(name) => name,
+        }
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Category {
+    type Err = Infallible;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Ok(Category::parse_standard(name).unwrap_or_else(|e| Category::Other(e.name)))
+    }
+}
+
+impl From<&str> for Category {
+    fn from(name: &str) -> Self {
+        let Ok(category) = name.parse();
+        category
+    }
+}
+
+impl From<String> for Category {
+    fn from(name: String) -> Self {
+        match name.parse() {
+            Ok(Category::Other(_)) => Category::Other(name),
+            Ok(category) => category,
+        }
+    }
+}
+
+/// The safe search level, sent to the engines that support filtering adult content.
+///
+/// Serialized as SearXNG's numeric levels `0`, `1` and `2`.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum SafeSearch {
+    /// No filtering, level `0`.
+    #[default]
+    Off,
+    /// Level `1`.
+    Moderate,
+    /// Level `2`.
+    Strict,
+}
+
+impl SafeSearch {
+    /// The numeric level used by SearXNG.
+    pub fn level(self) -> u8 {
+        match self {
+            SafeSearch::Off => 0,
+            SafeSearch::Moderate => 1,
+            SafeSearch::Strict => 2,
+        }
+    }
+
+    /// The safe search setting of a numeric level, if it is one of `0`, `1` and `2`.
+    pub fn from_level(level: u8) -> Option<Self> {
+        match level {
+            0 => Some(SafeSearch::Off),
+            1 => Some(SafeSearch::Moderate),
+            2 => Some(SafeSearch::Strict),
+            _ => None,
+        }
+    }
+}
+
+impl Serialize for SafeSearch {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(self.level())
+    }
+}
+
+impl<'de> Deserialize<'de> for SafeSearch {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let level = u8::deserialize(deserializer)?;
+        SafeSearch::from_level(level).ok_or_else(|| {
+            serde::de::Error::invalid_value(
+                serde::de::Unexpected::Unsigned(level.into()),
+                &"a safe search level of 0, 1 or 2",
+            )
+        })
+    }
+}
+
+/// How recent results must be, for engines that support it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeRange {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+/// Presentation parameters of SearXNG's web interface.
+///
+/// They have no effect on the results returned through the API.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UiParams {
+    pub results_on_new_tab: Option<u32>,
+    pub image_proxy: Option<bool>,
+    pub theme: Option<String>,
+}
+
+/// A builder for configuring and executing a SearXNG search request.
+#[derive(Clone)]
+pub struct SearchBuilder<'a> {
+    client: &'a SearXNGClient,
+    /// Shared, so that cloning a builder does not copy the parameters.
+    params: Arc<SearchParams>,
+    preset: Option<PartialParams>,
+    timeout: Option<Duration>,
+    filter: Option<Arc<dyn ContentFilter>>,
+    empty_page_tolerance: u32,
+    seen_set: SeenSet,
+    use_cache: bool,
+    dedup_pages: bool,
+    max_age: Option<Duration>,
+    projection: Projection,
+    ranker: Option<Arc<dyn Ranker>>,
+    headers: HeaderMap,
+}
+
+impl fmt::Debug for SearchBuilder<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut params = SearchParams::clone(&self.params);
+        params.api.q = self.client.redaction.apply(&params.api.q).into_owned();
+        f.debug_struct("SearchBuilder")
+            .field("client", &self.client)
+            .field("params", &params)
+            .field("preset", &self.preset)
+            .field("timeout", &self.timeout)
+            .field("filter", &self.filter)
+            .field("empty_page_tolerance", &self.empty_page_tolerance)
+            .field("seen_set", &self.seen_set)
+            .field("use_cache", &self.use_cache)
+            .field("dedup_pages", &self.dedup_pages)
+            .field("max_age", &self.max_age)
+            .field("projection", &self.projection)
+            .field("ranker", &self.ranker)
+            .field("headers", &self.headers)
+            .finish()
+    }
+}
+
+impl<'a> SearchBuilder<'a> {
+    /// Creates a new `SearchBuilder` with default parameters.
+    pub fn new(client: &'a SearXNGClient, query: impl Into<String>) -> Self {
+        SearchBuilder {
+            client,
+            params: Arc::new(SearchParams::new(query, client.format)),
+            preset: None,
+            timeout: None,
+            filter: None,
+            empty_page_tolerance: 0,
+            seen_set: SeenSet::default(),
+            use_cache: true,
+            dedup_pages: true,
+            max_age: None,
+            projection: Projection::all(),
+            ranker: None,
+            headers: HeaderMap::new(),
+        }
+    }
+
+    pub fn set_params(mut self, params: SearchParams) -> Self {
+        self.params = Arc::new(params);
+        self
+    }
+
+    /// Applies a preset, whose parameters take precedence over the client defaults but
+    /// not over the parameters set on this builder.
+    pub fn set_preset(mut self, preset: PartialParams) -> Self {
+        self.preset = Some(preset);
+        self
+    }
+
+    pub fn set_pageno(mut self, pageno: u32) -> Self {
+        Arc::make_mut(&mut self.params).api.pageno = Some(pageno);
+        self
+    }
+
+    /// Restricts the search to the given categories, e.g. `[Category::General, Category::It]`
+    /// or `["general", "it"]`.
+    pub fn set_categories(
+        mut self,
+        categories: impl IntoIterator<Item = impl Into<Category>>,
+    ) -> Self {
+        Arc::make_mut(&mut self.params).api.categories =
+            Some(categories.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Restricts the search to the given engines, e.g. `["duckduckgo", "google"]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use searxng_client::{SearXNGClient, ResponseFormat};
+    /// use searxng_client::SafeSearch;
+    /// # let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json);
+    /// let search = client
+    ///     .search("rust")
+    ///     .set_engines(["duckduckgo", "google"])
+    ///     .set_language("en-US".parse().unwrap())
+    ///     .set_safesearch(SafeSearch::Moderate)
+    ///     .set_theme("simple");
+    /// assert_eq!(search.params().api.engines.as_deref().unwrap(), ["duckduckgo", "google"]);
+    /// ```
+    pub fn set_engines(mut self, engines: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Arc::make_mut(&mut self.params).api.engines =
+            Some(engines.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn set_language(mut self, language: LanguageTag) -> Self {
+        Arc::make_mut(&mut self.params).api.language = Some(language);
+        self
+    }
+
+    /// Sets the autocomplete backend the instance uses, e.g. `"duckduckgo"`.
+    pub fn set_autocomplete(mut self, backend: impl Into<String>) -> Self {
+        Arc::make_mut(&mut self.params).api.autocomplete = Some(backend.into());
+        self
+    }
+
+    pub fn set_safesearch(mut self, level: SafeSearch) -> Self {
+        Arc::make_mut(&mut self.params).api.safesearch = Some(level);
+        self
+    }
+
+    /// Restricts the results to those published within `time_range`.
+    ///
+    /// Engines that do not support time ranges are skipped by the instance.
+    pub fn set_time_range(mut self, time_range: TimeRange) -> Self {
+        Arc::make_mut(&mut self.params).api.time_range = Some(time_range);
+        self
+    }
+
+    pub fn set_results_on_new_tab(mut self, new_tab: bool) -> Self {
+        self.ui().results_on_new_tab = Some(u32::from(new_tab));
+        self
+    }
+
+    pub fn set_image_proxy(mut self, image_proxy: bool) -> Self {
+        self.ui().image_proxy = Some(image_proxy);
+        self
+    }
+
+    pub fn set_theme(mut self, theme: impl Into<String>) -> Self {
+        self.ui().theme = Some(theme.into());
+        self
+    }
+
+    /// The parameters set on this builder, before client defaults and presets apply.
+    pub fn params(&self) -> &SearchParams {
+        &self.params
+    }
+
+    fn ui(&mut self) -> &mut UiParams {
+        Arc::make_mut(&mut self.params)
+            .ui
+            .get_or_insert_with(UiParams::default)
+    }
+
+    /// Sets a timeout for each request sent by this builder.
+    pub fn set_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets a [`ContentFilter`] for this request, applied in addition to the client's
+    /// filter.
+    ///
+    /// When paginating, the filter is applied to every page, so the requested number of
+    /// results counts only the results it keeps.
+    pub fn set_filter(mut self, filter: impl ContentFilter + 'static) -> Self {
+        self.filter = Some(Arc::new(filter));
+        self
+    }
+
+    /// Lets pagination continue across up to `k` consecutive empty pages before concluding
+    /// that the instance ran out of results.
+    ///
+    /// Some instances intermittently return an empty page followed by a populated one.
+    /// By default, the first empty page ends pagination.
+    pub fn tolerate_empty_pages(mut self, k: u32) -> Self {
+        self.empty_page_tolerance = k;
+        self
+    }
+
+    /// Sets how [`send_plan_to_sink`](Self::send_plan_to_sink) and [`watch`](Self::watch)
+    /// remember the results already seen.
+    ///
+    /// Crawls of many millions of results can trade exact deduplication for bounded
+    /// memory with [`SeenSet::Bloom`].
+    pub fn set_seen_set(mut self, seen_set: SeenSet) -> Self {
+        self.seen_set = seen_set;
+        self
+    }
+
+    pub(crate) fn seen_set(&self) -> SeenSet {
+        self.seen_set
+    }
+
+    /// Keeps results whose URL was already returned on an earlier page.
+    ///
+    /// By default [`send_plan`](Self::send_plan), [`send_get_num`](Self::send_get_num)
+    /// and [`stream`](Self::stream) drop them, keeping the first occurrence, so that the
+    /// requested number of results counts unique URLs. Duplicates are tracked as set with
+    /// [`set_seen_set`](Self::set_seen_set).
+    pub fn keep_duplicates(mut self) -> Self {
+        self.dedup_pages = false;
+        self
+    }
+
+    /// Sets the `User-Agent` header of this search, taking precedence over the client's.
+    pub fn set_user_agent(self, user_agent: HeaderValue) -> Self {
+        self.set_header(USER_AGENT_HEADER, user_agent)
+    }
+
+    /// Adds a header to the requests of this search, taking precedence over the headers
+    /// of the client.
+    ///
+    /// Searches with headers of their own bypass the client's [`ResponseCache`], since
+    /// headers like `Accept-Language` change the response.
+    pub fn set_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    /// Bypasses the client's [`ResponseCache`]: responses are neither read from nor
+    /// written to it.
+    pub fn no_cache(mut self) -> Self {
+        self.use_cache = false;
+        self
+    }
+
+    /// Accepts responses up to `max_age` old.
+    ///
+    /// Responses cached longer ago than `max_age` are fetched again, even if the client's
+    /// [`ResponseCache`] still keeps them. The requests carry a `Cache-Control: max-age`
+    /// header with the same bound, so that a caching proxy in front of the instance, or
+    /// an instance caching its results, can answer from its cache too.
+    pub fn set_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Keeps only `fields` of the results, see the [`project`](crate::project) module.
+    ///
+    /// Filters see the results before they are projected. Pages are deduplicated by URL,
+    /// so without [`Field::Url`] they are not deduplicated.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use searxng_client::{SearXNGClient, ResponseFormat};
+    /// use searxng_client::project::Field;
+    /// # tokio_test::block_on(async {
+    /// # let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json);
+    /// let results = client
+    ///     .search("rust")
+    ///     .project(&[Field::Url, Field::Title, Field::Snippet])
+    ///     .send_get_num(1_000)
+    ///     .await?;
+    /// # Ok::<(), searxng_client::Error>(())
+    /// # });
+    /// ```
+    pub fn project(mut self, fields: &[Field]) -> Self {
+        self.projection = Projection::new(fields);
+        self
+    }
+
+    /// Reorders results by `ranker`, see the [`rank`](crate::rank) module.
+    ///
+    /// Applies to the responses of [`send`](Self::send) and to the results collected by
+    /// [`send_plan`](Self::send_plan) and [`send_get_num`](Self::send_get_num), which
+    /// are ranked together before being cut to the target. Streamed results, and pages
+    /// written to a sink, stay in page order.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use searxng_client::{SearXNGClient, ResponseFormat};
+    /// use searxng_client::rank::ReciprocalRankFusion;
+    /// # tokio_test::block_on(async {
+    /// # let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json);
+    /// let results = client
+    ///     .search("rust")
+    ///     .set_ranker(ReciprocalRankFusion::default())
+    ///     .send_get_num(50)
+    ///     .await?;
+    /// # Ok::<(), searxng_client::Error>(())
+    /// # });
+    /// ```
+    pub fn set_ranker(mut self, ranker: impl Ranker + 'static) -> Self {
+        self.ranker = Some(Arc::new(ranker));
+        self
+    }
+
+    /// Applies CJK-friendly preprocessing to the query.
+    ///
+    /// Full-width ASCII characters are normalized to half-width, and when the query is
+    /// predominantly Chinese, Japanese or Korean and no language has been set, the
+    /// `language` parameter is set accordingly.
+    pub fn preprocess_cjk(mut self) -> Self {
+        let api = &mut Arc::make_mut(&mut self.params).api;
+        api.q = query::normalize_width(&api.q);
+        if api.language.is_none() {
+            api.language = query::dominant_cjk_script(&api.q).map(|s| s.language());
+        }
+        self
+    }
+
+    /// Executes the search request and returns the full [`SearchResponse`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if:
+    /// - The network request fails or times out ([`Error::Transport`]).
+    /// - The server rate limits the client ([`Error::RateLimited`]) or returns another
+    ///   status code that is not 2xx ([`Error::Http`]).
+    /// - The response body cannot be parsed as a [`SearchResponse`] under the client's
+    ///   [`ParseMode`] ([`Error::Decode`]).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use searxng_client::{SearXNGClient, ResponseFormat};
+    /// # tokio_test::block_on(async {
+    /// # let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json);
+    /// let response = client.search("rust").send().await?;
+    /// # Ok::<(), searxng_client::Error>(())
+    /// # });
+    /// ```
+    pub async fn send(&self) -> Result<SearchResponse, Error> {
+        let send = self.send_params(&self.params);
+        #[cfg(feature = "tracing")]
+        let send = tracing::Instrument::instrument(
+            send,
+            tracing::info_span!(
+                "searxng.send",
+                query = %self.client.redaction.apply(&self.params.api.q),
+                instance = %self.client.root,
+                pageno = self.params.api.pageno,
+            ),
+        );
+        send.await
+    }
+
+    /// Executes the search request and classifies the response as a [`SearchOutcome`].
+    ///
+    /// Unlike [`send`](Self::send), a response in which every engine failed is returned as
+    /// [`SearchOutcome::Degraded`] instead of an empty success.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] under the same conditions as [`send`](Self::send).
+    pub async fn send_outcome(&self) -> Result<SearchOutcome, Error> {
+        Ok(self.send().await?.into_outcome())
+    }
+
+    /// Executes the search request and returns the response with how it was received:
+    /// its final URL and status, and the redirects followed under the client's
+    /// [`RedirectPolicy`].
+    ///
+    /// The request bypasses the client's [`ResponseCache`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] under the same conditions as [`send`](Self::send). A redirect
+    /// that is not followed is returned as [`Error::Http`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use searxng_client::{SearXNGClient, ResponseFormat};
+    /// use searxng_client::redirect::RedirectPolicy;
+    /// # tokio_test::block_on(async {
+    /// let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json)
+    ///     .with_redirect_policy(RedirectPolicy::Limited(5));
+    /// let detailed = client.search("rust").send_detailed().await?;
+    /// for redirect in &detailed.redirects {
+    ///     println!("{} -> {} ({})", redirect.from, redirect.to, redirect.status);
+    /// }
+    /// # Ok::<(), searxng_client::Error>(())
+    /// # });
+    /// ```
+    pub async fn send_detailed(&self) -> Result<DetailedResponse, Error> {
+        let form = EncodedForm::new(self.resolve(&self.params).params);
+        let (mut response, exchange) = self
+            .send_decoded(&form, self.params.api.pageno, |body| {
+                self.decode_search(body)
+            })
+            .await?;
+        self.retain_allowed(&mut response.results);
+        Ok(DetailedResponse {
+            response,
+            url: exchange.url,
+            status: exchange.status,
+            redirects: exchange.redirects,
+            elapsed: exchange.elapsed,
+        })
+    }
+
+    /// Executes the search request and returns its results as [`WebResult`]s.
+    ///
+    /// With [`SearXNGClient::with_format_downgrade`], a JSON response that is valid JSON
+    /// of an unexpected shape is requested again as CSV. Content filters cannot be applied
+    /// to CSV rows, so searches with a [`ContentFilter`], set on the client or on this
+    /// builder, are not downgraded and return the decode error instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] under the same conditions as [`send`](Self::send).
+    pub async fn send_web(&self) -> Result<Vec<WebResult>, Error> {
+        let filtered = self.client.content_filter.is_some() || self.filter.is_some();
+        match self.send().await {
+            Ok(resp) => Ok(resp.web_results()),
+            Err(Error::Decode { source, .. })
+                if self.client.format_downgrade && !filtered && !source.is_syntax() =>
+            {
+                Ok(self.send_csv().await?.web_results())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Executes the search request, asking for a CSV response.
+    ///
+    /// Content filters only apply to [`SearchResult`]s and are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] under the same conditions as [`send`](Self::send).
+    pub async fn send_csv(&self) -> Result<CsvResponse, Error> {
+        self.send_in_format(ResponseFormat::Csv, CsvResponse::parse)
+            .await
+    }
+
+    /// Executes the search request, asking for an RSS response.
+    ///
+    /// Content filters only apply to [`SearchResult`]s and are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] under the same conditions as [`send`](Self::send).
+    pub async fn send_rss(&self) -> Result<RssChannel, Error> {
+        self.send_in_format(ResponseFormat::Rss, RssChannel::parse)
+            .await
+    }
+
+    /// Checks the engines and categories of this search, with the client defaults merged
+    /// in, against the instance's [`capabilities`](SearXNGClient::capabilities).
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`reqwest::Error`] if the capabilities had to be probed and the probe
+    /// failed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use searxng_client::{SearXNGClient, ResponseFormat};
+    /// # tokio_test::block_on(async {
+    /// # let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json);
+    /// let search = client.search("rust");
+    /// let unsupported = search.validate().await?;
+    /// if !unsupported.is_empty() {
+    ///     eprintln!("{unsupported}");
+    /// }
+    /// # Ok::<(), reqwest::Error>(())
+    /// # });
+    /// ```
+    pub async fn validate(&self) -> Result<UnsupportedParams, reqwest::Error> {
+        let config = self.client.capabilities().await?;
+        Ok(config.unsupported(&self.resolve(&self.params).params))
+    }
+
+    /// Summarizes the parameters this builder will send, with the client-level defaults
+    /// merged in.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use searxng_client::{SearXNGClient, ResponseFormat};
+    /// use searxng_client::client::SearchParams;
+    /// use searxng_client::resolve::ParamOrigin;
+    /// let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json)
+    ///     .with_category_engines("science", ["arxiv", "pubmed"]);
+    /// let mut params = SearchParams::new("rust", ResponseFormat::Json);
+    /// params.api.categories = Some(vec!["science".into()]);
+    /// let description = client.search("").set_params(params).describe();
+    ///
+    /// let engines = description.get("engines").unwrap();
+    /// assert_eq!(engines.value, "arxiv,pubmed");
+    /// assert_eq!(engines.origin, ParamOrigin::ClientDefault);
+    /// println!("{description}");
+    /// ```
+    pub fn describe(&self) -> SearchDescription {
+        let resolved = self.resolve(&self.params);
+        let mut fields = serde_json::to_value(&resolved.params).unwrap_or_default();
+        if let Some(q) = fields.get_mut("q") {
+            *q = self.client.redaction.apply(&self.params.api.q).into();
+        }
+        let params = match fields {
+            serde_json::Value::Object(map) => map
+                .into_iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(name, value)| EffectiveParam {
+                    origin: resolved.origin(&name).unwrap_or(ParamOrigin::Request),
+                    value: match value {
+                        serde_json::Value::String(s) => s,
+                        other => other.to_string(),
+                    },
+                    name,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        SearchDescription {
+            method: self.client.method.clone(),
+            endpoint: self.client.endpoint_url(Endpoint::Search),
+            params,
+            timeout: self.effective_timeout(),
+        }
+    }
+
+    /// Resolves `params` against this builder's preset and the client defaults.
+    pub fn resolve(&self, params: &SearchParams) -> ResolvedParams {
+        self.client.resolver.resolve(params, self.preset.as_ref())
+    }
+
+    fn effective_timeout(&self) -> Option<EffectiveTimeout> {
+        let (duration, origin) = match (self.timeout, self.client.adaptive_timeout) {
+            (Some(timeout), _) => (timeout, ParamOrigin::Request),
+            (None, Some(adaptive)) => (
+                adaptive.timeout(&self.client.latency),
+                ParamOrigin::ClientDefault,
+            ),
+            (None, None) => return None,
+        };
+        Some(EffectiveTimeout { duration, origin })
+    }
+
+    async fn send_params(&self, params: &SearchParams) -> Result<SearchResponse, Error> {
+        let mut resp = self.send_unfiltered(params).await?;
+        self.retain_allowed(&mut resp.results);
+        if let Some(ranker) = &self.ranker {
+            resp.rerank(ranker.as_ref());
+        }
+        Ok(resp)
+    }
+
+    /// Removes the results rejected by the client's or this request's filter, and
+    /// projects the others.
+    fn retain_allowed(&self, results: &mut Vec<SearchResult>) {
+        for filter in [&self.client.content_filter, &self.filter]
+            .into_iter()
+            .flatten()
+        {
+            results.retain(|r| filter.allows(r));
+        }
+        if self.projection != Projection::all() {
+            results.iter_mut().for_each(|r| self.projection.apply(r));
+        }
+    }
+
+    async fn send_unfiltered(&self, params: &SearchParams) -> Result<SearchResponse, Error> {
+        let form = EncodedForm::new(self.resolve(params).params);
+        self.send_form(&form, params.api.pageno, true).await
+    }
+
+    /// Sends a page, from the cache if possible. The time to a `first_page` fetched from
+    /// the instance is recorded.
+    async fn send_form(
+        &self,
+        form: &EncodedForm,
+        pageno: Option<u32>,
+        first_page: bool,
+    ) -> Result<SearchResponse, Error> {
+        let cache =
+            (self.client.cache.as_deref()).filter(|_| self.use_cache && self.headers.is_empty());
+        let key = cache.map(|_| form.body(pageno));
+        let cached = match (cache, &key, self.max_age) {
+            (Some(cache), Some(key), Some(max_age)) => cache.get_fresh(key, max_age),
+            (Some(cache), Some(key), None) => cache.get(key),
+            _ => None,
+        };
+        if let Some(resp) = cached {
+            #[cfg(feature = "tracing")]
+            trace_page(pageno, &resp, true);
+            return Ok(resp);
+        }
+        let started = Instant::now();
+        let (resp, _) = self
+            .send_decoded(form, pageno, |body| self.decode_search(body))
+            .await?;
+        if first_page {
+            self.record_first_page(form, started.elapsed());
+        }
+        self.client
+            .engine_timeline
+            .record(&self.client.root, &form.params.api.q, pageno, &resp);
+        #[cfg(feature = "tracing")]
+        trace_page(pageno, &resp, false);
+        // Empty pages are requested again, see `send_empty_check_retry`.
+        if let (Some(cache), Some(key)) = (cache, key)
+            && !resp.results.is_empty()
+        {
+            cache.put(key, &resp);
+        }
+        Ok(resp)
+    }
+
+    fn record_first_page(&self, form: &EncodedForm, latency: Duration) {
+        self.client.first_page_latency.record(latency);
+        if let Some(hook) = &self.client.slow_query {
+            hook.observe(&SlowQuery {
+                query_hash: query_hash(&form.params.api.q),
+                instance: self.client.root.clone(),
+                latency,
+            });
+        }
+    }
+
+    /// Decodes a JSON body with the client's decoder, interner and engine weights.
+    fn decode_search(&self, body: &[u8]) -> Result<SearchResponse, ParseError> {
+        let mut resp = self.client.decoder.decode(body, self.client.parse_mode)?;
+        if let Some(interner) = &self.client.interner {
+            interner.intern_response(&mut resp);
+        }
+        if let Some(weights) = &self.client.engine_weights {
+            weights.apply_response(&mut resp);
+        }
+        Ok(resp)
+    }
+
+    /// Sends the request in `format` instead of the builder's format, decoding the body
+    /// with `decode`.
+    async fn send_in_format<T>(
+        &self,
+        format: ResponseFormat,
+        decode: impl Fn(&[u8]) -> Result<T, ParseError>,
+    ) -> Result<T, Error> {
+        let mut params = self.resolve(&self.params).params;
+        params.api.format = format;
+        let pageno = params.api.pageno;
+        let (resp, _) = self
+            .send_decoded(&EncodedForm::new(params), pageno, decode)
+            .await?;
+        Ok(resp)
+    }
+
+    /// Sends the request and decodes the body, retrying according to the client's
+    /// [`RetryPolicy`].
+    async fn send_decoded<T>(
+        &self,
+        form: &EncodedForm,
+        pageno: Option<u32>,
+        decode: impl Fn(&[u8]) -> Result<T, ParseError>,
+    ) -> Result<(T, Exchange), Error> {
+        let policy = &self.client.retry;
+        let key = self
+            .client
+            .idempotency_keys
+            .then(|| Uuid::new_v4().to_string());
+        let mut attempt = 1;
+        loop {
+            match self
+                .send_decoded_once(form, pageno, key.as_deref(), &decode)
+                .await
+            {
+                Err(e) if attempt < policy.max_attempts() && policy.is_retryable(&e) => {
+                    let Some(wait) = policy.wait(attempt, &e) else {
+                        let query = &form.params.api.q;
+                        return Err(self.client.redaction.redact_error(e, query));
+                    };
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(
+                        attempt,
+                        pageno,
+                        error = %e,
+                        wait_ms = wait.as_millis() as u64,
+                        "retrying search request"
+                    );
+                    rt::sleep(wait).await;
+                    attempt += 1;
+                }
+                resp => {
+                    let query = &form.params.api.q;
+                    return resp.map_err(|e| self.client.redaction.redact_error(e, query));
+                }
+            }
+        }
+    }
+
+    async fn send_decoded_once<T>(
+        &self,
+        form: &EncodedForm,
+        pageno: Option<u32>,
+        idempotency_key: Option<&str>,
+        decode: impl Fn(&[u8]) -> Result<T, ParseError>,
+    ) -> Result<(T, Exchange), Error> {
+        let compression = self.client.compression;
+        match self
+            .send_encoded(form, pageno, compression, idempotency_key, &decode)
+            .await
+        {
+            // Broken compressed streams surface as decode errors or as invalid JSON.
+            // Valid JSON of an unexpected shape is not retried.
+            Err(e)
+                if is_corrupt_body(&e)
+                    && self.client.compression_fallback
+                    && compression.is_enabled() =>
+            {
+                self.send_encoded(form, pageno, Compression::none(), idempotency_key, &decode)
+                    .await
+            }
+            resp => resp,
+        }
+    }
+
+    async fn send_encoded<T>(
+        &self,
+        form: &EncodedForm,
+        pageno: Option<u32>,
+        compression: Compression,
+        idempotency_key: Option<&str>,
+        decode: impl Fn(&[u8]) -> Result<T, ParseError>,
+    ) -> Result<(T, Exchange), Error> {
+        self.client.throttle().await;
+        if let Some(transport) = &self.client.transport {
+            return (self.send_transport(
+                transport.as_ref(),
+                form,
+                pageno,
+                idempotency_key,
+                decode,
+            ))
+            .await;
+        }
+        let timestamp = Utc::now();
+        let started = Instant::now();
+        let body = form.body(pageno);
+        let mut url = Url::parse(&self.client.search_url).expect("the search URL is valid");
+        let mut method = self.client.method.clone();
+        let mut redirects = Vec::new();
+        let mut visited = HashSet::from([(method.clone(), url.clone())]);
+        let resp = loop {
+            // Hops to other origins only keep the headers that do not identify the client.
+            let (client_headers, headers) = if self.client.is_instance_origin(&url) {
+                (self.client.headers.clone(), self.headers.clone())
+            } else {
+                (
+                    neutral_headers(&self.client.headers),
+                    neutral_headers(&self.headers),
+                )
+            };
+            let mut request = match method {
+                Method::GET => {
+                    let mut url = url.clone();
+                    let query = match url.query() {
+                        Some(query) if !query.is_empty() => format!("{query}&{body}"),
+                        _ => body.clone(),
+                    };
+                    url.set_query(Some(&query));
+                    self.client.http.get(url)
+                }
+                _ => self
+                    .client
+                    .http
+                    .request(method.clone(), url.clone())
+                    .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(body.clone()),
+            }
+            .headers(client_headers)
+            .headers(headers)
+            .header(ACCEPT_ENCODING, compression.accept_encoding());
+            // The timeout covers every hop.
+            if let Some(timeout) = self.effective_timeout() {
+                request = request.timeout(timeout.duration.saturating_sub(started.elapsed()));
+            }
+            if let Some(key) = idempotency_key {
+                request = request.header(IDEMPOTENCY_KEY_HEADER, key);
+            }
+            if let Some(max_age) = self.max_age {
+                request = request.header(CACHE_CONTROL, format!("max-age={}", max_age.as_secs()));
+            }
+            let resp = self.client.execute(request).await;
+            // Redirects are only followed here if the HTTP client does not follow them.
+            if let (Some(policy), Ok(resp)) = (self.client.redirect, &resp)
+                && resp.status().is_redirection()
+                && policy.allows(redirects.len())
+                && let Some(to) = redirect::location(&url, resp.headers())
+                && !redirect::is_downgrade(&url, &to)
+                && (!self.client.https_only || is_secure(&to))
+                && visited.insert((
+                    redirect::redirected_method(&method, resp.status()),
+                    to.clone(),
+                ))
+            {
+                redirects.push(Redirect {
+                    from: url.to_string(),
+                    to: to.to_string(),
+                    status: resp.status().as_u16(),
+                });
+                method = redirect::redirected_method(&method, resp.status());
+                url = to;
+                continue;
+            }
+            break resp;
+        };
+        let resp = resp.map_err(without_query);
+        if resp.is_ok() {
+            self.client.latency.record(started.elapsed());
+        }
+        // With `GET`, or after a redirect, the query of the URL carries the search terms.
+        url.set_query(None);
+        #[cfg(feature = "tracing")]
+        match &resp {
+            Ok(resp) => tracing::debug!(
+                url = %url,
+                pageno,
+                status = resp.status().as_u16(),
+                latency_ms = started.elapsed().as_millis() as u64,
+                "search response received"
+            ),
+            Err(e) => tracing::debug!(
+                url = %url,
+                pageno,
+                error = %e,
+                latency_ms = started.elapsed().as_millis() as u64,
+                "search request failed"
+            ),
+        }
+
+        if let Some(audit) = &self.client.audit {
+            audit.record(&AuditRecord::new(
+                timestamp,
+                url.as_str(),
+                &form.params,
+                pageno,
+                resp.as_ref().ok().map(|r| r.status().as_u16()),
+                started.elapsed(),
+                resp.as_ref().err().map(ToString::to_string),
+            ));
+        }
+        let resp = check_status(resp?).await?;
+        let (final_url, status) = (resp.url().to_string(), resp.status().as_u16());
+        let body = resp.bytes().await.map_err(without_query)?;
+        let value = decode(&body).map_err(|source| Error::Decode {
+            source,
+            raw_body: String::from_utf8_lossy(&body).into_owned(),
+        })?;
+        let exchange = Exchange {
+            url: final_url,
+            status,
+            redirects,
+            elapsed: started.elapsed(),
+        };
+        Ok((value, exchange))
+    }
+
+    /// Like [`send_encoded`](Self::send_encoded), but with the client's [`Transport`].
+    async fn send_transport<T>(
+        &self,
+        transport: &dyn Transport,
+        form: &EncodedForm,
+        pageno: Option<u32>,
+        idempotency_key: Option<&str>,
+        decode: impl Fn(&[u8]) -> Result<T, ParseError>,
+    ) -> Result<(T, Exchange), Error> {
+        let timestamp = Utc::now();
+        let started = Instant::now();
+        let mut headers = self.client.headers.clone();
+        headers.extend(self.headers.clone());
+        if let Some(key) = idempotency_key.and_then(|key| HeaderValue::from_str(key).ok()) {
+            headers.insert(IDEMPOTENCY_KEY_HEADER, key);
+        }
+        if let Some(max_age) = self.max_age {
+            let value = format!("max-age={}", max_age.as_secs());
+            headers.insert(CACHE_CONTROL, value.try_into().expect("the value is ASCII"));
+        }
+        let mut params = form.params.clone();
+        params.api.pageno = pageno;
+        let request = TransportRequest {
+            url: self.client.search_url.to_string(),
+            method: self.client.method.clone(),
+            headers,
+            body: form.body(pageno),
+            params,
+        };
+        let resp = transport.execute(request).await;
+        if resp.is_ok() {
+            self.client.latency.record(started.elapsed());
+        }
+        if let Some(audit) = &self.client.audit {
+            audit.record(&AuditRecord::new(
+                timestamp,
+                &self.client.search_url,
+                &form.params,
+                pageno,
+                resp.as_ref().ok().map(|r| r.status.as_u16()),
+                started.elapsed(),
+                resp.as_ref().err().map(ToString::to_string),
+            ));
+        }
+        let resp = check_raw_status(resp?)?;
+        let value = decode(&resp.body).map_err(|source| Error::Decode {
+            source,
+            raw_body: String::from_utf8_lossy(&resp.body).into_owned(),
+        })?;
+        let exchange = Exchange {
+            url: self.client.search_url.to_string(),
+            status: resp.status.as_u16(),
+            redirects: Vec::new(),
+            elapsed: started.elapsed(),
+        };
+        Ok((value, exchange))
+    }
+
+    /// Fetches page `pageno`, and returns it with its filtered results.
+    ///
+    /// An empty page is requested again, up to the maximum number of attempts of the
+    /// client's [`RetryPolicy`]. Returns `None` if the page stayed empty, i.e. the
+    /// instance ran out of results.
+    async fn send_empty_check_retry(
+        &self,
+        form: &EncodedForm,
+        pageno: u32,
+        first_page: bool,
+    ) -> Result<Option<(SearchResponse, Provenance)>, Error> {
+        for attempt in 0..self.client.retry.max_attempts() {
+            // Emptiness is checked before filtering: a page whose results were all
+            // filtered out does not mean the instance ran out of results.
+            let mut resp = self
+                .send_form(form, Some(pageno), first_page && attempt == 0)
+                .await?;
+            if !resp.results.is_empty() {
+                self.retain_allowed(&mut resp.results);
+                let provenance = Provenance {
+                    instance: self.client.root.clone(),
+                    pageno,
+                    query: form.params.api.q.clone(),
+                    fetched_at: Utc::now(),
+                };
+                return Ok(Some((resp, provenance)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Executes the search request and automatically fetches results across multiple pages
+    /// until the specified number of results is reached.
+    ///
+    /// This is a convenience method that handles pagination and potential empty results.
+    /// It is equivalent to running a sequential [`SearchPlan::for_target`].
+    ///
+    /// # Arguments
+    ///
+    /// * `num` - The minimum number of results to retrieve.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if any of the underlying requests fail after retries.
+    pub async fn send_get_num(self, num: usize) -> Result<Vec<SearchResult>, Error> {
+        self.send_plan(&mut SearchPlan::for_target(num)).await
+    }
+
+    /// Like [`send_get_num`](Self::send_get_num), but requests up to `parallelism` pages
+    /// at once.
+    ///
+    /// Results are still returned in page order. This is equivalent to running a
+    /// [`SearchPlan::for_target`] with [`Schedule::Concurrent`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if any of the underlying requests fail after retries.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use searxng_client::{SearXNGClient, ResponseFormat};
+    /// # tokio_test::block_on(async {
+    /// # let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json);
+    /// let results = client.search("rust").send_get_num_concurrent(100, 4).await?;
+    /// # Ok::<(), searxng_client::Error>(())
+    /// # });
+    /// ```
+    pub async fn send_get_num_concurrent(
+        self,
+        num: usize,
+        parallelism: usize,
+    ) -> Result<Vec<SearchResult>, Error> {
+        let mut plan = SearchPlan::for_target(num).with_schedule(Schedule::Concurrent(parallelism));
+        self.send_plan(&mut plan).await
+    }
+
+    /// Like [`send_get_num`](Self::send_get_num), but returns each result with its [`Provenance`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if any of the underlying requests fail after retries.
+    pub async fn send_get_num_with_provenance(
+        self,
+        num: usize,
+    ) -> Result<Vec<SourcedResult>, Error> {
+        self.send_plan_with_provenance(&mut SearchPlan::for_target(num))
+            .await
+    }
+
+    /// Fetches pages one after the other, yielding each with its page-level metadata.
+    ///
+    /// Unlike [`send_plan`](Self::send_plan), which flattens the results, this keeps the
+    /// suggestions, answers and unresponsive engines of every page. The stream starts at
+    /// the builder's page, or the first one, and ends after the instance runs out of
+    /// results or a page fails. Empty pages tolerated by
+    /// [`tolerate_empty_pages`](Self::tolerate_empty_pages) are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use searxng_client::{SearXNGClient, ResponseFormat};
+    /// use futures::StreamExt;
+    /// # tokio_test::block_on(async {
+    /// # let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json);
+    /// let search = client.search("rust");
+    /// let mut pages = std::pin::pin!(search.pages().take(3));
+    /// while let Some(page) = pages.next().await {
+    ///     let page = page?;
+    ///     let failed = page.response_meta.unresponsive_engines.len();
+    ///     println!("page {}: {} results, {failed} engines failed", page.pageno, page.results.len());
+    /// }
+    /// # Ok::<(), searxng_client::Error>(())
+    /// # });
+    /// ```
+    pub fn pages(&self) -> impl Stream<Item = Result<Page, Error>> + '_ {
+        let form = EncodedForm::new(self.resolve(&self.params).params);
+        let first = self.params.api.pageno.unwrap_or(1);
+        stream::unfold(Some((form, first)), move |state| async move {
+            let (form, mut pageno) = state?;
+            let mut empty_pages = 0;
+            loop {
+                match self
+                    .send_empty_check_retry(&form, pageno, pageno == first)
+                    .await
+                {
+                    Ok(Some((resp, _))) => {
+                        let (results, response_meta) = resp.into_parts();
+                        let page = Page {
+                            pageno,
+                            results,
+                            response_meta,
+                        };
+                        return Some((Ok(page), Some((form, pageno + 1))));
+                    }
+                    Ok(None) if empty_pages < self.empty_page_tolerance => {
+                        empty_pages += 1;
+                        pageno += 1;
+                    }
+                    Ok(None) => return None,
+                    Err(e) => return Some((Err(e), None)),
+                }
+            }
+        })
+    }
+
+    /// Streams the results of every page, fetching each page only once the results of
+    /// the previous one have been consumed.
+    ///
+    /// Pages are fetched as by [`pages`](Self::pages): the stream ends when the instance
+    /// runs out of results, or after yielding the error a page failed with. Dropping the
+    /// stream stops pagination.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use searxng_client::{SearXNGClient, ResponseFormat};
+    /// use futures::{StreamExt, TryStreamExt};
+    /// # tokio_test::block_on(async {
+    /// # let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json);
+    /// let search = client.search("rust");
+    /// let first: Vec<_> = search.stream().take(25).try_collect().await?;
+    /// # Ok::<(), searxng_client::Error>(())
+    /// # });
+    /// ```
+    pub fn stream(&self) -> impl Stream<Item = Result<SearchResult, Error>> + '_ {
+        let mut seen = self.dedup_pages.then(|| SeenUrls::new(self.seen_set));
+        self.pages()
+            .flat_map(|page| {
+                let results: Vec<Result<SearchResult, Error>> = match page {
+                    Ok(page) => page.results.into_iter().map(Ok).collect(),
+                    Err(e) => vec![Err(e)],
+                };
+                stream::iter(results)
+            })
+            .filter(move |result| {
+                let new = match (result, &mut seen) {
+                    (Ok(result), Some(seen)) => seen.insert(result),
+                    _ => true,
+                };
+                future::ready(new)
+            })
+    }
+
+    /// Executes a [`SearchPlan`], fetching pages until its target is reached or the
+    /// instance runs out of results.
+    ///
+    /// Pages are requested in batches sized by [`SearchPlan::next_batch_size`] and their
+    /// results are appended in page order, dropping results whose URL an earlier page
+    /// already returned unless [`keep_duplicates`](Self::keep_duplicates) is set. Failed pages are retried according to the
+    /// client's [`RetryPolicy`]; a page that still fails ends pagination with its error.
+    /// Pagination stops at the first empty page, unless more are tolerated with
+    /// [`tolerate_empty_pages`](Self::tolerate_empty_pages).
+    /// The plan records every processed page, so it can be inspected afterwards.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if any of the underlying requests fail after retries.
+    pub async fn send_plan(&self, plan: &mut SearchPlan) -> Result<Vec<SearchResult>, Error> {
+        let results = self.send_plan_with_provenance(plan).await?;
+        Ok(results.into_iter().map(SearchResult::from).collect())
+    }
+
+    /// Like [`send_plan`](Self::send_plan), but returns each result with its [`Provenance`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if any of the underlying requests fail after retries.
+    pub async fn send_plan_with_provenance(
+        &self,
+        plan: &mut SearchPlan,
+    ) -> Result<Vec<SourcedResult>, Error> {
+        let num = plan.target();
+        let mut ret = Vec::with_capacity(num + 50);
+        let mut seen = self.dedup_pages.then(|| SeenUrls::new(self.seen_set));
+        self.run_plan(plan, |mut results, provenance| {
+            if let Some(seen) = &mut seen {
+                results.retain(|result| seen.insert(result));
+            }
+            ret.extend(results.into_iter().map(|result| SourcedResult {
+                result,
+                provenance: provenance.clone(),
+            }));
+            Ok(ret.len())
+        })
+        .await?;
+        if let Some(ranker) = &self.ranker {
+            rank::sort_by_ranker(ranker.as_ref(), &self.params.api.q, &mut ret, |r| &r.result);
+        }
+        ret.truncate(num);
+        Ok(ret)
+    }
+
+    /// Executes a [`SearchPlan`] like [`send_plan`](Self::send_plan), but writes each page
+    /// to `sink` as soon as it arrives instead of collecting the results.
+    ///
+    /// Duplicates are dropped across pages, and only a hash of each written URL is kept
+    /// in memory, or a fixed-size bloom filter with [`set_seen_set`](Self::set_seen_set),
+    /// so deep crawls run in bounded memory. The plan's target counts the results written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if any of the underlying requests fail after retries, or
+    /// [`Error::Sink`] if the sink fails. Pages written before the failure stay written.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use searxng_client::{SearXNGClient, ResponseFormat, SearchPlan};
+    /// use searxng_client::sink::NdjsonSink;
+    /// # tokio_test::block_on(async {
+    /// # let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json);
+    /// let file = std::io::BufWriter::new(std::fs::File::create("rust.ndjson").unwrap());
+    /// let mut sink = NdjsonSink::new(file);
+    /// let summary = client
+    ///     .search("rust")
+    ///     .send_plan_to_sink(&mut SearchPlan::for_target(50_000), &mut sink)
+    ///     .await?;
+    /// println!("{} results written", summary.written);
+    /// # Ok::<(), searxng_client::Error>(())
+    /// # });
+    /// ```
+    pub async fn send_plan_to_sink(
+        &self,
+        plan: &mut SearchPlan,
+        mut sink: impl ResultSink,
+    ) -> Result<CrawlSummary, Error> {
+        let num = plan.target();
+        let mut seen = SeenUrls::new(self.seen_set);
+        let mut summary = CrawlSummary::default();
+        self.run_plan(plan, |results, provenance| {
+            summary.pages += 1;
+            let page: Vec<SourcedResult> = results
+                .into_iter()
+                .filter(|result| {
+                    let new = seen.insert(result);
+                    summary.duplicates += usize::from(!new);
+                    new
+                })
+                .take(num.saturating_sub(summary.written))
+                .map(|result| SourcedResult {
+                    result,
+                    provenance: provenance.clone(),
+                })
+                .collect();
+            sink.write_page(&page).map_err(Error::Sink)?;
+            summary.written += page.len();
+            Ok(summary.written)
+        })
+        .await?;
+        Ok(summary)
+    }
+
+    /// Fetches the pages of `plan`, handing each page to `on_page` in order until the
+    /// number of results it reports collected reaches the plan's target.
+    async fn run_plan(
+        &self,
+        plan: &mut SearchPlan,
+        on_page: impl FnMut(Vec<SearchResult>, &Provenance) -> Result<usize, Error>,
+    ) -> Result<(), Error> {
+        #[cfg(feature = "tracing")]
+        let span = tracing::info_span!(
+            "searxng.send_plan",
+            query = %self.client.redaction.apply(&self.params.api.q),
+            instance = %self.client.root,
+            target = plan.target(),
+        );
+        let run = self.run_plan_pages(plan, on_page);
+        #[cfg(feature = "tracing")]
+        let run = tracing::Instrument::instrument(run, span);
+        run.await
+    }
+
+    async fn run_plan_pages(
+        &self,
+        plan: &mut SearchPlan,
+        mut on_page: impl FnMut(Vec<SearchResult>, &Provenance) -> Result<usize, Error>,
+    ) -> Result<(), Error> {
+        let num = plan.target();
+        let mut pageno = 1;
+        let mut collected = 0;
+        let mut empty_pages = 0;
+        // Only `pageno` changes from one page to the next, so the parameters are resolved
+        // and encoded once.
+        let form = EncodedForm::new(self.resolve(&self.params).params);
+        'pages: while collected < num {
+            let batch = plan.next_batch_size(collected);
+            let pages = join_all(
+                (pageno..pageno + batch).map(|p| self.send_empty_check_retry(&form, p, p == 1)),
+            )
+            .await;
+            for page in pages {
+                match page {
+                    Ok(Some((resp, provenance))) => {
+                        plan.observe(pageno, resp.results.len());
+                        empty_pages = 0;
+                        collected = on_page(resp.results, &provenance)?;
+                    }
+                    Ok(None) => {
+                        plan.observe(pageno, 0);
+                        empty_pages += 1;
+                        if empty_pages > self.empty_page_tolerance {
+                            break 'pages;
+                        }
+                    }
+                    // The request was already retried according to the client's policy.
+                    Err(e) => return Err(e),
+                }
+                pageno += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Emits the event for a page of results.
+#[cfg(feature = "tracing")]
+fn trace_page(pageno: Option<u32>, resp: &SearchResponse, cached: bool) {
+    tracing::debug!(
+        pageno,
+        results = resp.results.len(),
+        unresponsive_engines = resp.unresponsive_engines.len(),
+        cached,
+        "search page decoded"
+    );
+}
+
+/// How a response was received, for [`DetailedResponse`].
+struct Exchange {
+    url: String,
+    status: u16,
+    redirects: Vec<Redirect>,
+    elapsed: Duration,
+}
+
+/// Resolved parameters, form-encoded once and reused for every page requested with them.
+struct EncodedForm {
+    /// The resolved parameters, without `pageno`.
+    params: SearchParams,
+    /// `params`, form-encoded.
+    base: String,
+}
+
+impl EncodedForm {
+    fn new(mut params: SearchParams) -> Self {
+        params.api.pageno = None;
+        // Every parameter is a scalar or a comma-separated list, which always encodes.
+        let base = serde_urlencoded::to_string(&params)
+            .expect("search parameters are always form-encodable");
+        EncodedForm { params, base }
+    }
+
+    /// The request body for page `pageno`.
+    fn body(&self, pageno: Option<u32>) -> String {
+        match pageno {
+            Some(pageno) => format!("{}&pageno={pageno}", self.base),
+            None => self.base.clone(),
+        }
+    }
+}
+
+/// Returns `true` if `e` suggests the body was damaged in transit, e.g. by a broken
+/// compressed stream.
+fn is_corrupt_body(e: &Error) -> bool {
+    match e {
+        Error::Transport(e) => e.is_decode(),
+        Error::Decode { source, .. } => source.is_syntax(),
+        _ => false,
+    }
+}
+
+/// Removes the query from the URL of `e`, so that the search terms of `GET` requests do
+/// not show in its message.
+fn without_query(e: reqwest::Error) -> reqwest::Error {
+    match e.url() {
+        Some(url) if url.query().is_some() => {
+            let mut url = url.clone();
+            url.set_query(None);
+            e.with_url(url)
+        }
+        _ => e,
+    }
+}
+
+/// The headers of `headers` that do not identify the client, sent along redirects to
+/// other origins.
+fn neutral_headers(headers: &HeaderMap) -> HeaderMap {
+    let mut neutral = HeaderMap::new();
+    for name in [USER_AGENT_HEADER, ACCEPT, ACCEPT_LANGUAGE] {
+        for value in headers.get_all(&name) {
+            neutral.append(name.clone(), value.clone());
+        }
+    }
+    neutral
+}
+
+/// Returns `true` if requests to `url` are encrypted or never leave the machine, i.e.
+/// it uses HTTPS or points at a loopback host.
+fn is_secure(url: &Url) -> bool {
+    let is_loopback = match url.host() {
+        Some(Host::Domain(domain)) => domain.eq_ignore_ascii_case("localhost"),
+        Some(Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(Host::Ipv6(ip)) => ip.is_loopback(),
+        None => false,
+    };
+    url.scheme() == "https" || is_loopback
+}
+
+/// Turns 429 and other non-2xx responses into errors.
+async fn check_status(resp: Response) -> Result<Response, Error> {
+    let status = resp.status();
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        return Err(Error::RateLimited {
+            retry_after: retry_after(resp.headers()),
+        });
+    }
+    if !status.is_success() {
+        let body = resp.text().await.unwrap_or_default();
+        return Err(Error::Http { status, body });
+    }
+    Ok(resp)
+}
+
+/// Like [`check_status`], for responses received by a [`Transport`].
+fn check_raw_status(resp: RawResponse) -> Result<RawResponse, Error> {
+    if resp.status == StatusCode::TOO_MANY_REQUESTS {
+        return Err(Error::RateLimited {
+            retry_after: retry_after(&resp.headers),
+        });
+    }
+    if !resp.status.is_success() {
+        return Err(Error::Http {
+            status: resp.status,
+            body: String::from_utf8_lossy(&resp.body).into_owned(),
+        });
+    }
+    Ok(resp)
+}
+
+/// Parses a `Retry-After` header, given either in seconds or as an HTTP date.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some((date.to_utc() - Utc::now()).to_std().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::{empty_response_json, http_response, serve, serve_with};
+
+    fn form_body(params: &SearchParams) -> String {
+        let request = GLOBAL_CLIENT
+            .post("http://localhost/search")
+            .form(params)
+            .build()
+            .unwrap();
+        let body = request.body().and_then(|b| b.as_bytes()).unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+        headers.insert(RETRY_AFTER, "120".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(120)));
+        headers.insert(
+            RETRY_AFTER,
+            "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+        );
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_typed_params_encoding() {
+        let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json);
+        let search = client.search("rust").set_time_range(TimeRange::Month);
+        assert_eq!(
+            form_body(search.params()),
+            "q=rust&format=json&time_range=month"
+        );
+
+        let search = client
+            .search("rust")
+            .set_categories([Category::SocialMedia, "packages".into()])
+            .set_safesearch(SafeSearch::Strict);
+        assert_eq!(
+            form_body(search.params()),
+            "q=rust&format=json&categories=social+media%2Cpackages&safesearch=2"
+        );
+        let json = serde_json::to_value(search.params()).unwrap();
+        assert_eq!(json["safesearch"], 2);
+        let params: SearchParams = serde_json::from_value(json).unwrap();
+        assert_eq!(
+            params.api.categories.unwrap(),
+            [Category::SocialMedia, Category::Other("packages".into())]
+        );
+        assert!(serde_json::from_str::<SafeSearch>("3").is_err());
+    }
+
+    #[test]
+    fn test_category_aliases() {
+        let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json);
+        let search = client.search("rust").set_categories([
+            "Pictures",
+            "WEB",
+            "Social-Media",
+            "Nachrichten",
+            "Packages",
+        ]);
+        assert_eq!(
+            form_body(search.params()),
+            "q=rust&format=json&categories=images%2Cgeneral%2Csocial+media%2Cnews%2CPackages"
+        );
+        let mut json = serde_json::to_value(search.params()).unwrap();
+        json["categories"] = "it,视频".into();
+        let params: SearchParams = serde_json::from_value(json).unwrap();
+        assert_eq!(
+            params.api.categories.unwrap(),
+            [Category::It, Category::Videos]
+        );
+        let error = Category::parse_standard("packages").unwrap_err();
+        assert_eq!(error.name, "packages");
+        assert!(error.to_string().contains("general, images, videos"));
+    }
+
+    #[test]
+    fn test_params_form_encoding() {
+        let mut params = SearchParams::new("rust lang", ResponseFormat::Json);
+        params.api.engines = Some(vec!["duckduckgo".into(), "brave".into()]);
+        assert_eq!(
+            form_body(&params),
+            "q=rust+lang&format=json&engines=duckduckgo%2Cbrave"
+        );
+
+        params.ui = Some(UiParams {
+            theme: Some("simple".into()),
+            ..Default::default()
+        });
+        assert_eq!(
+            form_body(&params),
+            "q=rust+lang&format=json&engines=duckduckgo%2Cbrave&theme=simple"
+        );
+
+        params.api.pageno = Some(3);
+        let form = EncodedForm::new(params.clone());
+        params.api.pageno = None;
+        assert_eq!(
+            form.body(Some(3)),
+            format!("{}&pageno=3", form_body(&params))
+        );
+        assert_eq!(form.body(None), form_body(&params));
+    }
+
+    #[tokio::test]
+    async fn test_streams_end_after_failure() {
+        // Nothing listens on port 9, so the first page fails to connect.
+        let client = SearXNGClient::new("http://127.0.0.1:9/", ResponseFormat::Json)
+            .with_retry_policy(RetryPolicy::none());
+        let search = client.search("rust");
+        let pages: Vec<_> = search.pages().collect().await;
+        assert!(matches!(pages[..], [Err(Error::Transport(_))]));
+        let results: Vec<_> = search.stream().collect().await;
+        assert!(matches!(results[..], [Err(Error::Transport(_))]));
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_key_reused_across_retries() {
+        let unavailable = http_response("503 Service Unavailable", "", "");
+        let (root, server) = serve(vec![unavailable.clone(), unavailable]).await;
+        let client = SearXNGClient::new(root, ResponseFormat::Json)
+            .with_retry_policy(
+                RetryPolicy::new(2).with_backoff(crate::retry::Backoff::Fixed(Duration::ZERO)),
+            )
+            .with_idempotency_keys(true);
+        let error = client.search("rust").send().await.unwrap_err();
+        assert_eq!(error.status(), Some(StatusCode::SERVICE_UNAVAILABLE));
+        let keys: Vec<String> = server
+            .await
+            .unwrap()
+            .iter()
+            .filter_map(|request| {
+                let request = request.to_ascii_lowercase();
+                let key = request
+                    .lines()
+                    .find_map(|line| line.strip_prefix("idempotency-key: "))?;
+                Some(key.to_string())
+            })
+            .collect();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[0], keys[1]);
+        assert!(Uuid::parse_str(&keys[0]).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_client_credentials_refresh_on_unauthorized() {
+        let body = &empty_response_json("rust").to_string();
+        let token = |token| {
+            let body = format!(r#"{{"access_token": "{token}", "token_type": "Bearer"}}"#);
+            http_response("200 OK", "content-type: application/json\r\n", &body)
+        };
+        let (root, server) = serve(vec![
+            token("first"),
+            http_response("401 Unauthorized", "", ""),
+            token("second"),
+            http_response("200 OK", "content-type: application/json\r\n", body),
+        ])
+        .await;
+        let credentials =
+            ClientCredentials::new(format!("{root}token").parse().unwrap(), "id", "secret");
+        let client = SearXNGClient::new(&root, ResponseFormat::Json)
+            .with_retry_policy(RetryPolicy::none())
+            .with_client_credentials(credentials);
+        assert_eq!(client.search("rust").send().await.unwrap().query, "rust");
+
+        let requests: Vec<String> = server
+            .await
+            .unwrap()
+            .iter()
+            .map(|request| request.to_ascii_lowercase())
+            .collect();
+        assert!(requests[0].starts_with("post /token"));
+        assert!(requests[0].contains("grant_type=client_credentials"));
+        assert!(requests[1].contains("authorization: bearer first"));
+        assert!(requests[3].contains("authorization: bearer second"));
+    }
+
+    #[tokio::test]
+    async fn test_client_credentials_without_representable_expiry() {
+        let body = &empty_response_json("rust").to_string();
+        let token = r#"{"access_token": "t", "token_type": "Bearer",
+            "expires_in": 18446744073709551615}"#;
+        let (root, server) = serve(vec![
+            http_response("200 OK", "content-type: application/json\r\n", token),
+            http_response("200 OK", "content-type: application/json\r\n", body),
+            http_response("200 OK", "content-type: application/json\r\n", body),
+        ])
+        .await;
+        let credentials =
+            ClientCredentials::new(format!("{root}token").parse().unwrap(), "id", "secret");
+        let client = SearXNGClient::new(&root, ResponseFormat::Json)
+            .with_retry_policy(RetryPolicy::none())
+            .with_client_credentials(credentials);
+        client.search("rust").send().await.unwrap();
+        client.search("rust").set_pageno(2).send().await.unwrap();
+
+        // The token is treated as never expiring, so it is only fetched once.
+        let requests = server.await.unwrap();
+        assert!(
+            requests[2]
+                .to_ascii_lowercase()
+                .contains("authorization: bearer t")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_client_credentials_fetch_times_out() {
+        let token = http_response("200 OK", "", r#"{"access_token": "t"}"#);
+        let root = serve_with(1, move |_| (Duration::from_secs(30), token.clone())).await;
+        let credentials =
+            ClientCredentials::new(format!("{root}token").parse().unwrap(), "id", "secret")
+                .with_timeout(Duration::from_millis(100));
+        let client = SearXNGClient::new(&root, ResponseFormat::Json)
+            .with_retry_policy(RetryPolicy::none())
+            .with_client_credentials(credentials);
+        let search = client.search("rust");
+        let error = tokio::time::timeout(Duration::from_secs(5), search.send())
+            .await
+            .expect("the token request times out")
+            .unwrap_err();
+        assert!(error.is_timeout());
+    }
+
+    #[tokio::test]
+    async fn test_format_downgrade_skips_filtered_searches() {
+        let root = serve_with(3, |request| {
+            let response = if request.contains("format=csv") {
+                let csv = "title,url,content,host,engine,score,type\r\n\
+                    Rust,https://www.rust-lang.org/,,www.rust-lang.org,brave,1.0,result\r\n";
+                http_response("200 OK", "content-type: text/csv\r\n", csv)
+            } else {
+                let json = r#"{"query": "rust", "results": "moved"}"#;
+                http_response("200 OK", "content-type: application/json\r\n", json)
+            };
+            (Duration::ZERO, response)
+        })
+        .await;
+        let client = SearXNGClient::new(&root, ResponseFormat::Json)
+            .with_retry_policy(RetryPolicy::none())
+            .with_format_downgrade(true);
+
+        let results = client.search("rust").send_web().await.unwrap();
+        assert_eq!(
+            results[0].url.as_deref(),
+            Some("https://www.rust-lang.org/")
+        );
+        let filtered = client
+            .search("rust")
+            .set_filter(crate::filter::SafeContentFilter::new())
+            .send_web()
+            .await;
+        assert!(matches!(filtered, Err(Error::Decode { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_get_request_method() {
+        let body = &empty_response_json("rust").to_string();
+        let (root, server) = serve(vec![http_response(
+            "200 OK",
+            "content-type: application/json\r\n",
+            body,
+        )])
+        .await;
+        let client = SearXNGClient::new(&root, ResponseFormat::Json)
+            .with_retry_policy(RetryPolicy::none())
+            .with_request_method(Method::GET);
+        let search = client.search("rust");
+        assert!(search.describe().to_string().starts_with("GET "));
+        assert_eq!(search.send().await.unwrap().query, "rust");
+
+        let request = server.await.unwrap().remove(0);
+        let line = request.lines().next().unwrap();
+        assert!(line.starts_with("GET /search?"), "{line}");
+        assert!(line.contains("q=rust"), "{line}");
+    }
+
+    #[tokio::test]
+    async fn test_custom_headers() {
+        let body = &empty_response_json("rust").to_string();
+        let (root, server) = serve(vec![http_response(
+            "200 OK",
+            "content-type: application/json\r\n",
+            body,
+        )])
+        .await;
+        let client = SearXNGClient::new(&root, ResponseFormat::Json)
+            .with_retry_policy(RetryPolicy::none())
+            .with_header(
+                HeaderName::from_static("x-forwarded-for"),
+                HeaderValue::from_static("10.0.0.1"),
+            )
+            .with_cache(4, Duration::from_secs(60));
+        client
+            .search("rust")
+            .set_user_agent(HeaderValue::from_static("my-crawler/2.0"))
+            .send()
+            .await
+            .unwrap();
+        assert!(client.cache().unwrap().is_empty());
+
+        let request = server.await.unwrap().remove(0).to_ascii_lowercase();
+        assert!(request.contains("x-forwarded-for: 10.0.0.1\r\n"));
+        assert!(request.contains("user-agent: my-crawler/2.0\r\n"));
+        assert!(!request.contains(USER_AGENT));
+    }
+
+    #[tokio::test]
+    async fn test_max_age() {
+        // Responses without results are not cached.
+        let mut body = empty_response_json("rust");
+        body["results"] = serde_json::json!([crate::test::sample_result("https://a.org", "r", "")]);
+        let body = &body.to_string();
+        let response = http_response("200 OK", "content-type: application/json\r\n", body);
+        let (root, server) = serve(vec![response.clone(), response]).await;
+        let client = SearXNGClient::new(&root, ResponseFormat::Json)
+            .with_retry_policy(RetryPolicy::none())
+            .with_cache(4, Duration::from_secs(60));
+        let search = client.search("rust").set_max_age(Duration::from_secs(30));
+        search.send().await.unwrap();
+        search.send().await.unwrap();
+        // Too old for this search, though still cached.
+        let fresh = client.search("rust").set_max_age(Duration::ZERO);
+        fresh.send().await.unwrap();
+
+        let requests = server.await.unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(
+            requests[0]
+                .to_ascii_lowercase()
+                .contains("cache-control: max-age=30\r\n")
+        );
+        assert!(
+            requests[1]
+                .to_ascii_lowercase()
+                .contains("cache-control: max-age=0\r\n")
+        );
+        assert_eq!(client.cache().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_slow_query_hook() {
+        // Responses without results are not cached.
+        let mut body = empty_response_json("rust");
+        body["results"] = serde_json::json!([crate::test::sample_result("https://a.org", "r", "")]);
+        let body = &body.to_string();
+        let (root, server) = serve(vec![http_response(
+            "200 OK",
+            "content-type: application/json\r\n",
+            body,
+        )])
+        .await;
+        let slow = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let client = SearXNGClient::new(&root, ResponseFormat::Json)
+            .with_retry_policy(RetryPolicy::none())
+            .with_cache(4, Duration::from_secs(60))
+            .with_slow_query_hook(Duration::ZERO, {
+                let slow = slow.clone();
+                move |query| slow.lock().unwrap().push(query.clone())
+            });
+        client.search("rust").send().await.unwrap();
+        // Served from the cache, so not measured.
+        client.search("rust").send().await.unwrap();
+        server.await.unwrap();
+
+        assert_eq!(client.first_page_latency().len(), 1);
+        let slow = slow.lock().unwrap();
+        assert_eq!(slow.len(), 1);
+        assert_eq!(slow[0].query_hash, query_hash("rust"));
+        assert_eq!(slow[0].instance, client.instance_url());
+    }
+
+    #[tokio::test]
+    async fn test_send_get_num_drops_duplicates_across_pages() {
+        let page = |urls: &[&str]| {
+            let results: Vec<_> = urls
+                .iter()
+                .map(|url| crate::test::sample_result(url, "r", ""))
+                .collect();
+            let mut body = empty_response_json("rust");
+            body["results"] = serde_json::json!(results);
+            http_response(
+                "200 OK",
+                "content-type: application/json\r\n",
+                &body.to_string(),
+            )
+        };
+        let pages = vec![
+            page(&["https://a.org/", "https://b.org/"]),
+            page(&["https://www.b.org", "https://c.org/"]),
+        ];
+        let (root, _server) = serve(pages.clone()).await;
+        let client =
+            SearXNGClient::new(&root, ResponseFormat::Json).with_retry_policy(RetryPolicy::none());
+        let results = client.search("rust").send_get_num(3).await.unwrap();
+        let urls: Vec<_> = results
+            .iter()
+            .map(|r| WebResult::from(r).url.unwrap())
+            .collect();
+        assert_eq!(urls, ["https://a.org/", "https://b.org/", "https://c.org/"]);
+
+        let (root, _server) = serve(pages).await;
+        let client =
+            SearXNGClient::new(&root, ResponseFormat::Json).with_retry_policy(RetryPolicy::none());
+        let results = client
+            .search("rust")
+            .keep_duplicates()
+            .send_get_num(3)
+            .await;
+        assert_eq!(results.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_send_get_num_concurrent_keeps_page_order() {
+        let root = serve_with(4, |request| {
+            let pageno: u32 = request
+                .split("pageno=")
+                .nth(1)
+                .and_then(|rest| rest.split(|c: char| !c.is_ascii_digit()).next())
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(1);
+            let results: Vec<_> = (0..2)
+                .map(|i| crate::test::sample_result(&format!("https://p{pageno}.org/{i}"), "r", ""))
+                .collect();
+            let mut body = empty_response_json("rust");
+            body["results"] = serde_json::json!(results);
+            // Later pages answer first.
+            let delay = Duration::from_millis(50 * u64::from(5 - pageno.min(5)));
+            let response = http_response(
+                "200 OK",
+                "content-type: application/json\r\n",
+                &body.to_string(),
+            );
+            (delay, response)
+        })
+        .await;
+        let client =
+            SearXNGClient::new(&root, ResponseFormat::Json).with_retry_policy(RetryPolicy::none());
+        let results = client
+            .search("rust")
+            .send_get_num_concurrent(8, 4)
+            .await
+            .unwrap();
+        let urls: Vec<_> = results
+            .iter()
+            .map(|r| WebResult::from(r).url.unwrap())
+            .collect();
+        let expected: Vec<_> = (1..=4)
+            .flat_map(|p| (0..2).map(move |i| format!("https://p{p}.org/{i}")))
+            .collect();
+        assert_eq!(urls, expected);
+    }
+
+    #[tokio::test]
+    async fn test_redirect_trace() {
+        let body = &empty_response_json("rust").to_string();
+        let (root, server) = serve(vec![
+            http_response("302 Found", "location: /moved/search\r\n", ""),
+            http_response("200 OK", "content-type: application/json\r\n", body),
+        ])
+        .await;
+        let client = SearXNGClient::new(&root, ResponseFormat::Json)
+            .with_retry_policy(RetryPolicy::none())
+            .with_redirect_policy(RedirectPolicy::Limited(1));
+        let detailed = client.search("rust").send_detailed().await.unwrap();
+        assert_eq!(detailed.response.query, "rust");
+        assert_eq!(detailed.status, 200);
+        assert_eq!(
+            detailed.url,
+            format!("{root}moved/search?q=rust&format=json")
+        );
+        assert_eq!(
+            detailed.redirects,
+            [Redirect {
+                from: format!("{root}search"),
+                to: format!("{root}moved/search"),
+                status: 302,
+            }]
+        );
+        let requests = server.await.unwrap();
+        assert!(requests[0].starts_with("POST /search "));
+        assert!(requests[1].starts_with("GET /moved/search?q=rust"));
+    }
+
+    #[tokio::test]
+    async fn test_empty_page_retried_past_the_cache() {
+        let empty = empty_response_json("rust");
+        let mut full = empty.clone();
+        full["results"] = serde_json::json!([crate::test::sample_result("https://a.org", "r", "")]);
+        let json = "content-type: application/json\r\n";
+        let (root, server) = serve(vec![
+            http_response("200 OK", json, &empty.to_string()),
+            http_response("200 OK", json, &full.to_string()),
+        ])
+        .await;
+        let client = SearXNGClient::new(&root, ResponseFormat::Json)
+            .with_retry_policy(RetryPolicy::new(2))
+            .with_cache(4, Duration::from_secs(60));
+        let results = client.search("rust").send_get_num(1).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(server.await.unwrap().len(), 2);
+        // Only the page with results was cached.
+        assert_eq!(client.cache().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_https_only_refuses_plain_http_redirects() {
+        let moved = http_response("302 Found", "location: http://searx.invalid/search\r\n", "");
+        let (root, server) = serve(vec![moved.clone(), moved]).await;
+        let client = SearXNGClient::new(&root, ResponseFormat::Json)
+            .with_retry_policy(RetryPolicy::none())
+            .with_https_only()
+            .unwrap();
+        let error = client.search("rust").send().await.unwrap_err();
+        assert!(
+            matches!(&error, Error::Transport(e) if e.is_redirect()),
+            "{error:?}"
+        );
+
+        let client = client.with_redirect_policy(RedirectPolicy::Unlimited);
+        let error = client.search("rust").send().await.unwrap_err();
+        assert_eq!(error.status(), Some(StatusCode::FOUND));
+        assert_eq!(server.await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_redirect_to_other_origin_drops_headers() {
+        let body = &empty_response_json("rust").to_string();
+        let (other, other_server) = serve(vec![http_response(
+            "200 OK",
+            "content-type: application/json\r\n",
+            body,
+        )])
+        .await;
+        let location = format!("location: {other}search\r\n");
+        let (root, server) =
+            serve(vec![http_response("307 Temporary Redirect", &location, "")]).await;
+        let client = SearXNGClient::new(&root, ResponseFormat::Json)
+            .with_retry_policy(RetryPolicy::none())
+            .with_redirect_policy(RedirectPolicy::Limited(1))
+            .with_header(
+                HeaderName::from_static("x-forwarded-for"),
+                HeaderValue::from_static("10.0.0.1"),
+            )
+            .with_request_signer(crate::sign::HmacSigner::new("secret"));
+        client.search("rust").send().await.unwrap();
+
+        let first = server.await.unwrap().remove(0).to_ascii_lowercase();
+        assert!(first.contains("x-forwarded-for: 10.0.0.1\r\n"));
+        assert!(first.contains("x-signature: "));
+        let hop = other_server.await.unwrap().remove(0).to_ascii_lowercase();
+        assert!(hop.starts_with("post /search "), "{hop}");
+        assert!(hop.contains(&format!("user-agent: {USER_AGENT}\r\n")));
+        assert!(!hop.contains("x-forwarded-for"));
+        assert!(!hop.contains("x-signature"));
+    }
+
+    #[tokio::test]
+    async fn test_redirect_loop_is_not_followed() {
+        let moved = http_response("302 Found", "location: /search\r\n", "");
+        let (root, server) = serve(vec![moved.clone(), moved]).await;
+        let client = SearXNGClient::new(&root, ResponseFormat::Json)
+            .with_retry_policy(RetryPolicy::none())
+            .with_redirect_policy(RedirectPolicy::Unlimited);
+        let error = client.search("rust").send().await.unwrap_err();
+        assert_eq!(error.status(), Some(StatusCode::FOUND));
+        // The `POST` is followed once, as a `GET`.
+        assert_eq!(server.await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_bearer_token_not_sent_to_other_origins() {
+        let body = &empty_response_json("rust").to_string();
+        let (other, other_server) = serve(vec![http_response(
+            "200 OK",
+            "content-type: application/json\r\n",
+            body,
+        )])
+        .await;
+        let token = r#"{"access_token": "secret", "token_type": "Bearer"}"#;
+        let location = format!("location: {other}search\r\n");
+        let (root, server) = serve(vec![
+            http_response("200 OK", "content-type: application/json\r\n", token),
+            http_response("307 Temporary Redirect", &location, ""),
+        ])
+        .await;
+        let credentials =
+            ClientCredentials::new(format!("{root}token").parse().unwrap(), "id", "secret");
+        let client = SearXNGClient::new(&root, ResponseFormat::Json)
+            .with_retry_policy(RetryPolicy::none())
+            .with_redirect_policy(RedirectPolicy::Limited(1))
+            .with_client_credentials(credentials);
+        client.search("rust").send().await.unwrap();
+
+        let requests = server.await.unwrap();
+        assert!(
+            requests[1]
+                .to_ascii_lowercase()
+                .contains("authorization: bearer secret")
+        );
+        let hop = other_server.await.unwrap().remove(0).to_ascii_lowercase();
+        assert!(!hop.contains("authorization"), "{hop}");
+    }
+
+    #[tokio::test]
+    async fn test_audit_record_of_failed_get_omits_query() {
+        #[derive(Debug, Default)]
+        struct Records(std::sync::Mutex<Vec<AuditRecord>>);
+        impl AuditSink for Arc<Records> {
+            fn record(&self, record: &AuditRecord) {
+                self.0.lock().unwrap().push(record.clone());
+            }
+        }
+
+        let records = Arc::new(Records::default());
+        // Nothing listens on port 9, so the request fails to connect.
+        let client = SearXNGClient::new("http://127.0.0.1:9/", ResponseFormat::Json)
+            .with_retry_policy(RetryPolicy::none())
+            .with_request_method(Method::GET)
+            .with_audit_log(records.clone());
+        let error = client.search("secret terms").send().await.unwrap_err();
+        assert!(matches!(error, Error::Transport(_)));
+        assert!(!format!("{error} {error:?}").contains("secret"));
+
+        let records = records.0.lock().unwrap();
+        let record = &records[0];
+        assert_eq!(record.instance, "http://127.0.0.1:9/search");
+        let message = record.error.as_deref().unwrap();
+        assert!(message.contains("127.0.0.1:9/search"), "{message}");
+        assert!(!message.contains("secret"), "{message}");
     }
 }
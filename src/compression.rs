@@ -0,0 +1,93 @@
+//! Negotiation of compressed responses.
+//!
+//! Which encodings can be decoded is decided at compile time by the `gzip`, `brotli`,
+//! `zstd` and `deflate` features. Which of them are advertised to an instance is decided
+//! per client with [`SearXNGClient::with_compression`](crate::SearXNGClient::with_compression),
+//! since some misconfigured instances send broken brotli streams.
+
+/// The content encodings a client advertises in `Accept-Encoding`.
+///
+/// Encodings whose feature is disabled are never advertised, whatever this says.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Compression {
+    pub gzip: bool,
+    pub brotli: bool,
+    pub zstd: bool,
+    pub deflate: bool,
+}
+
+impl Default for Compression {
+    /// Advertises every encoding enabled at compile time.
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+impl Compression {
+    /// Advertises every encoding enabled at compile time.
+    pub const fn all() -> Self {
+        Compression {
+            gzip: true,
+            brotli: true,
+            zstd: true,
+            deflate: true,
+        }
+    }
+
+    /// Requests uncompressed responses.
+    pub const fn none() -> Self {
+        Compression {
+            gzip: false,
+            brotli: false,
+            zstd: false,
+            deflate: false,
+        }
+    }
+
+    /// Stops advertising brotli, keeping the other encodings.
+    pub const fn without_brotli(mut self) -> Self {
+        self.brotli = false;
+        self
+    }
+
+    /// The encodings that will actually be advertised, in order of preference.
+    pub fn encodings(&self) -> Vec<&'static str> {
+        let mut encodings = Vec::new();
+        if self.zstd && cfg!(feature = "zstd") {
+            encodings.push("zstd");
+        }
+        if self.brotli && cfg!(feature = "brotli") {
+            encodings.push("br");
+        }
+        if self.gzip && cfg!(feature = "gzip") {
+            encodings.push("gzip");
+        }
+        if self.deflate && cfg!(feature = "deflate") {
+            encodings.push("deflate");
+        }
+        encodings
+    }
+
+    /// The `Accept-Encoding` header value, `identity` if no encoding is advertised.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use searxng_client::compression::Compression;
+    /// assert_eq!(Compression::none().accept_encoding(), "identity");
+    /// assert!(!Compression::all().without_brotli().accept_encoding().contains("br"));
+    /// ```
+    pub fn accept_encoding(&self) -> String {
+        let encodings = self.encodings();
+        if encodings.is_empty() {
+            "identity".to_string()
+        } else {
+            encodings.join(", ")
+        }
+    }
+
+    /// Returns `true` if at least one encoding is advertised.
+    pub fn is_enabled(&self) -> bool {
+        !self.encodings().is_empty()
+    }
+}
@@ -0,0 +1,382 @@
+//! Instance capability discovery through the `/config` endpoint.
+//!
+//! SearXNG publishes the engines, categories and version of an instance at `/config`.
+//! Probing it is comparatively expensive, so the client caches the result for a
+//! configurable time-to-live, see [`SearXNGClient::capabilities`](crate::SearXNGClient::capabilities).
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::client::{Category, SearchParams};
+use crate::rt::Instant;
+
+/// The default time-to-live of cached capabilities.
+pub const DEFAULT_CAPABILITIES_TTL: Duration = Duration::from_secs(300);
+
+/// An engine configured on an instance.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EngineInfo {
+    /// The engine name, as accepted by the `engines` parameter.
+    pub name: String,
+    /// The categories this engine belongs to.
+    pub categories: Vec<String>,
+    /// The `!bang` shortcut of the engine.
+    pub shortcut: String,
+    /// Whether the engine is enabled by default.
+    pub enabled: bool,
+    /// Whether the engine supports the `pageno` parameter.
+    pub paging: bool,
+}
+
+impl Default for EngineInfo {
+    fn default() -> Self {
+        EngineInfo {
+            name: String::new(),
+            categories: Vec::new(),
+            shortcut: String::new(),
+            enabled: true,
+            paging: false,
+        }
+    }
+}
+
+/// A plugin installed on an instance.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PluginInfo {
+    pub name: String,
+    /// Whether the plugin is enabled by default.
+    pub enabled: bool,
+}
+
+/// The configuration reported by an instance's `/config` endpoint.
+///
+/// Only the fields relevant to API consumers are modelled. Missing fields default to
+/// empty values, so older instances can still be probed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InstanceConfig {
+    /// The name the instance presents itself with.
+    pub instance_name: String,
+    /// The SearXNG version the instance runs.
+    pub version: Option<String>,
+    /// The categories available on the instance.
+    pub categories: Vec<String>,
+    /// The engines configured on the instance.
+    pub engines: Vec<EngineInfo>,
+    /// The plugins installed on the instance.
+    pub plugins: Vec<PluginInfo>,
+    /// The interface locales, by code (e.g. `de`) with their display names.
+    pub locales: HashMap<String, String>,
+}
+
+impl InstanceConfig {
+    /// Parses the reported [`version`](Self::version), if present and well-formed.
+    pub fn parsed_version(&self) -> Option<InstanceVersion> {
+        self.version.as_deref()?.parse().ok()
+    }
+
+    /// Checks the reported version against [`MIN_SUPPORTED_VERSION`] and
+    /// [`MAX_SUPPORTED_VERSION`].
+    pub fn version_skew(&self) -> Option<VersionSkew> {
+        match self.parsed_version() {
+            Some(v) if (MIN_SUPPORTED_VERSION..=MAX_SUPPORTED_VERSION).contains(&v) => None,
+            parsed => Some(VersionSkew {
+                reported: self.version.clone(),
+                parsed,
+            }),
+        }
+    }
+
+    /// Returns the engines and categories named by `params` that the instance does not
+    /// have.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use searxng_client::{InstanceConfig, ResponseFormat};
+    /// use searxng_client::client::SearchParams;
+    /// use searxng_client::config::EngineInfo;
+    /// let config = InstanceConfig {
+    ///     categories: vec!["general".into()],
+    ///     engines: vec![EngineInfo { name: "brave".into(), ..Default::default() }],
+    ///     ..Default::default()
+    /// };
+    /// let mut params = SearchParams::new("rust", ResponseFormat::Json);
+    /// params.api.engines = Some(vec!["brave".into(), "bing".into()]);
+    /// assert_eq!(config.unsupported(&params).engines, ["bing"]);
+    /// ```
+    pub fn unsupported(&self, params: &SearchParams) -> UnsupportedParams {
+        let api = &params.api;
+        UnsupportedParams {
+            engines: api
+                .engines
+                .iter()
+                .flatten()
+                .filter(|name| !self.engines.iter().any(|e| &e.name == *name))
+                .cloned()
+                .collect(),
+            categories: api
+                .categories
+                .iter()
+                .flatten()
+                .map(Category::as_str)
+                .filter(|name| !self.categories.iter().any(|c| c == name))
+                .map(String::from)
+                .collect(),
+        }
+    }
+
+    /// Collects the diagnostics for this configuration.
+    pub fn diagnostics(&self) -> Diagnostics {
+        Diagnostics {
+            warnings: self
+                .version_skew()
+                .map(Diagnostic::VersionSkew)
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+/// The engines and categories of a search that an instance does not have, see
+/// [`InstanceConfig::unsupported`].
+///
+/// SearXNG silently ignores them, which usually leads to unexpected results rather than
+/// an error.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UnsupportedParams {
+    pub engines: Vec<String>,
+    pub categories: Vec<String>,
+}
+
+impl UnsupportedParams {
+    /// Returns `true` if the instance supports every engine and category.
+    pub fn is_empty(&self) -> bool {
+        self.engines.is_empty() && self.categories.is_empty()
+    }
+}
+
+impl fmt::Display for UnsupportedParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if !self.engines.is_empty() {
+            parts.push(format!("unknown engines: {}", self.engines.join(", ")));
+        }
+        if !self.categories.is_empty() {
+            parts.push(format!(
+                "unknown categories: {}",
+                self.categories.join(", ")
+            ));
+        }
+        f.write_str(&parts.join("; "))
+    }
+}
+
+impl std::error::Error for UnsupportedParams {}
+
+/// A SearXNG release version.
+///
+/// SearXNG uses calendar versioning (`2025.3.12`), optionally followed by a `+commit`
+/// or `-commit` suffix, which is ignored.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct InstanceVersion {
+    /// The release year.
+    pub year: u32,
+    /// The release month.
+    pub month: u32,
+    /// The release day.
+    pub day: u32,
+}
+
+impl InstanceVersion {
+    /// Creates a version from its components.
+    pub const fn new(year: u32, month: u32, day: u32) -> Self {
+        InstanceVersion { year, month, day }
+    }
+}
+
+impl FromStr for InstanceVersion {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let release = s.split(['+', '-']).next().unwrap_or_default();
+        let mut parts = release.trim().split('.').map(|p| p.parse::<u32>());
+        match (parts.next(), parts.next(), parts.next().unwrap_or(Ok(0))) {
+            (Some(Ok(year)), Some(Ok(month)), Ok(day)) => {
+                Ok(InstanceVersion::new(year, month, day))
+            }
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for InstanceVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.year, self.month, self.day)
+    }
+}
+
+/// The oldest SearXNG version whose response schema the models in this crate match.
+pub const MIN_SUPPORTED_VERSION: InstanceVersion = InstanceVersion::new(2025, 1, 1);
+
+/// The newest SearXNG version the models in this crate have been checked against.
+pub const MAX_SUPPORTED_VERSION: InstanceVersion = InstanceVersion::new(2026, 12, 31);
+
+/// The instance runs a version outside the supported range, or its version is unknown.
+///
+/// Schema drift between SearXNG releases is the most common cause of parse failures, so
+/// this warning usually explains them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionSkew {
+    /// The version string reported by the instance, if any.
+    pub reported: Option<String>,
+    /// The parsed version, if the reported string was well-formed.
+    pub parsed: Option<InstanceVersion>,
+}
+
+impl fmt::Display for VersionSkew {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.reported, self.parsed) {
+            (_, Some(v)) => write!(
+                f,
+                "instance version {v} is outside the supported range {MIN_SUPPORTED_VERSION} - {MAX_SUPPORTED_VERSION}"
+            ),
+            (Some(raw), None) => write!(f, "instance version {raw:?} could not be parsed"),
+            (None, None) => write!(f, "instance did not report its version"),
+        }
+    }
+}
+
+impl std::error::Error for VersionSkew {}
+
+/// A non-fatal finding about an instance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Diagnostic {
+    /// See [`VersionSkew`].
+    VersionSkew(VersionSkew),
+}
+
+/// The diagnostics collected while probing an instance.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Diagnostics {
+    /// The warnings found, in no particular order.
+    pub warnings: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    /// Returns `true` if no warnings were found.
+    pub fn is_empty(&self) -> bool {
+        self.warnings.is_empty()
+    }
+}
+
+/// The result of [`SearXNGClient::probe`](crate::SearXNGClient::probe).
+#[derive(Debug, Clone)]
+pub struct ProbeReport {
+    /// The instance configuration.
+    pub config: Arc<InstanceConfig>,
+    /// The diagnostics derived from it.
+    pub diagnostics: Diagnostics,
+}
+
+/// An error returned by [`SearXNGClient::probe`](crate::SearXNGClient::probe).
+#[derive(Debug)]
+pub enum ProbeError {
+    /// The `/config` request failed.
+    Request(reqwest::Error),
+    /// Strict version checking is enabled and the instance version is not supported.
+    VersionSkew(VersionSkew),
+}
+
+impl fmt::Display for ProbeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProbeError::Request(e) => write!(f, "capability probe failed: {e}"),
+            ProbeError::VersionSkew(skew) => skew.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ProbeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ProbeError::Request(e) => Some(e),
+            ProbeError::VersionSkew(skew) => Some(skew),
+        }
+    }
+}
+
+impl From<reqwest::Error> for ProbeError {
+    fn from(e: reqwest::Error) -> Self {
+        ProbeError::Request(e)
+    }
+}
+
+/// A per-client cache of the last `/config` probe.
+#[derive(Debug)]
+pub(crate) struct CapabilityCache {
+    ttl: Duration,
+    entry: Mutex<Option<(Instant, Arc<InstanceConfig>)>>,
+}
+
+impl CapabilityCache {
+    pub(crate) fn new(ttl: Duration) -> Self {
+        CapabilityCache {
+            ttl,
+            entry: Mutex::new(None),
+        }
+    }
+
+    pub(crate) fn get(&self) -> Option<Arc<InstanceConfig>> {
+        let entry = self.entry.lock().unwrap_or_else(|e| e.into_inner());
+        entry
+            .as_ref()
+            .filter(|(fetched, _)| fetched.elapsed() < self.ttl)
+            .map(|(_, config)| config.clone())
+    }
+
+    pub(crate) fn put(&self, config: InstanceConfig) -> Arc<InstanceConfig> {
+        let config = Arc::new(config);
+        *self.entry.lock().unwrap_or_else(|e| e.into_inner()) =
+            Some((Instant::now(), config.clone()));
+        config
+    }
+
+    pub(crate) fn invalidate(&self) {
+        *self.entry.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+impl Default for CapabilityCache {
+    fn default() -> Self {
+        CapabilityCache::new(DEFAULT_CAPABILITIES_TTL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_skew() {
+        assert_eq!(
+            "2025.3.12+a1b2c3".parse(),
+            Ok(InstanceVersion::new(2025, 3, 12))
+        );
+        let config = |version: Option<&str>| InstanceConfig {
+            version: version.map(String::from),
+            ..Default::default()
+        };
+        assert!(config(Some("2025.6.1-deadbeef")).version_skew().is_none());
+        assert!(config(Some("2023.12.1")).version_skew().is_some());
+        assert!(config(Some("unknown")).version_skew().is_some());
+        assert!(!config(None).diagnostics().is_empty());
+    }
+}
@@ -0,0 +1,63 @@
+//! Pluggable decoding of response bodies.
+//!
+//! Every request goes through the client's [`ResponseDecoder`], in tests as in
+//! production. [`SerdeDecoder`] is the default; [`DebugDecoder`] can be set at runtime
+//! with [`SearXNGClient::with_decoder`](crate::SearXNGClient::with_decoder) to dump the
+//! bodies that fail to parse.
+
+use std::fmt;
+
+use crate::parse::{ParseError, ParseMode};
+use crate::response::SearchResponse;
+
+/// Turns a response body into a [`SearchResponse`].
+pub trait ResponseDecoder: fmt::Debug + Send + Sync {
+    /// Decodes `body` under the client's [`ParseMode`].
+    fn decode(&self, body: &[u8], mode: ParseMode) -> Result<SearchResponse, ParseError>;
+}
+
+/// Decodes bodies with [`SearchResponse::from_json_slice`].
+#[derive(Debug, Copy, Clone, Default)]
+pub struct SerdeDecoder;
+
+impl ResponseDecoder for SerdeDecoder {
+    fn decode(&self, body: &[u8], mode: ParseMode) -> Result<SearchResponse, ParseError> {
+        SearchResponse::from_json_slice(body, mode)
+    }
+}
+
+/// Decodes like [`SerdeDecoder`], and prints the error and the raw body to standard error
+/// when decoding fails.
+///
+/// Bodies echo the query, so this decoder ignores the client's
+/// [`QueryRedaction`](crate::redact::QueryRedaction) and should only be used while debugging.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct DebugDecoder;
+
+impl ResponseDecoder for DebugDecoder {
+    fn decode(&self, body: &[u8], mode: ParseMode) -> Result<SearchResponse, ParseError> {
+        SerdeDecoder.decode(body, mode).inspect_err(|e| {
+            eprintln!("\n--- [DEBUG] JSON DECODE ERROR ---");
+            eprintln!("Reason: {e}");
+            if let ParseError::Response(e) | ParseError::Result { source: e, .. } = e {
+                eprintln!("At: Line {}, Column {}", e.line(), e.column());
+            }
+            eprintln!("Raw Body:\n{}", String::from_utf8_lossy(body));
+            eprintln!("---------------------------------\n");
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debug_decoder_matches_serde() {
+        for body in [&b"{"[..], br#"{"query": "rust"}"#] {
+            let serde = SerdeDecoder.decode(body, ParseMode::Strict).unwrap_err();
+            let debug = DebugDecoder.decode(body, ParseMode::Strict).unwrap_err();
+            assert_eq!(serde.to_string(), debug.to_string());
+        }
+    }
+}
@@ -0,0 +1,371 @@
+//! Client-side deduplication of search results.
+//!
+//! SearXNG merges results sharing a URL within a single response, but results collected
+//! across several pages or instances have to be merged by the client. [`Deduplicator`]
+//! identifies duplicates by their canonical URL and combines them according to a
+//! [`MergePolicy`].
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::str::FromStr;
+
+use reqwest::Url;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+
+use crate::response::SearchResult;
+
+/// How the scores of duplicate results are combined.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MergePolicy {
+    /// Keep the highest score seen.
+    #[default]
+    KeepMax,
+    /// Add the scores together, rewarding results returned several times.
+    Sum,
+    /// Use the mean of all scores seen.
+    Average,
+    /// Keep the first result unchanged and discard later duplicates.
+    KeepFirst,
+}
+
+/// Returns a canonical form of `url` used to identify duplicates.
+///
+/// The scheme and host are lowercased, a leading `www.` is removed, and the fragment
+/// and trailing slashes are dropped. Strings that are not valid URLs are returned trimmed.
+///
+/// # Examples
+///
+/// ```
+/// use searxng_client::dedup::canonical_url;
+/// assert_eq!(
+///     canonical_url("HTTPS://WWW.Rust-Lang.org/learn/#book"),
+///     canonical_url("https://rust-lang.org/learn"),
+/// );
+/// ```
+pub fn canonical_url(url: &str) -> String {
+    let Ok(mut parsed) = Url::parse(url.trim()) else {
+        return url.trim().to_string();
+    };
+    parsed.set_fragment(None);
+    if let Some(host) = parsed
+        .host_str()
+        .map(|h| h.trim_start_matches("www.").to_string())
+    {
+        let _ = parsed.set_host(Some(&host));
+    }
+    let path = parsed.path().trim_end_matches('/').to_string();
+    parsed.set_path(&path);
+    parsed.as_str().trim_end_matches('/').to_string()
+}
+
+/// A deterministic identifier of a result, see [`SearchResult::stable_id`].
+///
+/// Identifiers are displayed and serialized as 16 lowercase hex digits, since their
+/// numeric value does not fit a JSON number without losing precision.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ResultId(pub u64);
+
+impl fmt::Display for ResultId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl FromStr for ResultId {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        u64::from_str_radix(s, 16).map(ResultId)
+    }
+}
+
+impl Serialize for ResultId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ResultId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl SearchResult {
+    /// A deterministic identifier derived from the canonical URL and the title.
+    ///
+    /// The identifier is a 64-bit prefix of the SHA-256 digest of the
+    /// [`canonical_url`] and the trimmed title, so it is the same across processes,
+    /// platforms and versions of this crate, and can be stored in databases. Results the
+    /// [`Deduplicator`] would merge but whose titles differ get different identifiers.
+    pub fn stable_id(&self) -> ResultId {
+        let (url, title) = match self {
+            SearchResult::LegacyResult(l) => (l.url.as_deref(), l.title.as_str()),
+            SearchResult::MainResult(m) => (m.url.as_deref(), m.title.as_str()),
+        };
+        let mut hasher = Sha256::new();
+        hasher.update(url.map(canonical_url).unwrap_or_default());
+        hasher.update(b"\n");
+        hasher.update(title.trim());
+        let digest = hasher.finalize();
+        ResultId(u64::from_be_bytes(digest[..8].try_into().expect("8 bytes")))
+    }
+}
+
+/// A result compared, ordered and hashed by its [`canonical_url`], so that it can be put
+/// in a `HashSet` or used as a `BTreeMap` key.
+///
+/// Results without a URL are compared by title instead. The key is computed on every
+/// comparison; collections holding many results may prefer to key by
+/// [`canonical_url`] themselves.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashSet;
+/// use searxng_client::dedup::ByUrl;
+/// # fn collect(results: Vec<searxng_client::response::SearchResult>) {
+/// let unique: HashSet<ByUrl> = results.into_iter().map(ByUrl).collect();
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ByUrl(pub SearchResult);
+
+impl ByUrl {
+    fn key(&self) -> (bool, String) {
+        match self.0.url() {
+            Some(url) => (true, canonical_url(url)),
+            None => (false, self.0.title().trim().to_string()),
+        }
+    }
+
+    /// Returns the wrapped result.
+    pub fn into_inner(self) -> SearchResult {
+        self.0
+    }
+}
+
+impl PartialEq for ByUrl {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for ByUrl {}
+
+impl Hash for ByUrl {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+impl PartialOrd for ByUrl {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ByUrl {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl Deref for ByUrl {
+    type Target = SearchResult;
+
+    fn deref(&self) -> &SearchResult {
+        &self.0
+    }
+}
+
+impl From<SearchResult> for ByUrl {
+    fn from(result: SearchResult) -> Self {
+        ByUrl(result)
+    }
+}
+
+fn merge_into(target: &mut SearchResult, other: SearchResult, policy: MergePolicy, seen: usize) {
+    macro_rules! merge {
+        ($t:expr, $o:expr) => {{
+            for engine in $o.engines {
+                if !$t.engines.contains(&engine) {
+                    $t.engines.push(engine);
+                }
+            }
+            $t.positions.extend($o.positions);
+            $t.score = match policy {
+                MergePolicy::KeepMax => $t.score.max($o.score),
+                MergePolicy::Sum => $t.score + $o.score,
+                MergePolicy::Average => ($t.score * seen as f64 + $o.score) / (seen + 1) as f64,
+                MergePolicy::KeepFirst => $t.score,
+            };
+        }};
+    }
+
+    match (target, other) {
+        (SearchResult::LegacyResult(t), SearchResult::LegacyResult(o)) => merge!(t, o),
+        (SearchResult::LegacyResult(t), SearchResult::MainResult(o)) => merge!(t, o),
+        (SearchResult::MainResult(t), SearchResult::LegacyResult(o)) => merge!(t, o),
+        (SearchResult::MainResult(t), SearchResult::MainResult(o)) => merge!(t, o),
+    }
+}
+
+/// Incrementally collects results, merging duplicates by canonical URL.
+///
+/// Results without a URL are never considered duplicates. The order in which results
+/// were first seen is preserved.
+#[derive(Debug, Clone, Default)]
+pub struct Deduplicator {
+    policy: MergePolicy,
+    index: HashMap<String, usize>,
+    results: Vec<SearchResult>,
+    seen: Vec<usize>,
+}
+
+impl Deduplicator {
+    /// Creates an empty deduplicator using the given [`MergePolicy`].
+    pub fn new(policy: MergePolicy) -> Self {
+        Deduplicator {
+            policy,
+            ..Default::default()
+        }
+    }
+
+    /// Adds a result, returning `true` if it was not a duplicate.
+    pub fn push(&mut self, result: SearchResult) -> bool {
+        let len = self.results.len();
+        self.insert(result) == len
+    }
+
+    /// Adds a result, returning the index of the unique result it was merged into.
+    ///
+    /// Indices are stable and match the order of [`into_results`](Self::into_results).
+    pub fn insert(&mut self, result: SearchResult) -> usize {
+        let Some(key) = result.url().map(canonical_url) else {
+            self.results.push(result);
+            self.seen.push(1);
+            return self.results.len() - 1;
+        };
+
+        match self.index.get(&key) {
+            Some(&i) => {
+                if self.policy != MergePolicy::KeepFirst {
+                    merge_into(&mut self.results[i], result, self.policy, self.seen[i]);
+                }
+                self.seen[i] += 1;
+                i
+            }
+            None => {
+                self.index.insert(key, self.results.len());
+                self.results.push(result);
+                self.seen.push(1);
+                self.results.len() - 1
+            }
+        }
+    }
+
+    /// The number of unique results collected so far.
+    pub fn len(&self) -> usize {
+        self.results.len()
+    }
+
+    /// Returns `true` if no results have been collected.
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    /// Consumes the deduplicator, returning the unique results.
+    pub fn into_results(self) -> Vec<SearchResult> {
+        self.results
+    }
+}
+
+impl Extend<SearchResult> for Deduplicator {
+    fn extend<I: IntoIterator<Item = SearchResult>>(&mut self, iter: I) {
+        for result in iter {
+            self.push(result);
+        }
+    }
+}
+
+/// Deduplicates `results` by canonical URL using the given [`MergePolicy`].
+pub fn dedup(
+    results: impl IntoIterator<Item = SearchResult>,
+    policy: MergePolicy,
+) -> Vec<SearchResult> {
+    let mut deduplicator = Deduplicator::new(policy);
+    deduplicator.extend(results);
+    deduplicator.into_results()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::sample_result;
+
+    #[test]
+    fn test_stable_id() {
+        let id = sample_result("https://www.rust-lang.org/", "Rust ", "").stable_id();
+        assert_eq!(
+            id,
+            sample_result("https://rust-lang.org", "Rust", "").stable_id()
+        );
+        assert_ne!(
+            id,
+            sample_result("https://rust-lang.org", "Rust!", "").stable_id()
+        );
+        // Identifiers must never change, since applications store them.
+        assert_eq!(id.to_string(), "f172bce55335f09a");
+        assert_eq!(id.to_string().parse(), Ok(id));
+        assert_eq!(serde_json::to_value(id).unwrap(), id.to_string());
+    }
+
+    #[test]
+    fn test_by_url() {
+        use std::collections::{BTreeSet, HashSet};
+
+        let results = [
+            sample_result("https://www.rust-lang.org/", "Rust", ""),
+            sample_result("https://rust-lang.org", "Rust!", ""),
+            sample_result("https://docs.rs", "Docs", ""),
+        ];
+        let set: HashSet<ByUrl> = results.iter().cloned().map(ByUrl).collect();
+        assert_eq!(set.len(), 2);
+        let sorted: BTreeSet<ByUrl> = results.into_iter().map(ByUrl).collect();
+        let urls: Vec<_> = sorted
+            .iter()
+            .filter_map(|r| r.url())
+            .map(canonical_url)
+            .collect();
+        assert_eq!(urls, ["https://docs.rs", "https://rust-lang.org"]);
+    }
+
+    #[test]
+    fn test_merge_policies() {
+        let results = || {
+            let mut second = sample_result("https://www.rust-lang.org/", "Rust", "");
+            if let SearchResult::LegacyResult(l) = &mut second {
+                l.score = 3.0;
+            }
+            vec![sample_result("https://rust-lang.org", "Rust", ""), second]
+        };
+
+        for (policy, expected) in [
+            (MergePolicy::KeepMax, 3.0),
+            (MergePolicy::Sum, 4.0),
+            (MergePolicy::Average, 2.0),
+            (MergePolicy::KeepFirst, 1.0),
+        ] {
+            let merged = dedup(results(), policy);
+            assert_eq!(merged.len(), 1);
+            assert_eq!(merged[0].score(), expected, "{policy:?}");
+        }
+    }
+}
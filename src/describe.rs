@@ -0,0 +1,83 @@
+//! Inspection of the effective parameters of a search.
+//!
+//! The parameters sent for a search are the ones set on the [`SearchBuilder`] merged
+//! with its preset and the client-level defaults, as described in the
+//! [`resolve`](crate::resolve) module, and the timeout may come from the client's
+//! [adaptive timeout](crate::SearXNGClient::with_adaptive_timeout).
+//! [`SearchBuilder::describe`] reports the result of that merge without sending anything.
+//!
+//! [`SearchBuilder`]: crate::client::SearchBuilder
+//! [`SearchBuilder::describe`]: crate::client::SearchBuilder::describe
+
+use std::fmt;
+use std::time::Duration;
+
+use reqwest::Method;
+
+use crate::resolve::ParamOrigin;
+
+/// A parameter as it will be sent to the instance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EffectiveParam {
+    /// The form field name.
+    pub name: String,
+    /// The form field value. The query is redacted according to the client's
+    /// [`QueryRedaction`](crate::redact::QueryRedaction).
+    pub value: String,
+    /// Where the value came from.
+    pub origin: ParamOrigin,
+}
+
+/// The timeout that will be applied to the request.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct EffectiveTimeout {
+    /// The timeout.
+    pub duration: Duration,
+    /// [`ParamOrigin::ClientDefault`] if the timeout was derived from the client's
+    /// adaptive timeout.
+    pub origin: ParamOrigin,
+}
+
+/// A summary of everything a search request will send, see
+/// [`SearchBuilder::describe`](crate::client::SearchBuilder::describe).
+///
+/// The [`Display`](fmt::Display) implementation prints one parameter per line, marking
+/// values that did not come from the request itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchDescription {
+    /// The HTTP method, see
+    /// [`SearXNGClient::with_request_method`](crate::SearXNGClient::with_request_method).
+    pub method: Method,
+    /// The URL of the search endpoint.
+    pub endpoint: String,
+    /// The form parameters, sorted by name. Unset parameters are omitted.
+    pub params: Vec<EffectiveParam>,
+    /// The request timeout, if any.
+    pub timeout: Option<EffectiveTimeout>,
+}
+
+impl SearchDescription {
+    /// Returns the parameter named `name`, if it will be sent.
+    pub fn get(&self, name: &str) -> Option<&EffectiveParam> {
+        self.params.iter().find(|p| p.name == name)
+    }
+}
+
+impl fmt::Display for SearchDescription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.method, self.endpoint)?;
+        for param in &self.params {
+            write!(f, "\n  {} = {}", param.name, param.value)?;
+            if param.origin != ParamOrigin::Request {
+                write!(f, " ({})", param.origin)?;
+            }
+        }
+        if let Some(timeout) = &self.timeout {
+            write!(f, "\n  timeout = {:?}", timeout.duration)?;
+            if timeout.origin != ParamOrigin::Request {
+                write!(f, " ({})", timeout.origin)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+//! Choosing between IPv4 and IPv6 for instance connections.
+//!
+//! When a host has both A and AAAA records, the HTTP client tries the family of the
+//! first resolved address and falls back to the other after a short delay ("happy
+//! eyeballs"). Instances with broken AAAA records then connect slowly or not at all. An
+//! [`IpPreference`] set with
+//! [`SearXNGClient::with_ip_preference`](crate::SearXNGClient::with_ip_preference)
+//! reorders the resolved addresses so that the preferred family is tried first, or drops
+//! the other family entirely.
+//!
+//! On `wasm32` the browser resolves names and connects on its own, so preferences have
+//! no effect and [`PreferenceResolver`] does not exist.
+
+use std::net::SocketAddr;
+
+#[cfg(not(target_arch = "wasm32"))]
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+
+/// Which IP family connections to an instance use.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub enum IpPreference {
+    /// Uses the addresses in the order the system resolver returns them.
+    #[default]
+    Any,
+    /// Tries IPv4 addresses first, falling back to IPv6.
+    PreferV4,
+    /// Tries IPv6 addresses first, falling back to IPv4.
+    PreferV6,
+    /// Only connects over IPv4.
+    V4Only,
+    /// Only connects over IPv6.
+    V6Only,
+}
+
+impl IpPreference {
+    /// Orders and filters resolved addresses according to the preference.
+    pub fn apply(&self, addrs: impl IntoIterator<Item = SocketAddr>) -> Vec<SocketAddr> {
+        if *self == IpPreference::Any {
+            return addrs.into_iter().collect();
+        }
+        let (v4, v6): (Vec<SocketAddr>, Vec<SocketAddr>) =
+            addrs.into_iter().partition(SocketAddr::is_ipv4);
+        match self {
+            IpPreference::Any | IpPreference::PreferV4 => v4.into_iter().chain(v6).collect(),
+            IpPreference::PreferV6 => v6.into_iter().chain(v4).collect(),
+            IpPreference::V4Only => v4,
+            IpPreference::V6Only => v6,
+        }
+    }
+}
+
+/// A DNS resolver applying an [`IpPreference`] to the system resolver's answers.
+///
+/// Clients built with [`SearXNGClient::with_ip_preference`](crate::SearXNGClient::with_ip_preference)
+/// use it already; it is public for HTTP clients set with
+/// [`SearXNGClient::with_http_client`](crate::SearXNGClient::with_http_client).
+///
+/// # Examples
+///
+/// ```
+/// use searxng_client::dns::{IpPreference, PreferenceResolver};
+/// let http = reqwest::Client::builder()
+///     .dns_resolver(PreferenceResolver::new(IpPreference::V4Only))
+///     .build()
+///     .unwrap();
+/// ```
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct PreferenceResolver {
+    preference: IpPreference,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl PreferenceResolver {
+    pub fn new(preference: IpPreference) -> Self {
+        PreferenceResolver { preference }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Resolve for PreferenceResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let preference = self.preference;
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            // The port is replaced by the URL's port.
+            let addrs = tokio::net::lookup_host((host.as_str(), 0)).await?;
+            let addrs = preference.apply(addrs);
+            if addrs.is_empty() {
+                return Err(format!("{host} has no address of the required IP family").into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ip_preference() {
+        let addrs: Vec<SocketAddr> = ["[::1]:80", "127.0.0.1:80", "[::2]:80"]
+            .iter()
+            .map(|a| a.parse().unwrap())
+            .collect();
+        let v4: SocketAddr = "127.0.0.1:80".parse().unwrap();
+        assert_eq!(IpPreference::Any.apply(addrs.clone()), addrs);
+        assert_eq!(IpPreference::PreferV4.apply(addrs.clone())[0], v4);
+        assert_eq!(IpPreference::PreferV6.apply(addrs.clone())[2], v4);
+        assert_eq!(IpPreference::V4Only.apply(addrs.clone()), [v4]);
+        assert_eq!(IpPreference::V6Only.apply(addrs).len(), 2);
+    }
+}
@@ -0,0 +1,169 @@
+//! Embeddable players for video results.
+//!
+//! Video engines report an `iframe_src` pointing to the provider's player. [`VideoEmbed`]
+//! turns it into iframe markup that is safe to insert into a page: the source is
+//! validated and escaped, the frame is sandboxed, and YouTube players are switched to
+//! the privacy-enhanced `youtube-nocookie.com` domain.
+
+use std::fmt::Write;
+
+use url::Url;
+
+use crate::response::{MainSearchResult, SearchResult};
+
+/// The `sandbox` attribute of generated iframes.
+///
+/// Scripts and same-origin access are needed by every common video player, while
+/// top-level navigation, forms and modal dialogs stay blocked.
+pub const IFRAME_SANDBOX: &str = "allow-scripts allow-same-origin allow-presentation allow-popups";
+
+/// An embeddable video player.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VideoEmbed {
+    /// The player URL, already rewritten to a privacy-enhanced domain where possible.
+    pub src: Url,
+    /// The URL of the video page, used for oEmbed lookups.
+    pub page_url: Option<Url>,
+    /// The video title, used as the accessible title of the frame.
+    pub title: String,
+}
+
+impl VideoEmbed {
+    /// Creates an embed for the player at `iframe_src`.
+    ///
+    /// Returns `None` unless `iframe_src` is an absolute `http` or `https` URL.
+    pub fn new(iframe_src: &str, title: impl Into<String>) -> Option<Self> {
+        let src = Url::parse(iframe_src.trim()).ok()?;
+        if !matches!(src.scheme(), "http" | "https") {
+            return None;
+        }
+        Some(VideoEmbed {
+            src: privacy_enhanced(src),
+            page_url: None,
+            title: title.into(),
+        })
+    }
+
+    /// Sets the URL of the video page.
+    pub fn with_page_url(mut self, page_url: &str) -> Self {
+        self.page_url = Url::parse(page_url).ok();
+        self
+    }
+
+    /// Renders a sandboxed iframe for this player.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use searxng_client::embed::VideoEmbed;
+    /// let embed = VideoEmbed::new("https://www.youtube.com/embed/abc123", "A \"video\"").unwrap();
+    /// let html = embed.iframe_html();
+    /// assert!(html.starts_with(r#"<iframe src="https://www.youtube-nocookie.com/embed/abc123""#));
+    /// assert!(html.contains(r#"title="A &quot;video&quot;""#));
+    /// assert!(html.contains("sandbox="));
+    /// ```
+    pub fn iframe_html(&self) -> String {
+        let mut html = String::new();
+        let _ = write!(
+            html,
+            r#"<iframe src="{}" title="{}" sandbox="{IFRAME_SANDBOX}" allow="fullscreen; picture-in-picture; encrypted-media" referrerpolicy="strict-origin-when-cross-origin" loading="lazy" frameborder="0" allowfullscreen></iframe>"#,
+            escape_attr(self.src.as_str()),
+            escape_attr(&self.title),
+        );
+        html
+    }
+
+    /// Returns the oEmbed endpoint describing this video, for the providers known to
+    /// support oEmbed (YouTube, Vimeo and Dailymotion).
+    ///
+    /// Requires the [page URL](Self::with_page_url).
+    pub fn oembed_url(&self) -> Option<Url> {
+        let page_url = self.page_url.as_ref()?;
+        let endpoint = match page_url.host_str()?.trim_start_matches("www.") {
+            "youtube.com" | "m.youtube.com" | "youtu.be" => "https://www.youtube.com/oembed",
+            "vimeo.com" => "https://vimeo.com/api/oembed.json",
+            "dailymotion.com" | "dai.ly" => "https://www.dailymotion.com/services/oembed",
+            _ => return None,
+        };
+        Url::parse_with_params(endpoint, [("url", page_url.as_str()), ("format", "json")]).ok()
+    }
+}
+
+/// Rewrites YouTube players to `www.youtube-nocookie.com`, which does not set tracking
+/// cookies until the video is played.
+fn privacy_enhanced(mut src: Url) -> Url {
+    if matches!(
+        src.host_str(),
+        Some("youtube.com" | "www.youtube.com" | "m.youtube.com")
+    ) && src.path().starts_with("/embed/")
+    {
+        let _ = src.set_host(Some("www.youtube-nocookie.com"));
+        let _ = src.set_scheme("https");
+    }
+    src
+}
+
+fn escape_attr(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl MainSearchResult {
+    /// Returns an embeddable player, if this result has a valid `iframe_src`.
+    pub fn video_embed(&self) -> Option<VideoEmbed> {
+        let embed = VideoEmbed::new(&self.iframe_src, self.title.clone())?;
+        Some(match &self.url {
+            Some(url) => embed.with_page_url(url),
+            None => embed,
+        })
+    }
+}
+
+impl SearchResult {
+    /// Returns an embeddable player, if this is a video result with a valid `iframe_src`.
+    ///
+    /// Legacy results carry no player and always return `None`.
+    pub fn video_embed(&self) -> Option<VideoEmbed> {
+        match self {
+            SearchResult::LegacyResult(_) => None,
+            SearchResult::MainResult(m) => m.video_embed(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_video_embed() {
+        assert!(VideoEmbed::new("javascript:alert(1)", "x").is_none());
+        assert!(VideoEmbed::new("/relative/player", "x").is_none());
+
+        let embed = VideoEmbed::new("https://player.vimeo.com/video/42\"><script>", "Talk")
+            .unwrap()
+            .with_page_url("https://vimeo.com/42");
+        assert!(!embed.iframe_html().contains("<script>"));
+        assert_eq!(
+            embed.oembed_url().unwrap().as_str(),
+            "https://vimeo.com/api/oembed.json?url=https%3A%2F%2Fvimeo.com%2F42&format=json"
+        );
+
+        let embed = VideoEmbed::new("http://youtube.com/embed/xyz?start=10", "").unwrap();
+        assert_eq!(
+            embed.src.as_str(),
+            "https://www.youtube-nocookie.com/embed/xyz?start=10"
+        );
+        assert!(embed.oembed_url().is_none());
+    }
+}
@@ -0,0 +1,196 @@
+//! Base URL normalization and endpoint routing.
+//!
+//! A SearXNG instance exposes several endpoints (`/search`, `/autocompleter`, `/config`,
+//! ...) under a common root, which may itself be a subpath behind a reverse proxy. All
+//! endpoint URLs are derived here from a single normalized root.
+
+use std::fmt;
+use std::str::FromStr;
+
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ConfigError;
+
+/// An endpoint exposed by a SearXNG instance.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum Endpoint {
+    Search,
+    Autocompleter,
+    Config,
+    Stats,
+    Healthz,
+}
+
+impl Endpoint {
+    const ALL: [Endpoint; 5] = [
+        Endpoint::Search,
+        Endpoint::Autocompleter,
+        Endpoint::Config,
+        Endpoint::Stats,
+        Endpoint::Healthz,
+    ];
+
+    fn path(self) -> &'static str {
+        match self {
+            Endpoint::Search => "search",
+            Endpoint::Autocompleter => "autocompleter",
+            Endpoint::Config => "config",
+            Endpoint::Stats => "stats",
+            Endpoint::Healthz => "healthz",
+        }
+    }
+}
+
+/// Normalizes an instance base URL to its root, always ending with `/`.
+///
+/// The full path is preserved so instances served under a subpath or a reverse-proxy
+/// prefix (e.g. `https://host/searxng/`) work. Query strings and fragments are dropped,
+/// and a base URL pointing at one of the known endpoints is reduced to its root.
+pub(crate) fn normalize_root(base_url: &str) -> String {
+    let trimmed = base_url.trim().trim_end_matches('/');
+    let mut root = match Url::parse(trimmed) {
+        Ok(mut url) => {
+            url.set_query(None);
+            url.set_fragment(None);
+            url.as_str().trim_end_matches('/').to_string()
+        }
+        Err(_) => trimmed.to_string(),
+    };
+
+    if let Some((parent, last)) = root.rsplit_once('/')
+        && Endpoint::ALL.iter().any(|e| e.path() == last)
+        && !parent.ends_with('/')
+    {
+        root.truncate(parent.len());
+    }
+    root.push('/');
+    root
+}
+
+/// A validated, normalized instance root URL.
+///
+/// Constructing one checks that the URL parses, uses `http` or `https` and has a host,
+/// so that a malformed base URL is caught at setup instead of at the first request. The
+/// root is normalized like the base URL of [`SearXNGClient::new`](crate::SearXNGClient::new):
+/// it keeps any subpath, drops queries, fragments and known endpoint paths, and always
+/// ends with `/`. Everything that accepts a base URL also accepts an `InstanceUrl`.
+///
+/// # Examples
+///
+/// ```
+/// use searxng_client::{InstanceUrl, ResponseFormat, SearXNGClient};
+/// let url = InstanceUrl::try_from("https://host/searxng/search?q=rust")?;
+/// assert_eq!(url.as_str(), "https://host/searxng/");
+/// assert!(InstanceUrl::try_from("searx.be").is_err());
+/// let client = SearXNGClient::new(url, ResponseFormat::Json);
+/// # Ok::<(), searxng_client::ConfigError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct InstanceUrl(String);
+
+impl InstanceUrl {
+    /// The normalized root, always ending with `/`.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The root as a parsed [`Url`].
+    pub fn url(&self) -> Url {
+        Url::parse(&self.0).expect("an instance URL is valid")
+    }
+}
+
+impl TryFrom<&str> for InstanceUrl {
+    type Error = ConfigError;
+
+    fn try_from(base_url: &str) -> Result<Self, Self::Error> {
+        let invalid = || ConfigError::InvalidUrl {
+            url: base_url.to_string(),
+        };
+        let root = normalize_root(base_url);
+        let url = Url::parse(&root).map_err(|_| invalid())?;
+        if !matches!(url.scheme(), "http" | "https") || url.host().is_none() {
+            return Err(invalid());
+        }
+        Ok(InstanceUrl(root))
+    }
+}
+
+impl TryFrom<String> for InstanceUrl {
+    type Error = ConfigError;
+
+    fn try_from(base_url: String) -> Result<Self, Self::Error> {
+        InstanceUrl::try_from(base_url.as_str())
+    }
+}
+
+impl FromStr for InstanceUrl {
+    type Err = ConfigError;
+
+    fn from_str(base_url: &str) -> Result<Self, Self::Err> {
+        InstanceUrl::try_from(base_url)
+    }
+}
+
+impl From<InstanceUrl> for String {
+    fn from(url: InstanceUrl) -> Self {
+        url.0
+    }
+}
+
+impl AsRef<str> for InstanceUrl {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for InstanceUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Builds the URL of `endpoint` for an instance whose normalized root is `root`.
+pub(crate) fn endpoint_url(root: &str, endpoint: Endpoint) -> String {
+    format!("{root}{}", endpoint.path())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint_urls() {
+        for (base, expected) in [
+            ("https://searx.be", "https://searx.be/search"),
+            ("https://searx.be/", "https://searx.be/search"),
+            ("https://host/searxng", "https://host/searxng/search"),
+            ("https://host/searxng/", "https://host/searxng/search"),
+            ("https://host/a/b//", "https://host/a/b/search"),
+            ("https://host/searxng/search", "https://host/searxng/search"),
+            (
+                "https://host/searxng/?x=1#top",
+                "https://host/searxng/search",
+            ),
+            ("http://localhost:8089/", "http://localhost:8089/search"),
+        ] {
+            let root = normalize_root(base);
+            assert_eq!(endpoint_url(&root, Endpoint::Search), expected, "{base}");
+        }
+
+        for invalid in ["searx.be", "ftp://searx.be/", "file:///srv/searxng", ""] {
+            assert!(InstanceUrl::try_from(invalid).is_err(), "{invalid}");
+        }
+        let url: InstanceUrl = "https://host/searxng".parse().unwrap();
+        assert_eq!(String::from(url.clone()), "https://host/searxng/");
+        assert_eq!(normalize_root(url.as_str()), url.as_str());
+
+        let root = normalize_root("https://host/searxng/config");
+        assert_eq!(
+            endpoint_url(&root, Endpoint::Autocompleter),
+            "https://host/searxng/autocompleter"
+        );
+    }
+}
@@ -0,0 +1,85 @@
+//! Error types.
+
+use std::time::Duration;
+
+use reqwest::StatusCode;
+
+use crate::parse::ParseError;
+
+/// An invalid client configuration.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ConfigError {
+    /// The instance URL could not be parsed.
+    #[error("invalid instance URL: {url}")]
+    InvalidUrl {
+        /// The offending URL.
+        url: String,
+    },
+    /// The instance URL uses plain HTTP while HTTPS is required.
+    #[error("refusing to send queries over plain HTTP to {url}")]
+    InsecureScheme {
+        /// The offending URL.
+        url: String,
+    },
+}
+
+/// An error returned by a search request.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The request could not be sent, timed out, or its response could not be read.
+    #[error("request failed: {0}")]
+    Transport(#[from] reqwest::Error),
+    /// The instance answered with a status code that is not 2xx, other than 429.
+    #[error("instance answered with HTTP {status}")]
+    Http {
+        status: StatusCode,
+        /// The response body, usually an HTML error page.
+        body: String,
+    },
+    /// The instance answered with HTTP 429 Too Many Requests.
+    #[error("rate limited by the instance{}", wait_hint(*retry_after))]
+    RateLimited {
+        /// How long the instance asked to wait, from the `Retry-After` header.
+        retry_after: Option<Duration>,
+    },
+    /// The response body could not be parsed under the client's
+    /// [`ParseMode`](crate::parse::ParseMode).
+    #[error("failed to decode the response: {source}")]
+    Decode {
+        source: ParseError,
+        /// The body that failed to decode, decoded as UTF-8 lossily.
+        raw_body: String,
+    },
+    /// A [`ResultSink`](crate::sink::ResultSink) failed to write results.
+    #[error("failed to write results: {0}")]
+    Sink(std::io::Error),
+}
+
+fn wait_hint(retry_after: Option<Duration>) -> String {
+    match retry_after {
+        Some(wait) => format!(", retry after {}s", wait.as_secs()),
+        None => String::new(),
+    }
+}
+
+impl Error {
+    /// The HTTP status the instance answered with, if it answered.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Error::Transport(e) => e.status(),
+            Error::Http { status, .. } => Some(*status),
+            Error::RateLimited { .. } => Some(StatusCode::TOO_MANY_REQUESTS),
+            Error::Decode { .. } | Error::Sink(_) => None,
+        }
+    }
+
+    /// Returns `true` if the request timed out.
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, Error::Transport(e) if e.is_timeout())
+    }
+
+    /// Returns `true` if the instance is rate limiting this client.
+    pub fn is_rate_limited(&self) -> bool {
+        matches!(self, Error::RateLimited { .. })
+    }
+}
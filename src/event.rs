@@ -0,0 +1,192 @@
+//! Best-effort extraction of events and iCalendar export.
+//!
+//! Some engines report event data (dates and venues) in news and general results.
+//! [`Event::from_result`] turns every result with a parsed publication date into an
+//! event, and [`to_ics`] renders events as an RFC 5545 calendar, so "upcoming events
+//! about X" monitors can be subscribed to from any calendar application.
+
+use chrono::{DateTime, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::redact::query_hash;
+use crate::response::{SearchResponse, SearchResult};
+
+/// The product identifier written to exported calendars.
+pub const ICS_PRODUCT_ID: &str = "-//searxng-client//searxng-rust-client 0.1//EN";
+
+/// An event extracted from a result.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Event {
+    /// The event title.
+    pub summary: String,
+    /// The result snippet.
+    pub description: String,
+    /// The link to the event page.
+    pub url: Option<String>,
+    /// When the event starts.
+    pub start: DateTime<Utc>,
+    /// Whether the event lasts the whole day, because the engine reported no time of day.
+    pub all_day: bool,
+    /// The venue, if the result carries an address.
+    pub location: Option<String>,
+}
+
+impl Event {
+    /// Extracts an event from a result with a parsed publication date.
+    ///
+    /// Dates without an offset and at midnight are treated as all-day events.
+    pub fn from_result(result: &SearchResult) -> Option<Self> {
+        let date = result.published_date()?;
+        Some(Event {
+            summary: result.title().trim().to_string(),
+            description: result.content().trim().to_string(),
+            url: result.url().map(str::to_string),
+            start: date.to_utc(),
+            all_day: date.offset().is_none() && date.time() == NaiveTime::MIN,
+            location: (result.template_fields().address.as_ref())
+                .map(|a| a.to_line())
+                .filter(|line| !line.is_empty()),
+        })
+    }
+
+    /// Returns `true` if the event starts at or after `now`. All-day events count as
+    /// upcoming for the whole day.
+    pub fn is_upcoming(&self, now: DateTime<Utc>) -> bool {
+        if self.all_day {
+            self.start.date_naive() >= now.date_naive()
+        } else {
+            self.start >= now
+        }
+    }
+
+    /// A stable identifier derived from the event URL, or from its title and start.
+    pub fn uid(&self) -> String {
+        let source = match &self.url {
+            Some(url) => url.clone(),
+            None => format!("{}@{}", self.summary, self.start.to_rfc3339()),
+        };
+        format!("{}@searxng-client", query_hash(&source))
+    }
+
+    fn write_ics(&self, ics: &mut String, stamp: DateTime<Utc>) {
+        push_line(ics, "BEGIN:VEVENT");
+        push_line(ics, &format!("UID:{}", self.uid()));
+        push_line(ics, &format!("DTSTAMP:{}", stamp.format("%Y%m%dT%H%M%SZ")));
+        if self.all_day {
+            push_line(
+                ics,
+                &format!("DTSTART;VALUE=DATE:{}", self.start.format("%Y%m%d")),
+            );
+        } else {
+            push_line(
+                ics,
+                &format!("DTSTART:{}", self.start.format("%Y%m%dT%H%M%SZ")),
+            );
+        }
+        push_line(ics, &format!("SUMMARY:{}", escape_text(&self.summary)));
+        if !self.description.is_empty() {
+            push_line(
+                ics,
+                &format!("DESCRIPTION:{}", escape_text(&self.description)),
+            );
+        }
+        if let Some(location) = &self.location {
+            push_line(ics, &format!("LOCATION:{}", escape_text(location)));
+        }
+        if let Some(url) = &self.url {
+            push_line(ics, &format!("URL:{url}"));
+        }
+        push_line(ics, "END:VEVENT");
+    }
+}
+
+/// Escapes a TEXT value as required by RFC 5545, section 3.3.11.
+fn escape_text(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Appends a content line, folded to at most 75 octets per line.
+fn push_line(ics: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            ics.push_str("\r\n ");
+            width = 1;
+        }
+        ics.push(c);
+        width += c.len_utf8();
+    }
+    ics.push_str("\r\n");
+}
+
+/// Renders `events` as an iCalendar document.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use searxng_client::event::{Event, to_ics};
+/// let event = Event {
+///     summary: "RustConf, 2026".into(),
+///     description: String::new(),
+///     url: Some("https://rustconf.com".into()),
+///     start: Utc.with_ymd_and_hms(2026, 9, 8, 0, 0, 0).unwrap(),
+///     all_day: true,
+///     location: None,
+/// };
+/// let ics = to_ics([&event]);
+/// assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+/// assert!(ics.contains("DTSTART;VALUE=DATE:20260908\r\n"));
+/// assert!(ics.contains("SUMMARY:RustConf\\, 2026\r\n"));
+/// ```
+pub fn to_ics<'a>(events: impl IntoIterator<Item = &'a Event>) -> String {
+    let stamp = Utc::now();
+    let mut ics = String::new();
+    push_line(&mut ics, "BEGIN:VCALENDAR");
+    push_line(&mut ics, "VERSION:2.0");
+    push_line(&mut ics, &format!("PRODID:{ICS_PRODUCT_ID}"));
+    for event in events {
+        event.write_ics(&mut ics, stamp);
+    }
+    push_line(&mut ics, "END:VCALENDAR");
+    ics
+}
+
+impl SearchResponse {
+    /// Extracts the events of this response, see [`Event::from_result`].
+    pub fn events(&self) -> Vec<Event> {
+        self.results.iter().filter_map(Event::from_result).collect()
+    }
+
+    /// Renders the events of this response as an iCalendar document.
+    pub fn to_ics(&self) -> String {
+        to_ics(&self.events())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_folding() {
+        let mut ics = String::new();
+        push_line(&mut ics, &format!("SUMMARY:{}", "é".repeat(40)));
+        let lines: Vec<&str> = ics.trim_end().split("\r\n").collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|l| l.len() <= 75));
+        assert!(lines[1].starts_with(' '));
+    }
+}
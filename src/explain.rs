@@ -0,0 +1,134 @@
+//! Human-readable explanations of how SearXNG ranked a result.
+//!
+//! SearXNG's score is derived from the positions a result reached in each engine, the
+//! number of engines that returned it, and the result's priority. [`ScoreExplanation`]
+//! gathers these inputs in one place for relevance debugging.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::response::{PriorityType, SearchResult};
+
+/// The position a result reached in a single engine.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EngineRanking {
+    /// The engine name.
+    pub engine: String,
+    /// The 1-based position in that engine's result list, if known.
+    pub position: Option<i32>,
+}
+
+/// The ranking inputs of a result, see [`SearchResult::explain`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoreExplanation {
+    /// The final score computed by SearXNG.
+    pub score: f64,
+    /// The priority assigned to the result.
+    pub priority: PriorityType,
+    /// The per-engine rankings.
+    pub rankings: Vec<EngineRanking>,
+}
+
+fn ordinal(n: i32) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{n}{suffix}")
+}
+
+impl fmt::Display for ScoreExplanation {
+    /// Formats the explanation, e.g. `ranked 3rd by google, 5th by brave; priority high; score 4.50`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rankings = self
+            .rankings
+            .iter()
+            .map(|r| match r.position {
+                Some(p) => format!("{} by {}", ordinal(p), r.engine),
+                None => format!("returned by {}", r.engine),
+            })
+            .collect::<Vec<_>>();
+        if rankings.is_empty() {
+            write!(f, "not ranked by any engine")?;
+        } else {
+            write!(f, "ranked {}", rankings.join(", "))?;
+        }
+
+        let priority = match self.priority {
+            PriorityType::None => "normal",
+            PriorityType::High => "high",
+            PriorityType::Low => "low",
+        };
+        write!(f, "; priority {priority}; score {:.2}", self.score)
+    }
+}
+
+impl SearchResult {
+    /// Explains how this result was ranked.
+    ///
+    /// Engines and positions are paired in the order SearXNG reports them. When the two
+    /// lists have different lengths, engines without a matching position are reported
+    /// without one.
+    pub fn explain(&self) -> ScoreExplanation {
+        let (engines, positions, priority, score) = match self {
+            SearchResult::LegacyResult(l) => (&l.engines, &l.positions, &l.priority, l.score),
+            SearchResult::MainResult(m) => (&m.engines, &m.positions, &m.priority, m.score),
+        };
+
+        let rankings = engines
+            .iter()
+            .enumerate()
+            .map(|(i, engine)| EngineRanking {
+                engine: engine.to_string(),
+                position: positions.get(i).copied(),
+            })
+            .collect();
+
+        ScoreExplanation {
+            score,
+            priority: priority.clone(),
+            rankings,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::sample_result;
+
+    #[test]
+    fn test_explain() {
+        let result = sample_result("https://www.rust-lang.org", "Rust", "");
+        assert_eq!(
+            result.explain().to_string(),
+            "ranked 1st by duckduckgo; priority normal; score 1.00"
+        );
+
+        let result: SearchResult = serde_json::from_value(serde_json::json!({
+            "url": "https://www.rust-lang.org",
+            "template": "default.html",
+            "engine": "google",
+            "title": "Rust",
+            "content": "",
+            "img_src": "",
+            "thumbnail": "",
+            "priority": "high",
+            "engines": ["google", "brave", "bing"],
+            "positions": [3, 5],
+            "score": 4.5,
+            "category": "general"
+        }))
+        .unwrap();
+        assert_eq!(
+            result.explain().to_string(),
+            "ranked 3rd by google, 5th by brave, returned by bing; priority high; score 4.50"
+        );
+        assert_eq!(ordinal(12), "12th");
+        assert_eq!(ordinal(23), "23rd");
+    }
+}
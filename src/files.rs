@@ -0,0 +1,272 @@
+//! Content-type aware handling of file results.
+//!
+//! Results of the files category report their media type and size as free-form strings
+//! (`"application/pdf"`, `"1.4 MB"`). [`FileResult`] parses them, and [`FileFilter`]
+//! selects files by type and size. Being a [`ContentFilter`], it can be set on a request
+//! with [`SearchBuilder::set_filter`](crate::client::SearchBuilder::set_filter), so it
+//! applies to every page fetched during pagination.
+
+use mime::Mime;
+
+use crate::filter::ContentFilter;
+use crate::response::{
+    LegacySearchResult, MainSearchResult, ResultRef, SearchResult, TemplateFields,
+};
+
+/// Parses a human-readable size such as `"1.4 MB"`, `"512 KiB"` or `"2048"` into bytes.
+///
+/// Decimal units (`KB`, `MB`, ...) are powers of 1000 and binary units (`KiB`, `MiB`, ...)
+/// powers of 1024.
+///
+/// # Examples
+///
+/// ```
+/// use searxng_client::files::parse_size;
+/// assert_eq!(parse_size("1.5 MB"), Some(1_500_000));
+/// assert_eq!(parse_size("2KiB"), Some(2048));
+/// assert_eq!(parse_size("1,024 bytes"), Some(1024));
+/// assert_eq!(parse_size("large"), None);
+/// ```
+pub fn parse_size(size: &str) -> Option<u64> {
+    let size = size.trim().replace(',', "");
+    let split = size
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(size.len());
+    let (number, unit) = size.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" | "byte" | "bytes" => 1,
+        "k" | "kb" => 1_000,
+        "m" | "mb" => 1_000_000,
+        "g" | "gb" => 1_000_000_000,
+        "t" | "tb" => 1_000_000_000_000,
+        "kib" => 1 << 10,
+        "mib" => 1 << 20,
+        "gib" => 1 << 30,
+        "tib" => 1 << 40,
+        _ => return None,
+    };
+    Some((number * multiplier as f64).round() as u64)
+}
+
+/// The typed view of a file result.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileResult {
+    pub title: String,
+    pub url: Option<String>,
+    pub content: String,
+    pub filename: Option<String>,
+    /// The media type, from `mimetype` or from `mtype` and `subtype`.
+    pub mime: Option<Mime>,
+    /// The size in bytes, if the engine reported a parsable size.
+    pub size: Option<u64>,
+    /// An embeddable preview, such as a player URL for audio or video files.
+    pub embedded: Option<String>,
+}
+
+impl TemplateFields {
+    /// The media type of the result, if reported.
+    pub fn mime(&self) -> Option<Mime> {
+        match (&self.mimetype, &self.mtype, &self.subtype) {
+            (Some(mimetype), _, _) => mimetype.trim().parse().ok(),
+            (None, Some(mtype), Some(subtype)) => {
+                format!("{}/{}", mtype.trim(), subtype.trim()).parse().ok()
+            }
+            _ => None,
+        }
+    }
+
+    /// The size of the file in bytes, from `size` or the torrent `filesize`.
+    pub fn size_bytes(&self) -> Option<u64> {
+        (self.size.as_deref())
+            .or(self.filesize.as_deref())
+            .and_then(parse_size)
+    }
+}
+
+impl ResultRef<'_> {
+    pub(crate) fn file(self) -> Option<FileResult> {
+        let fields = self.template_fields();
+        let mime = fields.mime();
+        if mime.is_none() && !matches!(self.template_name(), "files" | "file") {
+            return None;
+        }
+        Some(FileResult {
+            title: self.title().to_string(),
+            url: self.url().map(str::to_string),
+            content: self.content().to_string(),
+            filename: fields.filename.clone(),
+            mime,
+            size: fields.size_bytes(),
+            embedded: fields.embedded.clone(),
+        })
+    }
+}
+
+impl SearchResult {
+    /// The media type of this result, if reported.
+    pub fn mime(&self) -> Option<Mime> {
+        self.template_fields().mime()
+    }
+
+    /// The size of the file in bytes, if reported.
+    pub fn size_bytes(&self) -> Option<u64> {
+        self.template_fields().size_bytes()
+    }
+
+    /// Returns the typed view of this result, if it is a file result.
+    ///
+    /// Results using the `files.html` or `file.html` template, or reporting a media type,
+    /// are considered files.
+    pub fn file(&self) -> Option<FileResult> {
+        self.by_ref().file()
+    }
+}
+
+impl MainSearchResult {
+    /// Same as [`SearchResult::mime`].
+    pub fn mime(&self) -> Option<Mime> {
+        self.template_fields.mime()
+    }
+
+    /// Same as [`SearchResult::size_bytes`].
+    pub fn size_bytes(&self) -> Option<u64> {
+        self.template_fields.size_bytes()
+    }
+
+    /// Same as [`SearchResult::file`].
+    pub fn file(&self) -> Option<FileResult> {
+        ResultRef::Main(self).file()
+    }
+}
+
+impl LegacySearchResult {
+    /// Same as [`SearchResult::mime`].
+    pub fn mime(&self) -> Option<Mime> {
+        self.template_fields.mime()
+    }
+
+    /// Same as [`SearchResult::size_bytes`].
+    pub fn size_bytes(&self) -> Option<u64> {
+        self.template_fields.size_bytes()
+    }
+
+    /// Same as [`SearchResult::file`].
+    pub fn file(&self) -> Option<FileResult> {
+        ResultRef::Legacy(self).file()
+    }
+}
+
+/// Keeps results by media type and size.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use searxng_client::{SearXNGClient, ResponseFormat};
+/// use searxng_client::files::FileFilter;
+/// # tokio_test::block_on(async {
+/// # let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json);
+/// let pdfs = client
+///     .search("rust book")
+///     .set_filter(FileFilter::new().only_mime("application/pdf").max_size(20_000_000))
+///     .send_get_num(30)
+///     .await?;
+/// # Ok::<(), searxng_client::Error>(())
+/// # });
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FileFilter {
+    mimes: Vec<Mime>,
+    max_size: Option<u64>,
+}
+
+impl FileFilter {
+    /// Creates a filter that keeps every result.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keeps only results of the given media type. Calling it several times keeps results
+    /// matching any of the types; `image/*` matches every image type.
+    ///
+    /// Invalid media types are ignored. Results without a media type are rejected once a
+    /// type has been set.
+    pub fn only_mime(mut self, mime: &str) -> Self {
+        if let Ok(mime) = mime.parse() {
+            self.mimes.push(mime);
+        }
+        self
+    }
+
+    /// Rejects files larger than `bytes`. Results without a known size are kept.
+    pub fn max_size(mut self, bytes: u64) -> Self {
+        self.max_size = Some(bytes);
+        self
+    }
+}
+
+impl ContentFilter for FileFilter {
+    fn allows(&self, result: &SearchResult) -> bool {
+        if !self.mimes.is_empty() {
+            let Some(mime) = result.mime() else {
+                return false;
+            };
+            let matches = self.mimes.iter().any(|wanted| {
+                wanted.type_() == mime.type_()
+                    && (wanted.subtype() == mime::STAR || wanted.subtype() == mime.subtype())
+            });
+            if !matches {
+                return false;
+            }
+        }
+        match (self.max_size, result.size_bytes()) {
+            (Some(max), Some(size)) => size <= max,
+            _ => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A result of the `files.html` template, or of the default one without `mimetype`.
+    fn file(mimetype: Option<&str>, size: &str) -> SearchResult {
+        serde_json::from_value(serde_json::json!({
+            "url": "https://example.org/f.pdf",
+            "template": if mimetype.is_some() { "files.html" } else { "default.html" },
+            "engine": "bt4g",
+            "title": "f.pdf",
+            "content": "",
+            "img_src": "",
+            "thumbnail": "",
+            "priority": "",
+            "engines": ["bt4g"],
+            "positions": [1],
+            "score": 1.0,
+            "category": "files",
+            "mimetype": mimetype,
+            "size": size,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_file_filter() {
+        let pdf = file(Some("application/pdf"), "2.5 MB");
+        assert_eq!(pdf.file().unwrap().size, Some(2_500_000));
+
+        let filter = FileFilter::new()
+            .only_mime("application/pdf")
+            .max_size(10_000_000);
+        assert!(filter.allows(&pdf));
+        assert!(!filter.allows(&file(Some("application/pdf"), "12 MB")));
+        assert!(!filter.allows(&file(Some("image/png"), "1 KB")));
+        assert!(!filter.allows(&file(None, "1 KB")));
+        assert!(
+            FileFilter::new()
+                .only_mime("image/*")
+                .allows(&file(Some("image/png"), "1 KB"))
+        );
+    }
+}
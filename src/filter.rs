@@ -0,0 +1,138 @@
+//! Client-side content filtering applied after a response has been parsed.
+//!
+//! SearXNG's `safesearch` parameter is forwarded to the engines, and not every engine
+//! honours it. For kiosk or classroom deployments a second, local line of defence is
+//! needed: a [`ContentFilter`] is run against every parsed result and rejected results
+//! are dropped before they reach the caller.
+
+use std::collections::HashSet;
+use std::fmt::Debug;
+
+use reqwest::Url;
+
+use crate::response::SearchResult;
+
+/// A pluggable predicate deciding whether a result may be shown.
+pub trait ContentFilter: Debug + Send + Sync {
+    /// Returns `true` if the result should be kept.
+    fn allows(&self, result: &SearchResult) -> bool;
+}
+
+const DEFAULT_BLOCKED_DOMAINS: &[&str] = &[
+    "pornhub.com",
+    "xvideos.com",
+    "xnxx.com",
+    "xhamster.com",
+    "redtube.com",
+    "youporn.com",
+    "onlyfans.com",
+];
+
+const DEFAULT_BLOCKED_KEYWORDS: &[&str] = &[
+    "porn", "porno", "xxx", "nsfw", "hentai", "nude", "nudes", "escort",
+];
+
+/// A heuristic filter based on domain lists and keyword matching.
+///
+/// A result is rejected if its URL belongs to a blocked domain (subdomains included),
+/// or if its URL, title or content contains a blocked keyword as a whole word.
+#[derive(Debug, Clone, Default)]
+pub struct SafeContentFilter {
+    blocked_domains: HashSet<String>,
+    blocked_keywords: HashSet<String>,
+}
+
+impl SafeContentFilter {
+    /// Creates a filter pre-populated with a small built-in list of adult domains and keywords.
+    pub fn new() -> Self {
+        let mut filter = Self::empty();
+        filter
+            .blocked_domains
+            .extend(DEFAULT_BLOCKED_DOMAINS.iter().map(|d| d.to_string()));
+        filter
+            .blocked_keywords
+            .extend(DEFAULT_BLOCKED_KEYWORDS.iter().map(|k| k.to_string()));
+        filter
+    }
+
+    /// Creates a filter without any built-in rules.
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    /// Blocks a domain and all of its subdomains.
+    pub fn block_domain(mut self, domain: impl Into<String>) -> Self {
+        let domain = domain.into().to_ascii_lowercase();
+        self.blocked_domains
+            .insert(domain.trim_start_matches("www.").to_string());
+        self
+    }
+
+    /// Blocks results containing the given keyword as a whole word (case-insensitive).
+    pub fn block_keyword(mut self, keyword: impl Into<String>) -> Self {
+        self.blocked_keywords.insert(keyword.into().to_lowercase());
+        self
+    }
+
+    fn is_blocked_host(&self, host: &str) -> bool {
+        let host = host.to_ascii_lowercase();
+        let mut candidate = host.as_str();
+        loop {
+            if self.blocked_domains.contains(candidate) {
+                return true;
+            }
+            match candidate.split_once('.') {
+                Some((_, parent)) if parent.contains('.') => candidate = parent,
+                _ => return false,
+            }
+        }
+    }
+
+    fn contains_blocked_keyword(&self, text: &str) -> bool {
+        text.to_lowercase()
+            .split(|c: char| !c.is_alphanumeric())
+            .any(|word| self.blocked_keywords.contains(word))
+    }
+}
+
+impl ContentFilter for SafeContentFilter {
+    fn allows(&self, result: &SearchResult) -> bool {
+        let (url, title, content) = match result {
+            SearchResult::LegacyResult(l) => (l.url.as_deref(), &l.title, &l.content),
+            SearchResult::MainResult(m) => (m.url.as_deref(), &m.title, &m.content),
+        };
+
+        if let Some(url) = url {
+            if let Some(host) = Url::parse(url).ok().as_ref().and_then(Url::host_str)
+                && self.is_blocked_host(host)
+            {
+                return false;
+            }
+            if self.contains_blocked_keyword(url) {
+                return false;
+            }
+        }
+
+        !(self.contains_blocked_keyword(title) || self.contains_blocked_keyword(content))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::sample_result;
+
+    #[test]
+    fn test_safe_content_filter() {
+        let filter = SafeContentFilter::empty()
+            .block_domain("www.example.org")
+            .block_domain("WWW.Example.com")
+            .block_keyword("casino");
+
+        assert!(!filter.allows(&sample_result("https://cdn.example.org/a", "A", "")));
+        assert!(filter.allows(&sample_result("https://notexample.org/a", "A", "")));
+        assert!(!filter.allows(&sample_result("https://www.example.com/a", "A", "")));
+        assert!(!filter.allows(&sample_result("https://rust-lang.org", "Online Casino", "")));
+        assert!(filter.allows(&sample_result("https://rust-lang.org", "Casinos", "")));
+    }
+}
@@ -0,0 +1,254 @@
+//! The CSV and RSS response formats.
+//!
+//! Besides JSON, SearXNG serves search results as CSV and as an OpenSearch RSS feed.
+//! Both carry far less than a [`SearchResponse`](crate::SearchResponse): no templates,
+//! thumbnails or per-engine positions. They are fetched with
+//! [`SearchBuilder::send_csv`](crate::client::SearchBuilder::send_csv) and
+//! [`SearchBuilder::send_rss`](crate::client::SearchBuilder::send_rss), and their results
+//! convert into [`WebResult`]s, like those of JSON responses.
+
+use chrono::{DateTime, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::parse::ParseError;
+use crate::response::{PublishedDate, WebResult};
+
+/// A result row of a CSV response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CsvResult {
+    pub title: String,
+    pub url: Option<String>,
+    pub content: String,
+    /// The host part of the URL.
+    pub host: String,
+    /// The engine that returned the result.
+    pub engine: String,
+    pub score: Option<f64>,
+}
+
+/// A CSV response.
+///
+/// SearXNG writes one row per result, followed by one row per answer, suggestion and
+/// correction, distinguished by their `type` column.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CsvResponse {
+    pub results: Vec<CsvResult>,
+    pub answers: Vec<String>,
+    pub suggestions: Vec<String>,
+    pub corrections: Vec<String>,
+}
+
+/// A row as written by SearXNG, before being sorted by type.
+#[derive(Deserialize)]
+struct CsvRow {
+    title: String,
+    url: Option<String>,
+    #[serde(default)]
+    content: String,
+    #[serde(default)]
+    host: String,
+    #[serde(default)]
+    engine: String,
+    score: Option<f64>,
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+impl CsvResponse {
+    /// Parses a CSV response body.
+    ///
+    /// Rows of an unknown type are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use searxng_client::formats::CsvResponse;
+    /// let body = "title,url,content,host,engine,score,type\r\n\
+    ///     Rust,https://www.rust-lang.org/,\"A language, fast\",www.rust-lang.org,brave,2.5,result\r\n\
+    ///     rust book,,,,,,suggestion\r\n";
+    /// let response = CsvResponse::parse(body.as_bytes())?;
+    /// assert_eq!(response.results[0].content, "A language, fast");
+    /// assert_eq!(response.suggestions, ["rust book"]);
+    /// # Ok::<(), searxng_client::parse::ParseError>(())
+    /// ```
+    pub fn parse(body: &[u8]) -> Result<Self, ParseError> {
+        let mut response = CsvResponse::default();
+        for row in csv::Reader::from_reader(body).deserialize() {
+            let row: CsvRow = row.map_err(ParseError::Csv)?;
+            match row.kind.as_str() {
+                "result" => response.results.push(CsvResult {
+                    title: row.title,
+                    url: row.url,
+                    content: row.content,
+                    host: row.host,
+                    engine: row.engine,
+                    score: row.score,
+                }),
+                "answer" => response.answers.push(row.title),
+                "suggestion" => response.suggestions.push(row.title),
+                "correction" => response.corrections.push(row.title),
+                _ => {}
+            }
+        }
+        Ok(response)
+    }
+
+    /// Returns the results as [`WebResult`]s.
+    pub fn web_results(&self) -> Vec<WebResult> {
+        self.results.iter().map(WebResult::from).collect()
+    }
+}
+
+impl From<&CsvResult> for WebResult {
+    fn from(result: &CsvResult) -> Self {
+        WebResult {
+            url: result.url.clone(),
+            title: result.title.clone(),
+            content: result.content.clone(),
+            engines: Some(result.engine.clone())
+                .filter(|e| !e.is_empty())
+                .into_iter()
+                .collect(),
+            score: result.score.unwrap_or_default(),
+            category: String::new(),
+            published_date: None,
+            thumbnail: None,
+        }
+    }
+}
+
+/// An item of an RSS response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RssItem {
+    pub title: String,
+    /// The item type, `result` for search results.
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    pub link: Option<String>,
+    #[serde(default)]
+    pub description: String,
+    /// The publication date, as written by the instance.
+    #[serde(rename = "pubDate")]
+    pub pub_date: Option<String>,
+}
+
+impl RssItem {
+    /// Parses [`pub_date`](Self::pub_date).
+    ///
+    /// SearXNG writes dates as `%Y-%m-%d %H:%M:%S%z`, while RSS readers expect RFC 2822
+    /// dates; both are accepted.
+    pub fn published_date(&self) -> Option<PublishedDate> {
+        let raw = self.pub_date.as_deref()?.trim();
+        if let Ok(date) = DateTime::parse_from_str(raw, "%Y-%m-%d %H:%M:%S%z") {
+            return Some(date.into());
+        }
+        if let Ok(date) = DateTime::parse_from_rfc2822(raw) {
+            return Some(date.into());
+        }
+        NaiveDateTime::parse_from_str(raw, "%Y-%m-%d %H:%M:%S")
+            .ok()
+            .map(PublishedDate::from)
+    }
+}
+
+impl From<&RssItem> for WebResult {
+    fn from(item: &RssItem) -> Self {
+        WebResult {
+            url: item.link.clone(),
+            title: item.title.clone(),
+            content: item.description.clone(),
+            engines: Vec::new(),
+            score: 0.0,
+            category: String::new(),
+            published_date: item.published_date(),
+            thumbnail: None,
+        }
+    }
+}
+
+/// The channel of an RSS response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RssChannel {
+    pub title: String,
+    pub link: String,
+    #[serde(default)]
+    pub description: String,
+    /// The estimated total number of results, from `opensearch:totalResults`.
+    #[serde(rename = "totalResults")]
+    pub total_results: Option<i64>,
+    #[serde(rename = "item", default)]
+    pub items: Vec<RssItem>,
+}
+
+#[derive(Deserialize)]
+struct Rss {
+    channel: RssChannel,
+}
+
+impl RssChannel {
+    /// Parses an RSS response body.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use searxng_client::formats::RssChannel;
+    /// let body = r#"<?xml version="1.0" encoding="UTF-8"?>
+    /// <rss version="2.0" xmlns:opensearch="http://a9.com/-/spec/opensearch/1.1/">
+    ///   <channel>
+    ///     <title>SearXNG search: rust</title>
+    ///     <link>https://searx.be/search?q=rust</link>
+    ///     <opensearch:totalResults>1</opensearch:totalResults>
+    ///     <item>
+    ///       <title>Rust &amp; Cargo</title>
+    ///       <type>result</type>
+    ///       <link>https://www.rust-lang.org/</link>
+    ///       <description>A language</description>
+    ///     </item>
+    ///   </channel>
+    /// </rss>"#;
+    /// let channel = RssChannel::parse(body.as_bytes())?;
+    /// assert_eq!(channel.total_results, Some(1));
+    /// assert_eq!(channel.items[0].title, "Rust & Cargo");
+    /// # Ok::<(), searxng_client::parse::ParseError>(())
+    /// ```
+    pub fn parse(body: &[u8]) -> Result<Self, ParseError> {
+        let body = std::str::from_utf8(body)
+            .map_err(|e| ParseError::Rss(quick_xml::DeError::Custom(e.to_string())))?;
+        let rss: Rss = quick_xml::de::from_str(body).map_err(ParseError::Rss)?;
+        Ok(rss.channel)
+    }
+
+    /// Returns the result items as [`WebResult`]s, skipping error messages and other
+    /// items that are not results.
+    pub fn web_results(&self) -> Vec<WebResult> {
+        self.items
+            .iter()
+            .filter(|item| item.kind.as_deref() == Some("result"))
+            .map(WebResult::from)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rss_items() {
+        let body = br#"<rss version="2.0"><channel>
+            <title>SearXNG search: rust</title>
+            <link>https://searx.be/search?q=rust</link>
+            <item><title>Error</title><description>timeout</description></item>
+            <item>
+                <title>Rust</title><type>result</type><link>https://www.rust-lang.org/</link>
+                <description></description><pubDate>2024-05-01 12:00:00+0200</pubDate>
+            </item>
+        </channel></rss>"#;
+        let channel = RssChannel::parse(body).unwrap();
+        assert_eq!(channel.items.len(), 2);
+        let results = channel.web_results();
+        assert_eq!(results.len(), 1);
+        let date = results[0].published_date.unwrap();
+        assert_eq!(date.to_utc().to_rfc3339(), "2024-05-01T10:00:00+00:00");
+    }
+}
@@ -0,0 +1,166 @@
+//! Hedged requests for latency-sensitive searches.
+//!
+//! A hedged search sends the request to a primary instance and, if no answer arrived
+//! within a configurable delay, sends the same request to a secondary instance. The first
+//! successful response wins and the other request is cancelled by dropping it.
+
+use std::pin::pin;
+use std::time::Duration;
+
+use futures::future::{Either, select};
+
+use crate::client::{SearXNGClient, SearchParams};
+use crate::error::Error;
+use crate::response::SearchResponse;
+use crate::rt;
+
+/// The default delay before the secondary instance is queried.
+pub const DEFAULT_HEDGE_DELAY: Duration = Duration::from_millis(500);
+
+/// The response of a hedged search.
+#[derive(Debug, Clone)]
+pub struct HedgedResponse {
+    /// The winning response.
+    pub response: SearchResponse,
+    /// The root URL of the instance that served the response.
+    pub instance: String,
+    /// Whether the secondary instance was queried.
+    pub hedged: bool,
+}
+
+/// Races a primary and a secondary instance, see the [module documentation](self).
+#[derive(Debug, Clone)]
+pub struct Hedged {
+    primary: SearXNGClient,
+    secondary: SearXNGClient,
+    delay: Duration,
+}
+
+impl Hedged {
+    /// Creates a hedged searcher using [`DEFAULT_HEDGE_DELAY`].
+    pub fn new(primary: SearXNGClient, secondary: SearXNGClient) -> Self {
+        Hedged {
+            primary,
+            secondary,
+            delay: DEFAULT_HEDGE_DELAY,
+        }
+    }
+
+    /// Sets how long to wait for the primary instance before querying the secondary one.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Runs a hedged search for `query`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if both instances failed, see [`search_with_params`].
+    ///
+    /// [`search_with_params`]: Hedged::search_with_params
+    pub async fn search(&self, query: impl Into<String>) -> Result<HedgedResponse, Error> {
+        let params = SearchParams::new(query, self.primary.format());
+        self.search_with_params(params).await
+    }
+
+    /// Runs a hedged search with the given parameters.
+    ///
+    /// If the primary instance fails before the delay elapsed, the secondary instance is
+    /// queried immediately.
+    ///
+    /// # Errors
+    ///
+    /// Returns the [`Error`] of the instance that failed last if both instances failed;
+    /// the other error is discarded. If the primary instance failed before the delay
+    /// elapsed, this is always the error of the secondary instance.
+    pub async fn search_with_params(&self, params: SearchParams) -> Result<HedgedResponse, Error> {
+        let primary = self.primary.search("").set_params(params.clone());
+        let secondary = self.secondary.search("").set_params(params);
+        let answer = |client: &SearXNGClient, hedged, response| HedgedResponse {
+            response,
+            instance: client.instance_url().to_string(),
+            hedged,
+        };
+
+        let delay = pin!(rt::sleep(self.delay));
+        let primary_fut = pin!(primary.send());
+        let primary_fut = match select(primary_fut, delay).await {
+            Either::Left((Ok(response), _)) => return Ok(answer(&self.primary, false, response)),
+            Either::Left((Err(_), _)) => {
+                let response = secondary.send().await?;
+                return Ok(answer(&self.secondary, true, response));
+            }
+            Either::Right((_, primary_fut)) => primary_fut,
+        };
+
+        let secondary_fut = pin!(secondary.send());
+        match select(primary_fut, secondary_fut).await {
+            Either::Left((Ok(response), _)) => Ok(answer(&self.primary, true, response)),
+            Either::Left((Err(_), secondary_fut)) => {
+                Ok(answer(&self.secondary, true, secondary_fut.await?))
+            }
+            Either::Right((Ok(response), _)) => Ok(answer(&self.secondary, true, response)),
+            Either::Right((Err(_), primary_fut)) => {
+                Ok(answer(&self.primary, true, primary_fut.await?))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::StatusCode;
+
+    use super::*;
+    use crate::client::ResponseFormat;
+    use crate::retry::RetryPolicy;
+    use crate::test::{empty_response_json, http_response, serve_with};
+
+    /// An instance answering every request with `status` after `delay`.
+    async fn instance(status: &'static str, delay: Duration) -> SearXNGClient {
+        let body = empty_response_json("rust").to_string();
+        let root = serve_with(1, move |_| (delay, http_response(status, "", &body))).await;
+        SearXNGClient::new(root, ResponseFormat::Json).with_retry_policy(RetryPolicy::none())
+    }
+
+    #[tokio::test]
+    async fn test_slow_primary_is_hedged() {
+        let primary = instance("200 OK", Duration::from_secs(2)).await;
+        let secondary = instance("200 OK", Duration::ZERO).await;
+        let hedged = Hedged::new(primary, secondary.clone()).with_delay(Duration::from_millis(20));
+        let answer = hedged.search("rust").await.unwrap();
+        assert!(answer.hedged);
+        assert_eq!(answer.instance, secondary.instance_url());
+    }
+
+    #[tokio::test]
+    async fn test_failed_primary_queries_secondary_immediately() {
+        let primary = instance("500 Internal Server Error", Duration::ZERO).await;
+        let secondary = instance("200 OK", Duration::ZERO).await;
+        let hedged = Hedged::new(primary, secondary.clone()).with_delay(Duration::from_secs(60));
+        let answer = tokio::time::timeout(Duration::from_secs(5), hedged.search("rust"))
+            .await
+            .expect("the secondary instance is queried without waiting for the delay")
+            .unwrap();
+        assert!(answer.hedged);
+        assert_eq!(answer.instance, secondary.instance_url());
+    }
+
+    #[tokio::test]
+    async fn test_both_failing_returns_last_error() {
+        // The primary fails after the secondary was queried, so its error is the last one.
+        let primary = instance("503 Service Unavailable", Duration::from_millis(200)).await;
+        let secondary = instance("500 Internal Server Error", Duration::ZERO).await;
+        let hedged = Hedged::new(primary, secondary).with_delay(Duration::from_millis(20));
+        let error = hedged.search("rust").await.unwrap_err();
+        assert_eq!(error.status(), Some(StatusCode::SERVICE_UNAVAILABLE));
+
+        // The primary fails before the delay, so only the secondary's error is returned.
+        let primary = instance("503 Service Unavailable", Duration::ZERO).await;
+        let secondary = instance("500 Internal Server Error", Duration::ZERO).await;
+        let hedged = Hedged::new(primary, secondary).with_delay(Duration::from_secs(60));
+        let error = hedged.search("rust").await.unwrap_err();
+        assert_eq!(error.status(), Some(StatusCode::INTERNAL_SERVER_ERROR));
+    }
+}
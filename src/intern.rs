@@ -0,0 +1,232 @@
+//! Shared storage for the strings repeated across results.
+//!
+//! Every result names its engines and category, and a large crawl repeats the same few
+//! hundred names thousands of times. These fields are [`Symbol`]s, cheap reference-counted
+//! strings. Each decoded result owns its own copies, unless the client was given an
+//! [`Interner`] with [`SearXNGClient::with_interner`](crate::SearXNGClient::with_interner):
+//! then equal names share one allocation, which cuts the memory used by long-running
+//! collectors holding many results.
+
+use std::borrow::Borrow;
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::response::{SearchResponse, SearchResult};
+
+/// An immutable, cheaply cloneable string.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Symbol(Arc<str>);
+
+impl Symbol {
+    /// Returns `true` if both symbols share the same allocation, as interned symbols do.
+    pub fn ptr_eq(a: &Symbol, b: &Symbol) -> bool {
+        Arc::ptr_eq(&a.0, &b.0)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Deref for Symbol {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Symbol {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for Symbol {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&*self.0, f)
+    }
+}
+
+impl From<&str> for Symbol {
+    fn from(s: &str) -> Self {
+        Symbol(s.into())
+    }
+}
+
+impl From<String> for Symbol {
+    fn from(s: String) -> Self {
+        Symbol(s.into())
+    }
+}
+
+impl From<Symbol> for String {
+    fn from(s: Symbol) -> Self {
+        s.0.to_string()
+    }
+}
+
+impl PartialEq<str> for Symbol {
+    fn eq(&self, other: &str) -> bool {
+        &*self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Symbol {
+    fn eq(&self, other: &&str) -> bool {
+        &*self.0 == *other
+    }
+}
+
+impl PartialEq<String> for Symbol {
+    fn eq(&self, other: &String) -> bool {
+        *self.0 == **other
+    }
+}
+
+impl Serialize for Symbol {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Symbol {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Symbol::from)
+    }
+}
+
+/// A thread-safe set of [`Symbol`]s, handing out one shared allocation per distinct string.
+///
+/// Symbols are never evicted, so an interner should only be used for small vocabularies
+/// such as engine and category names. Share one interner between the clients of an
+/// aggregation by cloning the [`Arc`].
+///
+/// # Examples
+///
+/// ```
+/// use searxng_client::intern::{Interner, Symbol};
+/// let interner = Interner::new();
+/// let a = interner.intern("duckduckgo");
+/// let b = interner.intern("duckduckgo");
+/// assert!(Symbol::ptr_eq(&a, &b));
+/// assert_eq!(interner.len(), 1);
+/// ```
+#[derive(Debug, Default)]
+pub struct Interner {
+    symbols: Mutex<HashSet<Symbol>>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the shared symbol equal to `s`, adding it if it is new.
+    pub fn intern(&self, s: &str) -> Symbol {
+        let mut symbols = self.symbols.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(symbol) = symbols.get(s) {
+            return symbol.clone();
+        }
+        let symbol = Symbol::from(s);
+        symbols.insert(symbol.clone());
+        symbol
+    }
+
+    /// The number of distinct symbols.
+    pub fn len(&self) -> usize {
+        self.symbols.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Replaces the engine and category names of `result` with shared symbols.
+    pub fn intern_result(&self, result: &mut SearchResult) {
+        macro_rules! intern_fields {
+            ($r:expr) => {{
+                for engine in $r.engines.iter_mut() {
+                    *engine = self.intern(engine);
+                }
+                $r.category = self.intern(&$r.category);
+            }};
+        }
+        match result {
+            SearchResult::LegacyResult(l) => {
+                intern_fields!(l);
+                l.engine = self.intern(&l.engine);
+            }
+            SearchResult::MainResult(m) => {
+                intern_fields!(m);
+                if let Some(engine) = &mut m.engine {
+                    *engine = self.intern(engine);
+                }
+            }
+        }
+    }
+
+    /// Interns the names of every result of `response`, see [`intern_result`](Self::intern_result).
+    pub fn intern_response(&self, response: &mut SearchResponse) {
+        for result in &mut response.results {
+            self.intern_result(result);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(url: &str) -> SearchResult {
+        serde_json::from_value(serde_json::json!({
+            "url": url,
+            "template": "default.html",
+            "engine": "duckduckgo",
+            "title": url,
+            "content": "",
+            "img_src": "",
+            "thumbnail": "",
+            "priority": "",
+            "engines": ["duckduckgo"],
+            "positions": [1],
+            "score": 1.0,
+            "category": "general"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_intern_results() {
+        let interner = Interner::new();
+        let mut a = result("https://a.org");
+        let mut b = result("https://b.org");
+        interner.intern_result(&mut a);
+        interner.intern_result(&mut b);
+
+        let (SearchResult::LegacyResult(a), SearchResult::LegacyResult(b)) = (a, b) else {
+            panic!("legacy-shaped results parse as legacy results");
+        };
+        assert!(Symbol::ptr_eq(&a.category, &b.category));
+        assert!(Symbol::ptr_eq(&a.engines[0], &b.engine));
+        assert_eq!(a.category, "general");
+        // "duckduckgo" and "general".
+        assert_eq!(interner.len(), 2);
+    }
+}
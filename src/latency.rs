@@ -0,0 +1,172 @@
+//! Latency tracking and adaptive timeouts.
+//!
+//! Every client records the latency of its search requests. An [`AdaptiveTimeout`] derives
+//! request timeouts from the observed percentiles instead of a single static value, so
+//! slow instances are given the time they usually need while hung requests are still cut
+//! off early.
+//!
+//! Clients also track the time to the first page of every query separately, and can call
+//! a hook set with
+//! [`SearXNGClient::with_slow_query_hook`](crate::SearXNGClient::with_slow_query_hook)
+//! whenever it exceeds a threshold, so services can alert when their instance degrades.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// The number of samples kept by a [`LatencyTracker`].
+pub const LATENCY_WINDOW: usize = 128;
+
+/// A sliding window of recent request latencies.
+#[derive(Debug, Default)]
+pub struct LatencyTracker {
+    samples: Mutex<VecDeque<Duration>>,
+}
+
+impl LatencyTracker {
+    /// Creates an empty tracker.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a latency sample, evicting the oldest one once the window is full.
+    pub fn record(&self, latency: Duration) {
+        let mut samples = self.samples.lock().unwrap_or_else(|e| e.into_inner());
+        if samples.len() == LATENCY_WINDOW {
+            samples.pop_front();
+        }
+        samples.push_back(latency);
+    }
+
+    /// The number of samples currently in the window.
+    pub fn len(&self) -> usize {
+        self.samples.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Returns `true` if no samples have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the latency at percentile `p` (between `0.0` and `1.0`), if any sample exists.
+    pub fn percentile(&self, p: f64) -> Option<Duration> {
+        let mut samples: Vec<Duration> = self
+            .samples
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .copied()
+            .collect();
+        if samples.is_empty() {
+            return None;
+        }
+        samples.sort_unstable();
+        let rank = (p.clamp(0.0, 1.0) * (samples.len() - 1) as f64).round() as usize;
+        Some(samples[rank])
+    }
+}
+
+/// Derives request timeouts from a [`LatencyTracker`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AdaptiveTimeout {
+    /// The latency percentile the timeout is based on.
+    pub percentile: f64,
+    /// The multiplier applied to the percentile.
+    pub factor: f64,
+    /// The lower bound of the derived timeout.
+    pub min: Duration,
+    /// The upper bound of the derived timeout.
+    pub max: Duration,
+    /// The timeout used until `min_samples` samples have been recorded.
+    pub initial: Duration,
+    /// The number of samples needed before the timeout adapts.
+    pub min_samples: usize,
+}
+
+impl Default for AdaptiveTimeout {
+    fn default() -> Self {
+        AdaptiveTimeout {
+            percentile: 0.99,
+            factor: 1.5,
+            min: Duration::from_secs(2),
+            max: Duration::from_secs(30),
+            initial: Duration::from_secs(10),
+            min_samples: 10,
+        }
+    }
+}
+
+impl AdaptiveTimeout {
+    /// Computes the timeout for the next request given the observed latencies.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use searxng_client::latency::{AdaptiveTimeout, LatencyTracker};
+    /// let tracker = LatencyTracker::new();
+    /// let adaptive = AdaptiveTimeout::default();
+    /// assert_eq!(adaptive.timeout(&tracker), adaptive.initial);
+    ///
+    /// for _ in 0..20 {
+    ///     tracker.record(Duration::from_secs(4));
+    /// }
+    /// assert_eq!(adaptive.timeout(&tracker), Duration::from_secs(6));
+    /// ```
+    pub fn timeout(&self, tracker: &LatencyTracker) -> Duration {
+        if tracker.len() < self.min_samples {
+            return self.initial;
+        }
+        tracker
+            .percentile(self.percentile)
+            .map_or(self.initial, |p| p.mul_f64(self.factor))
+            .clamp(self.min, self.max)
+    }
+}
+
+/// A query whose first page took longer than the threshold of the slow-query hook.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlowQuery {
+    /// The hashed query, see [`query_hash`](crate::redact::query_hash).
+    pub query_hash: String,
+    /// The root URL of the instance.
+    pub instance: String,
+    /// The time to the first page, retries included.
+    pub latency: Duration,
+}
+
+/// The hook set with
+/// [`SearXNGClient::with_slow_query_hook`](crate::SearXNGClient::with_slow_query_hook).
+#[derive(Clone)]
+pub(crate) struct SlowQueryHook {
+    threshold: Duration,
+    hook: Arc<dyn Fn(&SlowQuery) + Send + Sync>,
+}
+
+impl fmt::Debug for SlowQueryHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SlowQueryHook")
+            .field("threshold", &self.threshold)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SlowQueryHook {
+    pub(crate) fn new(
+        threshold: Duration,
+        hook: impl Fn(&SlowQuery) + Send + Sync + 'static,
+    ) -> Self {
+        SlowQueryHook {
+            threshold,
+            hook: Arc::new(hook),
+        }
+    }
+
+    /// Calls the hook if `query` took longer than the threshold.
+    pub(crate) fn observe(&self, query: &SlowQuery) {
+        if query.latency > self.threshold {
+            (self.hook)(query);
+        }
+    }
+}
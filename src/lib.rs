@@ -6,13 +6,86 @@
 //! handling the dynamic nature of SearXNG results through robust serialization
 //! and a convenient builder pattern.
 
+pub mod aggregate;
+#[cfg(feature = "archive")]
+pub mod archive;
+pub mod audio;
+pub mod audit;
+pub mod autocomplete;
+pub mod batch;
+pub mod blend;
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+pub mod blocking;
+pub mod bloom;
+pub mod cache;
+pub mod card;
 pub mod client;
+pub mod compression;
+pub mod config;
+pub mod decode;
+pub mod dedup;
+pub mod describe;
+pub mod dns;
+pub mod embed;
+pub(crate) mod endpoint;
+pub mod error;
+pub mod event;
+pub mod explain;
+pub mod files;
+pub mod filter;
+pub mod formats;
+pub mod hedge;
+pub mod intern;
+pub mod latency;
+pub mod llm;
+pub mod map;
+#[cfg(feature = "msgpack")]
+pub mod msgpack;
+pub mod oauth;
+pub mod paper;
+pub mod parse;
+pub mod plan;
+pub mod pool;
+pub mod preferences;
+pub mod prelude;
+pub mod project;
+pub mod provenance;
+pub mod query;
+pub mod quick;
+pub mod rank;
+pub mod ratelimit;
+pub mod redact;
+pub mod redirect;
+pub mod resolve;
 pub mod response;
+pub mod retry;
+mod rt;
+pub mod sign;
+pub mod sink;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod task;
 #[cfg(test)]
 mod test;
+pub mod text;
+pub mod thumbnail;
+pub mod timeline;
+pub mod torrent;
+pub mod transport;
+pub mod watch;
+pub mod weight;
 
-pub use client::{ResponseFormat, SearXNGClient};
-pub use response::SearchResponse;
+pub use aggregate::{Aggregator, MergedResult};
+pub use card::{CardKind, ResultCard};
+pub use client::{Category, ResponseFormat, SafeSearch, SearXNGClient, TimeRange};
+pub use config::InstanceConfig;
+pub use endpoint::InstanceUrl;
+pub use error::{ConfigError, Error};
+pub use filter::{ContentFilter, SafeContentFilter};
+pub use plan::{Schedule, SearchPlan};
+pub use pool::SearXNGPool;
+pub use quick::{search, search_n};
+pub use response::{SearchOutcome, SearchResponse};
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,226 @@
+//! Search defaults for feeding results to language models.
+//!
+//! Agents mostly want a handful of distinct, readable results to paste into a prompt.
+//! [`SearchBuilder::for_llm`] sets up such a search in one call: general and news
+//! results, a blocklist of content farms, deduplication across pages, plain-text
+//! snippets of bounded length and a result budget. The results come back as an
+//! [`LlmContext`], which renders them as Markdown.
+
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+
+use crate::client::{Category, SafeSearch, SearchBuilder};
+use crate::error::Error;
+use crate::filter::SafeContentFilter;
+use crate::response::{PublishedDate, WebResult};
+use crate::text::{clean_snippet, truncate_snippet};
+
+/// The number of results returned unless set with [`LlmSearch::with_budget`].
+pub const DEFAULT_BUDGET: usize = 8;
+
+/// The maximum snippet length in graphemes, unless set with
+/// [`LlmSearch::with_snippet_len`].
+pub const DEFAULT_SNIPPET_LEN: usize = 300;
+
+/// Content farms and scraper sites blocked by [`SearchBuilder::for_llm`], subdomains
+/// included.
+pub const LOW_QUALITY_DOMAINS: &[&str] = &[
+    "pinterest.com",
+    "ehow.com",
+    "answers.com",
+    "ask.com",
+    "reference.com",
+    "stackovernet.com",
+    "stackoom.com",
+    "itecnote.com",
+    "programmerall.com",
+    "coder.social",
+    "newbedev.com",
+];
+
+fn low_quality_filter() -> SafeContentFilter {
+    LOW_QUALITY_DOMAINS
+        .iter()
+        .fold(SafeContentFilter::empty(), |filter, domain| {
+            filter.block_domain(*domain)
+        })
+}
+
+/// A search set up for language models, see the [module documentation](self).
+#[derive(Debug)]
+pub struct LlmSearch<'a> {
+    inner: SearchBuilder<'a>,
+    budget: usize,
+    snippet_len: usize,
+}
+
+impl<'a> SearchBuilder<'a> {
+    /// Sets this search up for language models: general and news results with moderate
+    /// safe search, and the [`LOW_QUALITY_DOMAINS`] filtered out.
+    ///
+    /// The filter replaces any set with [`set_filter`](Self::set_filter); the client's
+    /// filter still applies. The returned [`LlmSearch`] collects up to [`DEFAULT_BUDGET`]
+    /// unique results with cleaned snippets.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use searxng_client::{SearXNGClient, ResponseFormat};
+    /// # tokio_test::block_on(async {
+    /// # let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json);
+    /// let context = client.search("rust 2024 edition").for_llm().with_budget(5).send().await?;
+    /// let prompt = format!("Answer using these sources:\n\n{}", context.to_markdown());
+    /// # Ok::<(), searxng_client::Error>(())
+    /// # });
+    /// ```
+    pub fn for_llm(self) -> LlmSearch<'a> {
+        LlmSearch {
+            inner: self
+                .set_categories([Category::General, Category::News])
+                .set_safesearch(SafeSearch::Moderate)
+                .set_filter(low_quality_filter()),
+            budget: DEFAULT_BUDGET,
+            snippet_len: DEFAULT_SNIPPET_LEN,
+        }
+    }
+}
+
+impl<'a> LlmSearch<'a> {
+    /// Sets the number of results to return.
+    pub fn with_budget(mut self, budget: usize) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Sets the maximum snippet length in graphemes.
+    pub fn with_snippet_len(mut self, snippet_len: usize) -> Self {
+        self.snippet_len = snippet_len;
+        self
+    }
+
+    /// Configures the underlying builder, e.g. to change the categories.
+    pub fn map(mut self, f: impl FnOnce(SearchBuilder<'a>) -> SearchBuilder<'a>) -> Self {
+        self.inner = f(self.inner);
+        self
+    }
+
+    /// Fetches pages until the budget is filled with unique results, or the instance
+    /// runs out of results.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if a request fails after retries.
+    pub async fn send(self) -> Result<LlmContext, Error> {
+        let query = self.inner.params().api.q.clone();
+        let results = self.inner.send_get_num(self.budget).await?;
+        let results = results
+            .iter()
+            .map(|result| LlmResult::new(WebResult::from(result), self.snippet_len))
+            .collect();
+        Ok(LlmContext { query, results })
+    }
+}
+
+/// A result prepared for a language model.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LlmResult {
+    pub title: String,
+    pub url: Option<String>,
+    /// The content as plain text, truncated to the snippet length.
+    pub snippet: String,
+    pub published_date: Option<PublishedDate>,
+}
+
+impl LlmResult {
+    fn new(result: WebResult, snippet_len: usize) -> Self {
+        LlmResult {
+            title: clean_snippet(&result.title),
+            url: result.url,
+            snippet: truncate_snippet(&clean_snippet(&result.content), snippet_len).into_owned(),
+            published_date: result.published_date,
+        }
+    }
+}
+
+/// The results of an [`LlmSearch`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LlmContext {
+    pub query: String,
+    pub results: Vec<LlmResult>,
+}
+
+impl LlmContext {
+    /// Renders the results as a numbered Markdown list, one linked title and snippet per
+    /// result.
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = String::new();
+        for (i, result) in self.results.iter().enumerate() {
+            let title = escape_markdown(&result.title);
+            match &result.url {
+                Some(url) => {
+                    let url = url.replace(' ', "%20").replace(')', "%29");
+                    writeln!(markdown, "{}. [{title}]({url})", i + 1)
+                }
+                None => writeln!(markdown, "{}. {title}", i + 1),
+            }
+            .expect("writing to a String never fails");
+            if let Some(date) = result.published_date {
+                writeln!(markdown, "   Published {}", date.to_utc().date_naive())
+                    .expect("writing to a String never fails");
+            }
+            if !result.snippet.is_empty() {
+                writeln!(markdown, "   {}", escape_markdown(&result.snippet))
+                    .expect("writing to a String never fails");
+            }
+        }
+        markdown
+    }
+}
+
+/// Escapes the characters that would otherwise start Markdown links or emphasis.
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '[' | ']' | '*' | '_' | '`') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{ResponseFormat, SearXNGClient};
+    use crate::filter::ContentFilter;
+    use crate::test::sample_result;
+
+    #[test]
+    fn test_llm_markdown() {
+        let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json);
+        let search = client.search("rust").for_llm();
+        let params = &search.inner.params().api;
+        assert_eq!(
+            params.categories,
+            Some(vec![Category::General, Category::News])
+        );
+        let farm = sample_result("https://www.pinterest.com/pin/1", "Rust", "");
+        assert!(!low_quality_filter().allows(&farm));
+
+        let result = sample_result(
+            "https://doc.rust-lang.org/book (2024)",
+            "The [Rust] Book",
+            "<p>Learn   Rust &amp; Cargo</p>",
+        );
+        let context = LlmContext {
+            query: "rust".into(),
+            results: vec![LlmResult::new(WebResult::from(&result), 12)],
+        };
+        assert_eq!(
+            context.to_markdown(),
+            "1. [The \\[Rust\\] Book](https://doc.rust-lang.org/book%20(2024%29)\n   Learn Rust…\n"
+        );
+    }
+}
@@ -0,0 +1,328 @@
+//! Map results and client-side geographic filtering.
+//!
+//! Results of the map category (OpenStreetMap, Photon, ...) carry coordinates.
+//! [`MapResults`] collects the results that have them, for location-aware filtering and
+//! sorting that SearXNG itself does not offer.
+
+use serde::{Deserialize, Serialize};
+
+use crate::response::{
+    LegacySearchResult, MainSearchResult, MapAddress, ResultRef, SearchResponse, SearchResult,
+};
+
+/// The mean Earth radius used for distances, in kilometres.
+pub const EARTH_RADIUS_KM: f64 = 6371.0088;
+
+/// A latitude/longitude rectangle, in degrees.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BoundingBox {
+    pub min_lat: f64,
+    pub min_lon: f64,
+    pub max_lat: f64,
+    pub max_lon: f64,
+}
+
+impl BoundingBox {
+    /// Creates a bounding box. A box with `min_lon > max_lon` crosses the antimeridian.
+    pub fn new(min_lat: f64, min_lon: f64, max_lat: f64, max_lon: f64) -> Self {
+        BoundingBox {
+            min_lat,
+            min_lon,
+            max_lat,
+            max_lon,
+        }
+    }
+
+    /// Returns `true` if the point lies inside the box or on its border.
+    pub fn contains(&self, lat: f64, lon: f64) -> bool {
+        let lon_inside = if self.min_lon <= self.max_lon {
+            (self.min_lon..=self.max_lon).contains(&lon)
+        } else {
+            lon >= self.min_lon || lon <= self.max_lon
+        };
+        (self.min_lat..=self.max_lat).contains(&lat) && lon_inside
+    }
+}
+
+/// A result with geographic coordinates.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MapResult {
+    pub title: String,
+    pub url: Option<String>,
+    pub content: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    /// The extent of the place, if the engine reported one.
+    pub bounding_box: Option<BoundingBox>,
+    pub address: Option<MapAddress>,
+    /// The outline of the place as a GeoJSON geometry, if the engine reported one.
+    pub geojson: Option<serde_json::Value>,
+}
+
+impl MapResult {
+    /// The great-circle distance to the given point, in kilometres.
+    pub fn distance_km(&self, lat: f64, lon: f64) -> f64 {
+        haversine_km(self.latitude, self.longitude, lat, lon)
+    }
+}
+
+/// The great-circle distance between two points, in kilometres.
+pub fn haversine_km(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let (phi1, phi2) = (lat1.to_radians(), lat2.to_radians());
+    let d_phi = (lat2 - lat1).to_radians();
+    let d_lambda = (lon2 - lon1).to_radians();
+    let a = (d_phi / 2.0).sin().powi(2) + phi1.cos() * phi2.cos() * (d_lambda / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().atan2((1.0 - a).sqrt())
+}
+
+impl ResultRef<'_> {
+    pub(crate) fn map_result(self) -> Option<MapResult> {
+        let fields = self.template_fields();
+        let (latitude, longitude) = (fields.latitude?, fields.longitude?);
+        if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+            return None;
+        }
+        // SearXNG reports boxes in Nominatim order: [min_lat, max_lat, min_lon, max_lon].
+        let bounding_box = match fields.boundingbox.as_deref() {
+            Some(&[min_lat, max_lat, min_lon, max_lon]) => {
+                Some(BoundingBox::new(min_lat, min_lon, max_lat, max_lon))
+            }
+            _ => None,
+        };
+        Some(MapResult {
+            title: self.title().to_string(),
+            url: self.url().map(str::to_string),
+            content: self.content().to_string(),
+            latitude,
+            longitude,
+            bounding_box,
+            address: fields.address.clone(),
+            geojson: fields.geojson.clone(),
+        })
+    }
+}
+
+impl SearchResult {
+    /// Returns the geographic view of this result, if it has valid coordinates.
+    pub fn map_result(&self) -> Option<MapResult> {
+        self.by_ref().map_result()
+    }
+}
+
+impl MainSearchResult {
+    /// Same as [`SearchResult::map_result`].
+    pub fn map_result(&self) -> Option<MapResult> {
+        ResultRef::Main(self).map_result()
+    }
+}
+
+impl LegacySearchResult {
+    /// Same as [`SearchResult::map_result`].
+    pub fn map_result(&self) -> Option<MapResult> {
+        ResultRef::Legacy(self).map_result()
+    }
+}
+
+/// The results of a response that have coordinates.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use searxng_client::{SearXNGClient, ResponseFormat};
+/// # tokio_test::block_on(async {
+/// # let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json);
+/// let response = client.search("cafe").send().await?;
+/// let nearby = response
+///     .map_results()
+///     .within_bbox(48.80, 2.25, 48.92, 2.42)
+///     .sorted_by_distance(48.8566, 2.3522);
+/// for place in nearby.iter() {
+///     println!("{} ({:.1} km)", place.title, place.distance_km(48.8566, 2.3522));
+/// }
+/// # Ok::<(), searxng_client::Error>(())
+/// # });
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MapResults(pub Vec<MapResult>);
+
+impl MapResults {
+    /// Collects the results with coordinates, in their original order.
+    pub fn from_results<'a>(results: impl IntoIterator<Item = &'a SearchResult>) -> Self {
+        MapResults(
+            results
+                .into_iter()
+                .filter_map(SearchResult::map_result)
+                .collect(),
+        )
+    }
+
+    /// Keeps the results located inside the given box.
+    pub fn within_bbox(self, min_lat: f64, min_lon: f64, max_lat: f64, max_lon: f64) -> Self {
+        let bbox = BoundingBox::new(min_lat, min_lon, max_lat, max_lon);
+        MapResults(
+            self.0
+                .into_iter()
+                .filter(|r| bbox.contains(r.latitude, r.longitude))
+                .collect(),
+        )
+    }
+
+    /// Keeps the results within `radius_km` of the given point.
+    pub fn within_radius(self, lat: f64, lon: f64, radius_km: f64) -> Self {
+        MapResults(
+            self.0
+                .into_iter()
+                .filter(|r| r.distance_km(lat, lon) <= radius_km)
+                .collect(),
+        )
+    }
+
+    /// Sorts the results by distance from the given point, nearest first.
+    pub fn sorted_by_distance(mut self, lat: f64, lon: f64) -> Self {
+        self.0
+            .sort_by(|a, b| a.distance_km(lat, lon).total_cmp(&b.distance_km(lat, lon)));
+        self
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, MapResult> {
+        self.0.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn into_inner(self) -> Vec<MapResult> {
+        self.0
+    }
+}
+
+impl MapResult {
+    /// Converts this result to a GeoJSON `Feature` with a `Point` geometry.
+    ///
+    /// The title, URL, content and formatted address are included as properties, along
+    /// with the bounding box as the feature's `bbox` member.
+    pub fn to_geojson_feature(&self) -> serde_json::Value {
+        let mut properties = serde_json::Map::new();
+        properties.insert("title".into(), self.title.clone().into());
+        if let Some(url) = &self.url {
+            properties.insert("url".into(), url.clone().into());
+        }
+        if !self.content.trim().is_empty() {
+            properties.insert("content".into(), self.content.clone().into());
+        }
+        if let Some(address) = self.address.as_ref().map(MapAddress::to_line)
+            && !address.is_empty()
+        {
+            properties.insert("address".into(), address.into());
+        }
+
+        // GeoJSON positions are [longitude, latitude].
+        let mut feature = serde_json::json!({
+            "type": "Feature",
+            "geometry": {
+                "type": "Point",
+                "coordinates": [self.longitude, self.latitude],
+            },
+            "properties": properties,
+        });
+        if let Some(bbox) = &self.bounding_box {
+            feature["bbox"] =
+                serde_json::json!([bbox.min_lon, bbox.min_lat, bbox.max_lon, bbox.max_lat]);
+        }
+        feature
+    }
+}
+
+impl MapResults {
+    /// Converts the results to a GeoJSON `FeatureCollection`, ready to be added as a
+    /// Leaflet or MapLibre layer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use searxng_client::map::MapResults;
+    /// let collection = MapResults::default().to_geojson();
+    /// assert_eq!(collection["type"], "FeatureCollection");
+    /// assert!(collection["features"].as_array().unwrap().is_empty());
+    /// ```
+    pub fn to_geojson(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "FeatureCollection",
+            "features": self.iter().map(MapResult::to_geojson_feature).collect::<Vec<_>>(),
+        })
+    }
+}
+
+impl IntoIterator for MapResults {
+    type Item = MapResult;
+    type IntoIter = std::vec::IntoIter<MapResult>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl SearchResponse {
+    /// Collects the results of this response that have coordinates.
+    pub fn map_results(&self) -> MapResults {
+        MapResults::from_results(&self.results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn place(title: &str, latitude: f64, longitude: f64) -> MapResult {
+        MapResult {
+            title: title.into(),
+            url: None,
+            content: String::new(),
+            latitude,
+            longitude,
+            bounding_box: None,
+            address: None,
+            geojson: None,
+        }
+    }
+
+    #[test]
+    fn test_bbox_and_distance() {
+        let results = MapResults(vec![
+            place("Berlin", 52.52, 13.405),
+            place("Paris", 48.8566, 2.3522),
+            place("Lyon", 45.764, 4.8357),
+        ]);
+        let france = results.clone().within_bbox(41.3, -5.2, 51.1, 9.6);
+        assert_eq!(france.len(), 2);
+
+        let from_berlin = results.sorted_by_distance(52.52, 13.405);
+        let titles: Vec<_> = from_berlin.iter().map(|p| p.title.as_str()).collect();
+        assert_eq!(titles, ["Berlin", "Paris", "Lyon"]);
+        assert!((from_berlin.0[1].distance_km(52.52, 13.405) - 878.0).abs() < 5.0);
+
+        assert!(BoundingBox::new(-20.0, 170.0, -10.0, -170.0).contains(-15.0, 179.0));
+    }
+
+    #[test]
+    fn test_geojson_feature() {
+        let mut paris = place("Paris", 48.8566, 2.3522);
+        paris.address = Some(MapAddress {
+            locality: Some("Paris".into()),
+            country: Some("France".into()),
+            ..Default::default()
+        });
+        let feature = paris.to_geojson_feature();
+        assert_eq!(
+            feature["geometry"]["coordinates"],
+            serde_json::json!([2.3522, 48.8566])
+        );
+        assert_eq!(feature["properties"]["address"], "Paris, France");
+        assert!(feature["properties"].get("url").is_none());
+    }
+}
@@ -0,0 +1,93 @@
+//! Compact binary encoding of responses and results, behind the `msgpack` feature.
+//!
+//! Caching or passing large result sets between processes as JSON spends most of the
+//! time formatting and parsing text. MessagePack encodes the same data model in binary,
+//! so every type that serializes to JSON round-trips here too, including the untagged
+//! [`SearchResult`](crate::response::SearchResult) and the unknown fields kept in `extra`.
+//! Formats such as bincode cannot, since they do not describe their own structure.
+//!
+//! Fields skipped during serialization, such as
+//! [`SearchResponse::diagnostics`](crate::SearchResponse::diagnostics), are not encoded.
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+pub use rmp_serde::decode::Error as DecodeError;
+pub use rmp_serde::encode::Error as EncodeError;
+
+/// Encodes `value` as MessagePack, with field names.
+///
+/// # Examples
+///
+/// ```
+/// use searxng_client::msgpack;
+/// use searxng_client::response::WebResult;
+/// let result = WebResult {
+///     url: Some("https://www.rust-lang.org/".into()),
+///     title: "Rust".into(),
+///     content: String::new(),
+///     engines: vec!["brave".into()],
+///     score: 1.0,
+///     category: "general".into(),
+///     published_date: None,
+///     thumbnail: None,
+/// };
+/// let bytes = msgpack::to_vec(&result)?;
+/// assert_eq!(msgpack::from_slice::<WebResult>(&bytes)?, result);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn to_vec<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, EncodeError> {
+    // Field names are required by `flatten` and `skip_serializing_if`.
+    rmp_serde::to_vec_named(value)
+}
+
+/// Decodes a value encoded by [`to_vec`].
+pub fn from_slice<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, DecodeError> {
+    rmp_serde::from_slice(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SearchResponse;
+    use crate::parse::ParseMode;
+
+    #[test]
+    fn test_response_round_trip() {
+        // `new_field` is not modelled, and must survive the round trip in `extra`.
+        let result = serde_json::json!({
+            "url": "https://a.org",
+            "template": "default.html",
+            "engine": "brave",
+            "title": "a",
+            "content": "",
+            "img_src": "",
+            "thumbnail": "",
+            "priority": "",
+            "engines": ["brave"],
+            "positions": [1],
+            "score": 1.0,
+            "category": "general",
+            "publishedDate": "2024-05-01T08:30:00+02:00",
+            "new_field": true,
+        });
+        let body = serde_json::json!({
+            "query": "rust",
+            "number_of_results": 1,
+            "results": [result],
+            "answers": [],
+            "corrections": [],
+            "infoboxes": [],
+            "suggestions": ["rust book"],
+            "unresponsive_engines": [],
+        });
+        let response = SearchResponse::from_json_value(&body, ParseMode::Lenient).unwrap();
+
+        let decoded: SearchResponse = from_slice(&to_vec(&response).unwrap()).unwrap();
+        assert_eq!(
+            serde_json::to_value(&decoded).unwrap(),
+            serde_json::to_value(&response).unwrap()
+        );
+        assert_eq!(decoded.results[0].extra()["new_field"], true);
+    }
+}
@@ -0,0 +1,170 @@
+//! OAuth2 client-credentials tokens for instances behind a protected gateway.
+//!
+//! With [`ClientCredentials`] set on the client through
+//! [`SearXNGClient::with_client_credentials`](crate::SearXNGClient::with_client_credentials),
+//! every request carries an `Authorization: Bearer` header. The token is fetched from
+//! the token endpoint on first use and shared by clones of the client. It is fetched
+//! again shortly before it expires, and once more if the gateway rejects a request with
+//! `401 Unauthorized`, after which that request is sent again.
+
+use std::fmt;
+use std::time::Duration;
+
+use futures::lock::Mutex;
+use reqwest::{Client, Url};
+use serde::Deserialize;
+
+use crate::rt::Instant;
+
+/// How long before its expiry a token is fetched again.
+pub const REFRESH_MARGIN: Duration = Duration::from_secs(30);
+
+/// The timeout of a token request, unless set with [`ClientCredentials::with_timeout`].
+pub const DEFAULT_TOKEN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A token as returned by the token endpoint.
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    /// The lifetime of the token in seconds, if the endpoint tells.
+    expires_in: Option<u64>,
+}
+
+struct Token {
+    access_token: String,
+    refresh_at: Option<Instant>,
+}
+
+impl Token {
+    fn is_fresh(&self) -> bool {
+        self.refresh_at.is_none_or(|at| Instant::now() < at)
+    }
+}
+
+/// Fetches and caches OAuth2 client-credentials tokens, see the
+/// [module documentation](self).
+///
+/// The client authenticates to the token endpoint with HTTP basic authentication.
+///
+/// # Examples
+///
+/// ```no_run
+/// use searxng_client::{SearXNGClient, ResponseFormat};
+/// use searxng_client::oauth::ClientCredentials;
+/// let credentials = ClientCredentials::new(
+///     "https://auth.example.org/oauth2/token".parse()?,
+///     "searxng-client",
+///     "secret",
+/// )
+/// .with_scope("search");
+/// let client = SearXNGClient::new("https://searx.example.org", ResponseFormat::Json)
+///     .with_client_credentials(credentials);
+/// # Ok::<(), url::ParseError>(())
+/// ```
+pub struct ClientCredentials {
+    token_url: Url,
+    client_id: String,
+    client_secret: String,
+    scope: Option<String>,
+    http: Client,
+    timeout: Duration,
+    token: Mutex<Option<Token>>,
+}
+
+impl fmt::Debug for ClientCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientCredentials")
+            .field("token_url", &self.token_url.as_str())
+            .field("client_id", &self.client_id)
+            .field("scope", &self.scope)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ClientCredentials {
+    /// Creates a token provider for the client `client_id`, fetching tokens from
+    /// `token_url`.
+    pub fn new(
+        token_url: Url,
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+    ) -> Self {
+        ClientCredentials {
+            token_url,
+            client_id: client_id.into(),
+            client_secret: client_secret.into(),
+            scope: None,
+            http: Client::new(),
+            timeout: DEFAULT_TOKEN_TIMEOUT,
+            token: Mutex::new(None),
+        }
+    }
+
+    /// Sets the space-separated scopes requested with every token.
+    pub fn with_scope(mut self, scope: impl Into<String>) -> Self {
+        self.scope = Some(scope.into());
+        self
+    }
+
+    /// Sets the timeout of a token request.
+    ///
+    /// Requests needing a token wait for the fetch in progress, so this bounds how long an
+    /// unresponsive token endpoint holds up every search of the client and its clones.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Returns the current access token, fetching a new one if there is none yet or it
+    /// is about to expire.
+    ///
+    /// Concurrent callers wait for the same fetch instead of each fetching a token.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`reqwest::Error`] if the token endpoint cannot be reached, answers with
+    /// a status code that is not 2xx, or the body is not a token response.
+    pub async fn token(&self) -> Result<String, reqwest::Error> {
+        let mut token = self.token.lock().await;
+        if let Some(token) = token.as_ref().filter(|t| t.is_fresh()) {
+            return Ok(token.access_token.clone());
+        }
+        let fetched = self.fetch().await?;
+        let access_token = fetched.access_token.clone();
+        *token = Some(fetched);
+        Ok(access_token)
+    }
+
+    /// Discards `rejected` if it is still the current token, so that the next call to
+    /// [`token`](Self::token) fetches a new one.
+    pub(crate) async fn invalidate(&self, rejected: &str) {
+        let mut token = self.token.lock().await;
+        if token.as_ref().is_some_and(|t| t.access_token == rejected) {
+            *token = None;
+        }
+    }
+
+    async fn fetch(&self) -> Result<Token, reqwest::Error> {
+        let mut form = vec![("grant_type", "client_credentials")];
+        form.extend(self.scope.as_deref().map(|scope| ("scope", scope)));
+        let response: TokenResponse = self
+            .http
+            .post(self.token_url.clone())
+            .basic_auth(&self.client_id, Some(&self.client_secret))
+            .form(&form)
+            .timeout(self.timeout)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        // Lifetimes too long to represent are treated as no expiry.
+        let refresh_at = response.expires_in.and_then(|secs| {
+            Instant::now().checked_add(Duration::from_secs(secs).saturating_sub(REFRESH_MARGIN))
+        });
+        Ok(Token {
+            access_token: response.access_token,
+            refresh_at,
+        })
+    }
+}
@@ -0,0 +1,572 @@
+//! Paper results of the science category.
+//!
+//! Scholarly engines (arXiv, Crossref, PubMed, ...) report bibliographic metadata along
+//! with each result. [`PaperResult`] collects it in one place, and [`Doi`] normalizes the
+//! DOIs that engines report in different spellings, so the same paper found by several
+//! engines can be recognized.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::client::{GLOBAL_CLIENT, USER_AGENT};
+use crate::response::{
+    LegacySearchResult, MainSearchResult, PublishedDate, ResultRef, SearchResponse, SearchResult,
+};
+
+/// A normalized Digital Object Identifier, such as `10.1000/xyz123`.
+///
+/// DOIs are case-insensitive, so the normalized form is lowercase and has no resolver
+/// prefix.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Doi(String);
+
+/// The error returned when a string does not contain a DOI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidDoi(pub String);
+
+impl fmt::Display for InvalidDoi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid DOI {:?}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidDoi {}
+
+impl Doi {
+    /// The normalized DOI.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The `https://doi.org/` URL of this DOI.
+    pub fn url(&self) -> String {
+        format!("https://doi.org/{}", self.0)
+    }
+}
+
+impl FromStr for Doi {
+    type Err = InvalidDoi;
+
+    /// Parses a DOI, accepting resolver URLs and `doi:` prefixes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use searxng_client::paper::Doi;
+    /// let doi: Doi = "https://doi.org/10.1145/3368089.3409704".parse()?;
+    /// assert_eq!(doi, "DOI:10.1145/3368089.3409704".parse()?);
+    /// assert_eq!(doi.as_str(), "10.1145/3368089.3409704");
+    /// assert!("not a doi".parse::<Doi>().is_err());
+    /// # Ok::<(), searxng_client::paper::InvalidDoi>(())
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut doi = s.trim();
+        for prefix in [
+            "https://doi.org/",
+            "http://doi.org/",
+            "https://dx.doi.org/",
+            "http://dx.doi.org/",
+            "doi.org/",
+            "doi:",
+        ] {
+            if doi
+                .get(..prefix.len())
+                .is_some_and(|head| head.eq_ignore_ascii_case(prefix))
+            {
+                doi = doi[prefix.len()..].trim_start();
+                break;
+            }
+        }
+        let doi = doi.replace("%2F", "/").replace("%2f", "/");
+        let valid = doi.strip_prefix("10.").is_some_and(|rest| {
+            rest.split_once('/').is_some_and(|(registrant, suffix)| {
+                !registrant.is_empty()
+                    && registrant.bytes().all(|b| b.is_ascii_digit() || b == b'.')
+                    && !suffix.is_empty()
+            })
+        }) && !doi.contains(char::is_whitespace);
+        if valid {
+            Ok(Doi(doi.to_lowercase()))
+        } else {
+            Err(InvalidDoi(s.to_string()))
+        }
+    }
+}
+
+impl TryFrom<String> for Doi {
+    type Error = InvalidDoi;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Doi> for String {
+    fn from(doi: Doi) -> Self {
+        doi.0
+    }
+}
+
+impl fmt::Display for Doi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// The bibliographic view of a paper result.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaperResult {
+    /// The paper title.
+    pub title: String,
+    /// The link reported by the engine.
+    pub url: Option<String>,
+    /// The abstract or snippet.
+    pub content: String,
+    /// The normalized DOI, if the engine reported a valid one.
+    pub doi: Option<Doi>,
+    /// The authors, in the order reported.
+    pub authors: Vec<String>,
+    pub editor: Option<String>,
+    pub journal: Option<String>,
+    pub publisher: Option<String>,
+    pub volume: Option<String>,
+    pub number: Option<String>,
+    pub pages: Option<String>,
+    /// The publication type, such as `journal-article` or `preprint`.
+    pub paper_type: Option<String>,
+    pub issn: Vec<String>,
+    pub isbn: Vec<String>,
+    pub tags: Vec<String>,
+    /// A direct link to the full text PDF.
+    pub pdf_url: Option<String>,
+    /// A link to the full text HTML.
+    pub html_url: Option<String>,
+    /// The publication date.
+    pub published_date: Option<PublishedDate>,
+}
+
+impl ResultRef<'_> {
+    pub(crate) fn paper(self) -> Option<PaperResult> {
+        let fields = self.template_fields();
+        if self.template_name() != "paper" && fields.doi.is_none() && fields.journal.is_none() {
+            return None;
+        }
+        Some(PaperResult {
+            title: self.title().to_string(),
+            url: self.url().map(str::to_string),
+            content: self.content().to_string(),
+            doi: fields.doi.as_deref().and_then(|doi| doi.parse().ok()),
+            authors: fields.authors.clone().unwrap_or_default(),
+            editor: fields.editor.clone(),
+            journal: fields.journal.clone(),
+            publisher: fields.publisher.clone(),
+            volume: fields.volume.clone(),
+            number: fields.number.clone(),
+            pages: fields.pages.clone(),
+            paper_type: fields.paper_type.clone(),
+            issn: fields.issn.clone().unwrap_or_default(),
+            isbn: fields.isbn.clone().unwrap_or_default(),
+            tags: fields.tags.clone().unwrap_or_default(),
+            pdf_url: fields.pdf_url.clone(),
+            html_url: fields.html_url.clone(),
+            published_date: self.published_date(),
+        })
+    }
+}
+
+impl SearchResult {
+    /// Returns the bibliographic view of this result, if it is a paper result.
+    ///
+    /// Results using the `paper.html` template, or carrying a DOI or journal, are
+    /// considered papers.
+    pub fn paper(&self) -> Option<PaperResult> {
+        self.by_ref().paper()
+    }
+}
+
+impl MainSearchResult {
+    /// Same as [`SearchResult::paper`].
+    pub fn paper(&self) -> Option<PaperResult> {
+        ResultRef::Main(self).paper()
+    }
+}
+
+impl LegacySearchResult {
+    /// Same as [`SearchResult::paper`].
+    pub fn paper(&self) -> Option<PaperResult> {
+        ResultRef::Legacy(self).paper()
+    }
+}
+
+impl PaperResult {
+    /// The BibTeX entry type matching [`paper_type`](Self::paper_type), falling back to
+    /// `article` for journal papers and `misc` otherwise.
+    pub fn bibtex_entry_type(&self) -> &'static str {
+        match self
+            .paper_type
+            .as_deref()
+            .map(str::to_ascii_lowercase)
+            .as_deref()
+        {
+            Some("journal-article" | "article") => "article",
+            Some("book" | "monograph") => "book",
+            Some("book-chapter" | "chapter") => "incollection",
+            Some("proceedings-article" | "conference" | "inproceedings") => "inproceedings",
+            Some("dissertation" | "thesis" | "phdthesis") => "phdthesis",
+            Some("report" | "techreport") => "techreport",
+            _ if self.journal.is_some() => "article",
+            _ if !self.isbn.is_empty() => "book",
+            _ => "misc",
+        }
+    }
+
+    /// A citation key in the usual `lastnameYEARword` form, e.g. `lovelace1843notes`.
+    pub fn citation_key(&self) -> String {
+        let family_name = self
+            .authors
+            .first()
+            .and_then(|a| match a.split_once(',') {
+                // "Lovelace, Ada"
+                Some((family_name, _)) => Some(family_name),
+                // "Ada Lovelace"
+                None => a.split_whitespace().next_back(),
+            })
+            .map(key_part)
+            .unwrap_or_default();
+        let year = self
+            .published_date
+            .map(|d| d.format("%Y").to_string())
+            .unwrap_or_default();
+        let word = self
+            .title
+            .split_whitespace()
+            .map(key_part)
+            .find(|w| w.len() > 3)
+            .unwrap_or_default();
+        let key = format!("{family_name}{year}{word}");
+        if key.is_empty() {
+            "untitled".to_string()
+        } else {
+            key
+        }
+    }
+
+    /// Renders this paper as a BibTeX entry, using [`citation_key`](Self::citation_key).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use searxng_client::paper::PaperResult;
+    /// # let paper: PaperResult = serde_json::from_value(serde_json::json!({
+    /// #     "title": "On Computable Numbers", "url": null, "content": "",
+    /// #     "doi": "10.1112/plms/s2-42.1.230", "authors": ["Alan Turing"], "editor": null,
+    /// #     "journal": "Proceedings of the London Mathematical Society", "publisher": null,
+    /// #     "volume": "s2-42", "number": "1", "pages": "230-265", "paper_type": null,
+    /// #     "issn": [], "isbn": [], "tags": [], "pdf_url": null, "html_url": null,
+    /// #     "published_date": "1937-01-01T00:00:00"
+    /// # })).unwrap();
+    /// let bibtex = paper.to_bibtex();
+    /// assert!(bibtex.starts_with("@article{turing1937computable,"));
+    /// assert!(bibtex.contains("  doi = {10.1112/plms/s2-42.1.230},"));
+    /// ```
+    pub fn to_bibtex(&self) -> String {
+        self.to_bibtex_with_key(&self.citation_key())
+    }
+
+    fn to_bibtex_with_key(&self, key: &str) -> String {
+        let year = self.published_date.map(|d| d.format("%Y").to_string());
+        let month = self
+            .published_date
+            .map(|d| d.format("%b").to_string().to_lowercase());
+        let fields = [
+            ("title", Some(self.title.clone())),
+            (
+                "author",
+                (!self.authors.is_empty()).then(|| self.authors.join(" and ")),
+            ),
+            ("editor", self.editor.clone()),
+            ("journal", self.journal.clone()),
+            ("publisher", self.publisher.clone()),
+            ("volume", self.volume.clone()),
+            ("number", self.number.clone()),
+            ("pages", self.pages.as_ref().map(|p| p.replace('-', "--"))),
+            ("year", year),
+            ("month", month),
+            ("doi", self.doi.as_ref().map(ToString::to_string)),
+            ("issn", self.issn.first().cloned()),
+            ("isbn", self.isbn.first().cloned()),
+            ("url", self.url.clone()),
+            (
+                "keywords",
+                (!self.tags.is_empty()).then(|| self.tags.join(", ")),
+            ),
+        ];
+
+        let mut entry = format!("@{}{{{key},\n", self.bibtex_entry_type());
+        for (name, value) in fields {
+            let Some(value) = value.filter(|v| !v.trim().is_empty()) else {
+                continue;
+            };
+            // URLs and DOIs are kept verbatim apart from braces, which would end the field.
+            let value = match name {
+                "url" | "doi" => value.replace('{', "\\{").replace('}', "\\}"),
+                _ => escape_bibtex(&value),
+            };
+            entry.push_str(&format!("  {name} = {{{value}}},\n"));
+        }
+        entry.push('}');
+        entry
+    }
+}
+
+/// Keeps the ASCII alphanumeric characters of a citation key component, lowercased.
+fn key_part(s: &str) -> String {
+    s.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+fn escape_bibtex(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.split_whitespace().collect::<Vec<_>>().join(" ").chars() {
+        match c {
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Renders the paper results among `results` as a BibTeX database.
+///
+/// Results that are not papers are skipped. Duplicate citation keys are disambiguated
+/// with a letter suffix (`turing1937computable`, `turing1937computablea`, ...), and with
+/// a numeric suffix (`turing1937computable1`, ...) once the letters are used up.
+pub fn to_bibtex<'a>(results: impl IntoIterator<Item = &'a SearchResult>) -> String {
+    let mut keys: Vec<String> = Vec::new();
+    let mut entries: Vec<String> = Vec::new();
+    for paper in results.into_iter().filter_map(SearchResult::paper) {
+        let base = paper.citation_key();
+        let key = (0u32..)
+            .map(|n| match n {
+                0 => base.clone(),
+                1..=26 => format!("{base}{}", char::from(b'a' + n as u8 - 1)),
+                n => format!("{base}{}", n - 26),
+            })
+            .find(|key| !keys.contains(key))
+            .expect("a free key is found among infinitely many candidates");
+        entries.push(paper.to_bibtex_with_key(&key));
+        keys.push(key);
+    }
+    entries.join("\n\n")
+}
+
+impl SearchResponse {
+    /// Renders the paper results of this response as a BibTeX database, see
+    /// [`to_bibtex`].
+    pub fn to_bibtex(&self) -> String {
+        to_bibtex(&self.results)
+    }
+}
+
+/// The pages a DOI resolves to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedDoi {
+    /// The DOI that was resolved.
+    pub doi: Doi,
+    /// The publisher's landing page, after following all redirects.
+    pub landing_url: String,
+    /// The full text PDF, if the landing page advertises one through a
+    /// `citation_pdf_url` meta tag.
+    pub pdf_url: Option<String>,
+}
+
+/// The default timeout used by a [`DoiResolver`].
+pub const DEFAULT_RESOLVE_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Follows DOIs to their landing pages through `doi.org`.
+///
+/// Resolution contacts the DOI proxy and the publisher, so it is never done implicitly.
+///
+/// # Examples
+///
+/// ```no_run
+/// use searxng_client::paper::DoiResolver;
+/// # tokio_test::block_on(async {
+/// let resolved = DoiResolver::new().resolve(&"10.1145/3368089.3409704".parse().unwrap()).await?;
+/// println!("{}", resolved.landing_url);
+/// # Ok::<(), reqwest::Error>(())
+/// # });
+/// ```
+#[derive(Debug, Clone)]
+pub struct DoiResolver {
+    timeout: Duration,
+    http: Client,
+}
+
+impl Default for DoiResolver {
+    fn default() -> Self {
+        DoiResolver {
+            timeout: DEFAULT_RESOLVE_TIMEOUT,
+            http: GLOBAL_CLIENT.clone(),
+        }
+    }
+}
+
+impl DoiResolver {
+    /// Creates a resolver using [`DEFAULT_RESOLVE_TIMEOUT`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the HTTP client used for resolutions, see
+    /// [`SearXNGClient::with_http_client`](crate::SearXNGClient::with_http_client).
+    ///
+    /// The client must follow redirects for DOIs to resolve to their landing pages.
+    pub fn with_http_client(mut self, http: Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Sets the timeout of a resolution, including all redirects.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Resolves `doi` to its landing page.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`reqwest::Error`] if the request fails or the final page returns a
+    /// non-2xx status.
+    pub async fn resolve(&self, doi: &Doi) -> Result<ResolvedDoi, reqwest::Error> {
+        let resp = self
+            .http
+            .get(doi.url())
+            .header("User-Agent", USER_AGENT)
+            .timeout(self.timeout)
+            .send()
+            .await?
+            .error_for_status()?;
+        let landing_url = resp.url().to_string();
+        let is_pdf = resp
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("application/pdf"));
+        let pdf_url = if is_pdf {
+            Some(landing_url.clone())
+        } else {
+            citation_pdf_url(&resp.text().await?)
+        };
+        Ok(ResolvedDoi {
+            doi: doi.clone(),
+            landing_url,
+            pdf_url,
+        })
+    }
+}
+
+/// Extracts the `citation_pdf_url` meta tag used by Google Scholar compatible pages.
+fn citation_pdf_url(html: &str) -> Option<String> {
+    html.split("<meta").skip(1).find_map(|tag| {
+        let tag = &tag[..tag.find('>')?];
+        if !tag.contains("citation_pdf_url") {
+            return None;
+        }
+        let content = &tag[tag.find("content=")? + "content=".len()..];
+        let quote = content.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let value = &content[1..];
+        Some(value[..value.find(quote)?].replace("&amp;", "&"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paper_result() {
+        let result: SearchResult = serde_json::from_value(serde_json::json!({
+            "url": "https://arxiv.org/abs/2101.00001",
+            "template": "paper.html",
+            "engine": "arxiv",
+            "parsed_url": null,
+            "title": "A Paper",
+            "content": "",
+            "img_src": "",
+            "thumbnail": "",
+            "priority": "",
+            "engines": ["arxiv"],
+            "positions": [1],
+            "score": 1.0,
+            "category": "science",
+            "publishedDate": null,
+            "pubdate": null,
+            "doi": "https://doi.org/10.48550/ARXIV.2101.00001",
+            "authors": ["Ada Lovelace", "Alan Turing"],
+            "volume": 12
+        }))
+        .unwrap();
+        let paper = result.paper().unwrap();
+        assert_eq!(paper.doi.unwrap().as_str(), "10.48550/arxiv.2101.00001");
+        assert_eq!(paper.authors.len(), 2);
+        assert_eq!(paper.volume.as_deref(), Some("12"));
+
+        assert_eq!(
+            citation_pdf_url(
+                r#"<meta name="citation_pdf_url" content="https://x.org/a.pdf?a=1&amp;b=2">"#
+            ),
+            Some("https://x.org/a.pdf?a=1&b=2".into())
+        );
+    }
+
+    #[test]
+    fn test_bibtex_keys() {
+        let paper = |url: &str| -> SearchResult {
+            serde_json::from_value(serde_json::json!({
+                "url": url,
+                "template": "paper.html",
+                "engine": "crossref",
+                "title": "On Computable Numbers",
+                "content": "",
+                "img_src": "",
+                "thumbnail": "",
+                "priority": "",
+                "engines": ["crossref"],
+                "positions": [1],
+                "score": 1.0,
+                "category": "science",
+                "publishedDate": "1937-01-01T00:00:00",
+                "authors": ["Alan Turing"],
+                "journal": "Proceedings of the London Mathematical Society"
+            }))
+            .unwrap()
+        };
+        let results: Vec<_> = (0..29)
+            .map(|i| paper(&format!("https://example.org/{{{i}}}")))
+            .collect();
+        let bibtex = to_bibtex(&results);
+        let keys: Vec<_> = bibtex
+            .lines()
+            .filter_map(|line| line.strip_prefix("@article{turing1937computable"))
+            .collect();
+        assert_eq!(keys.len(), 29);
+        assert_eq!(keys[..3], [",", "a,", "b,"]);
+        assert_eq!(keys[26..], ["z,", "1,", "2,"]);
+        assert!(bibtex.contains("  url = {https://example.org/\\{0\\}},"));
+    }
+}
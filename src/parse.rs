@@ -0,0 +1,293 @@
+//! Runtime control over how strictly responses are parsed.
+//!
+//! SearXNG adds result fields from one release to the next. [`ParseMode::Strict`] rejects
+//! every field this crate does not model, which catches drift early in CI against a
+//! pinned instance. [`ParseMode::Lenient`], the default, keeps such fields in the result's
+//! `extra` map, and [`ParseMode::Salvage`] also drops results that cannot be decoded
+//! instead of failing the whole response. Whatever was tolerated is reported in
+//! [`SearchResponse::diagnostics`].
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::response::{
+    LegacySearchResult, MainSearchResult, RawSearchResponse, SearchResponse, SearchResult,
+};
+
+/// How a client treats responses that do not match the modelled schema.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ParseMode {
+    /// Fails on unknown fields and on any result that cannot be decoded.
+    Strict,
+    /// Keeps unknown fields and records their names; still fails on undecodable results.
+    #[default]
+    Lenient,
+    /// Like [`Lenient`](ParseMode::Lenient), but drops undecodable results and records
+    /// them along with their raw JSON.
+    Salvage,
+}
+
+/// The fields of one result that this crate does not model.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnknownFields {
+    /// The position of the result in the response.
+    pub index: usize,
+    /// The unknown field names, sorted.
+    pub fields: Vec<String>,
+}
+
+/// A result dropped in [`ParseMode::Salvage`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DroppedResult {
+    /// The position of the result in the response.
+    pub index: usize,
+    /// Why the result could not be decoded.
+    pub error: String,
+    /// The result as sent by the instance.
+    pub raw: serde_json::Value,
+}
+
+/// What was tolerated while parsing a response.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ParseDiagnostics {
+    /// Results with unknown fields, recorded in lenient and salvage modes.
+    pub unknown_fields: Vec<UnknownFields>,
+    /// Results dropped in salvage mode.
+    pub dropped_results: Vec<DroppedResult>,
+}
+
+impl ParseDiagnostics {
+    /// Returns `true` if the response matched the modelled schema exactly.
+    pub fn is_empty(&self) -> bool {
+        self.unknown_fields.is_empty() && self.dropped_results.is_empty()
+    }
+}
+
+/// A response body that could not be parsed under the client's [`ParseMode`].
+#[derive(Debug)]
+pub enum ParseError {
+    /// The body is not valid JSON, or the response outside of its results does not
+    /// match the schema.
+    Response(serde_json::Error),
+    /// A result could not be decoded.
+    Result {
+        /// The position of the result in the response.
+        index: usize,
+        source: serde_json::Error,
+    },
+    /// A result contains fields this crate does not model, in strict mode.
+    UnknownFields(UnknownFields),
+    /// A [CSV response](crate::formats::CsvResponse) could not be parsed.
+    Csv(csv::Error),
+    /// An [RSS response](crate::formats::RssChannel) could not be parsed.
+    Rss(quick_xml::DeError),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Response(e) => write!(f, "invalid response: {e}"),
+            ParseError::Result { index, source } => write!(f, "invalid result #{index}: {source}"),
+            ParseError::UnknownFields(unknown) => write!(
+                f,
+                "result #{} has unknown fields: {}",
+                unknown.index,
+                unknown.fields.join(", ")
+            ),
+            ParseError::Csv(e) => write!(f, "invalid CSV response: {e}"),
+            ParseError::Rss(e) => write!(f, "invalid RSS response: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParseError::Response(e) | ParseError::Result { source: e, .. } => Some(e),
+            ParseError::UnknownFields(_) => None,
+            ParseError::Csv(e) => Some(e),
+            ParseError::Rss(e) => Some(e),
+        }
+    }
+}
+
+impl ParseMode {
+    /// Parses a JSON response body.
+    ///
+    /// Results are decoded one by one, so that errors point at the offending result.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use searxng_client::parse::ParseMode;
+    /// let body = br#"{
+    ///     "query": "rust", "number_of_results": 0, "results": [{"url": 42}],
+    ///     "answers": [], "corrections": [], "infoboxes": [],
+    ///     "suggestions": [], "unresponsive_engines": []
+    /// }"#;
+    /// assert!(ParseMode::Strict.parse(body).is_err());
+    ///
+    /// let response = ParseMode::Salvage.parse(body).unwrap();
+    /// assert!(response.results.is_empty());
+    /// assert_eq!(response.diagnostics.dropped_results[0].index, 0);
+    /// assert_eq!(response.skipped()[0].raw["url"], 42);
+    /// ```
+    pub fn parse(self, body: &[u8]) -> Result<SearchResponse, ParseError> {
+        let raw: RawSearchResponse<'_> =
+            serde_json::from_slice(body).map_err(ParseError::Response)?;
+        let (mut response, raw_results) = raw.split();
+
+        for (index, raw) in raw_results.into_iter().enumerate() {
+            let result = match decode_result(raw.get()) {
+                Ok(result) => result,
+                Err(e) if self == ParseMode::Salvage => {
+                    response.diagnostics.dropped_results.push(DroppedResult {
+                        index,
+                        error: e.to_string(),
+                        raw: serde_json::from_str(raw.get()).unwrap_or_default(),
+                    });
+                    continue;
+                }
+                Err(source) => return Err(ParseError::Result { index, source }),
+            };
+
+            let mut fields: Vec<String> = result.extra().keys().cloned().collect();
+            if !fields.is_empty() {
+                fields.sort();
+                let unknown = UnknownFields { index, fields };
+                if self == ParseMode::Strict {
+                    return Err(ParseError::UnknownFields(unknown));
+                }
+                response.diagnostics.unknown_fields.push(unknown);
+            }
+            response.results.push(result);
+        }
+        Ok(response)
+    }
+}
+
+/// Decodes one result, trying the variants in the same order as the untagged
+/// [`SearchResult`] but without buffering the result first.
+///
+/// The error reported is the one of the variant the result looks like, which is more
+/// helpful than "did not match any variant".
+fn decode_result(raw: &str) -> Result<SearchResult, serde_json::Error> {
+    let main_err = match serde_json::from_str::<MainSearchResult>(raw) {
+        Ok(main) => return Ok(SearchResult::MainResult(main)),
+        Err(e) => e,
+    };
+    match serde_json::from_str::<LegacySearchResult>(raw) {
+        Ok(legacy) => Ok(SearchResult::LegacyResult(legacy)),
+        Err(legacy_err) => {
+            // `iframe_src` is required by the main structure only.
+            let looks_main = serde_json::from_str::<serde_json::Value>(raw)
+                .is_ok_and(|value| value.get("iframe_src").is_some());
+            Err(if looks_main { main_err } else { legacy_err })
+        }
+    }
+}
+
+impl ParseError {
+    /// Returns `true` if the body is not valid JSON, as with truncated or corrupted
+    /// responses, rather than valid JSON of an unexpected shape.
+    pub fn is_syntax(&self) -> bool {
+        match self {
+            ParseError::Response(e) => e.is_syntax() || e.is_eof(),
+            _ => false,
+        }
+    }
+}
+
+/// Pure parsing entry points, independent of any HTTP request.
+///
+/// Clients parse responses exactly this way, so fuzzing harnesses and property tests
+/// can target the parsing layer directly.
+impl SearchResponse {
+    /// Parses a JSON response body.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use searxng_client::SearchResponse;
+    /// use searxng_client::parse::ParseMode;
+    /// let body = r#"{
+    ///     "query": "rust", "number_of_results": 0, "results": [],
+    ///     "answers": [], "corrections": [], "infoboxes": [],
+    ///     "suggestions": [], "unresponsive_engines": []
+    /// }"#;
+    /// let response = SearchResponse::from_json_str(body, ParseMode::Strict).unwrap();
+    /// assert_eq!(response.query, "rust");
+    /// assert!(SearchResponse::from_json_str("{", ParseMode::Salvage).unwrap_err().is_syntax());
+    /// ```
+    pub fn from_json_str(body: &str, mode: ParseMode) -> Result<Self, ParseError> {
+        mode.parse(body.as_bytes())
+    }
+
+    /// Parses a JSON response body given as bytes, see [`ParseMode::parse`].
+    pub fn from_json_slice(body: &[u8], mode: ParseMode) -> Result<Self, ParseError> {
+        mode.parse(body)
+    }
+
+    /// Parses an already decoded JSON response body.
+    ///
+    /// The value is serialized again first, so prefer the other entry points when the
+    /// body is available as text.
+    pub fn from_json_value(body: &serde_json::Value, mode: ParseMode) -> Result<Self, ParseError> {
+        let body = serde_json::to_vec(body).map_err(ParseError::Response)?;
+        mode.parse(&body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::empty_response_json;
+
+    fn body(results: Vec<serde_json::Value>) -> serde_json::Value {
+        let mut body = empty_response_json("rust");
+        body["results"] = results.into();
+        body
+    }
+
+    #[test]
+    fn test_parse_modes() {
+        let known = serde_json::json!({
+            "url": "https://a.org",
+            "template": "default.html",
+            "engine": "brave",
+            "title": "a",
+            "content": "",
+            "img_src": "",
+            "thumbnail": "",
+            "priority": "",
+            "engines": ["brave"],
+            "positions": [1],
+            "score": 1.0,
+            "category": "general",
+        });
+        let mut unknown = known.clone();
+        unknown["new_field"] = true.into();
+        let decodable = body(vec![known.clone(), unknown.clone()]);
+        let response = SearchResponse::from_json_value(&decodable, ParseMode::default()).unwrap();
+        assert_eq!(response.results[1].extra()["new_field"], true);
+
+        let body = body(vec![known, unknown, serde_json::json!({"title": 1})]);
+
+        assert!(matches!(
+            SearchResponse::from_json_value(&body, ParseMode::Strict),
+            Err(ParseError::UnknownFields(UnknownFields { index: 1, .. }))
+        ));
+        assert!(matches!(
+            SearchResponse::from_json_value(&body, ParseMode::Lenient),
+            Err(ParseError::Result { index: 2, .. })
+        ));
+
+        let response = SearchResponse::from_json_value(&body, ParseMode::Salvage).unwrap();
+        assert_eq!(response.results.len(), 2);
+        assert_eq!(response.results[1].extra()["new_field"], true);
+        assert_eq!(response.diagnostics.unknown_fields[0].fields, ["new_field"]);
+        assert_eq!(response.diagnostics.dropped_results[0].index, 2);
+        assert_eq!(response.skipped()[0].raw["title"], 1);
+    }
+}
@@ -0,0 +1,134 @@
+//! Pagination planning for multi-page searches.
+//!
+//! SearXNG does not report a usable page size: `number_of_results` is an estimate that is
+//! often `0`, and the number of results per page depends on the engines that answered.
+//! A [`SearchPlan`] estimates how many pages are needed to reach a target from the page
+//! sizes it has observed, and records what was actually fetched so the pagination can be
+//! inspected afterwards.
+
+use serde::{Deserialize, Serialize};
+
+use crate::SearchResponse;
+
+/// The page size assumed before any page has been observed.
+pub const DEFAULT_PAGE_SIZE: usize = 10;
+
+/// How the pages of a [`SearchPlan`] are fetched.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Schedule {
+    /// Fetch one page at a time.
+    #[default]
+    Sequential,
+    /// Fetch up to the given number of pages at once.
+    Concurrent(usize),
+}
+
+/// An introspectable plan for collecting a target number of results.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchPlan {
+    target: usize,
+    schedule: Schedule,
+    observed_page_sizes: Vec<usize>,
+    fetched_pages: Vec<u32>,
+}
+
+impl SearchPlan {
+    /// Creates a sequential plan collecting `target` results.
+    pub fn for_target(target: usize) -> Self {
+        SearchPlan {
+            target,
+            schedule: Schedule::Sequential,
+            observed_page_sizes: Vec::new(),
+            fetched_pages: Vec::new(),
+        }
+    }
+
+    /// Sets the [`Schedule`] used to fetch pages.
+    pub fn with_schedule(mut self, schedule: Schedule) -> Self {
+        self.schedule = schedule;
+        self
+    }
+
+    /// Seeds the page size estimate from previously received responses.
+    pub fn with_prior_responses<'a>(
+        mut self,
+        responses: impl IntoIterator<Item = &'a SearchResponse>,
+    ) -> Self {
+        self.observed_page_sizes
+            .extend(responses.into_iter().map(|r| r.results.len()));
+        self
+    }
+
+    /// The number of results the plan aims to collect.
+    pub fn target(&self) -> usize {
+        self.target
+    }
+
+    /// The schedule used to fetch pages.
+    pub fn schedule(&self) -> Schedule {
+        self.schedule
+    }
+
+    /// The estimated number of results per page.
+    pub fn estimated_page_size(&self) -> usize {
+        let nonempty = self.observed_page_sizes.iter().filter(|&&n| n > 0);
+        let (sum, count) = nonempty.fold((0, 0), |(s, c), &n| (s + n, c + 1));
+        sum.checked_div(count)
+            .map_or(DEFAULT_PAGE_SIZE, |n: usize| n.max(1))
+    }
+
+    /// The estimated total number of pages needed to reach the target.
+    pub fn estimated_pages(&self) -> u32 {
+        self.target.div_ceil(self.estimated_page_size()) as u32
+    }
+
+    /// The estimated number of pages still needed once `collected` results are available.
+    pub fn remaining_pages(&self, collected: usize) -> u32 {
+        self.target
+            .saturating_sub(collected)
+            .div_ceil(self.estimated_page_size()) as u32
+    }
+
+    /// The number of pages to request in the next round.
+    pub fn next_batch_size(&self, collected: usize) -> u32 {
+        let remaining = self.remaining_pages(collected).max(1);
+        match self.schedule {
+            Schedule::Sequential => 1,
+            Schedule::Concurrent(n) => remaining.min(n.max(1) as u32),
+        }
+    }
+
+    /// Records that page `pageno` was fetched and returned `results` results.
+    pub fn observe(&mut self, pageno: u32, results: usize) {
+        self.fetched_pages.push(pageno);
+        self.observed_page_sizes.push(results);
+    }
+
+    /// The page numbers fetched so far, in the order they were processed.
+    pub fn fetched_pages(&self) -> &[u32] {
+        &self.fetched_pages
+    }
+
+    /// The page sizes observed so far, including those seeded from prior responses.
+    pub fn observed_page_sizes(&self) -> &[usize] {
+        &self.observed_page_sizes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_estimates() {
+        let mut plan = SearchPlan::for_target(50).with_schedule(Schedule::Concurrent(3));
+        assert_eq!(plan.estimated_pages(), 5);
+        assert_eq!(plan.next_batch_size(0), 3);
+
+        plan.observe(1, 25);
+        assert_eq!(plan.estimated_pages(), 2);
+        assert_eq!(plan.next_batch_size(25), 1);
+        assert_eq!(plan.fetched_pages(), &[1]);
+    }
+}
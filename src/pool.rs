@@ -0,0 +1,319 @@
+//! Failover across several instances serving the same purpose.
+//!
+//! A [`SearXNGPool`] sends each search to one instance at a time. When an instance fails,
+//! for example because it rate limits the client or times out, the next one is tried and
+//! the failing instance is set aside for a cooldown. Unlike an
+//! [`Aggregator`](crate::aggregate::Aggregator), only one response is returned, together
+//! with the instance that served it.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::client::{SearXNGClient, SearchParams};
+use crate::error::Error;
+use crate::response::SearchResponse;
+use crate::rt::Instant;
+
+/// How long a failing instance is skipped by default.
+pub const DEFAULT_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// The longest a rate limited instance is skipped for its `Retry-After`, unless the
+/// cooldown of the pool is longer.
+pub const MAX_RETRY_AFTER_COOLDOWN: Duration = Duration::from_secs(60 * 60);
+
+/// The order in which a [`SearXNGPool`] tries its instances.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub enum PoolStrategy {
+    /// Always starts with the first available instance, in the order they were configured.
+    #[default]
+    InOrder,
+    /// Starts each search with the instance after the one the previous search started with.
+    RoundRobin,
+}
+
+/// The health of one instance, as observed by a [`SearXNGPool`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstanceHealth {
+    /// The root URL of the instance.
+    pub instance: String,
+    /// The number of searches the instance served.
+    pub successes: u64,
+    /// The number of searches the instance failed.
+    pub failures: u64,
+    /// The number of failures since the last success.
+    pub consecutive_failures: u32,
+    /// Until when the instance is skipped, after a failure.
+    pub unavailable_until: Option<Instant>,
+}
+
+impl InstanceHealth {
+    fn new(instance: String) -> Self {
+        InstanceHealth {
+            instance,
+            successes: 0,
+            failures: 0,
+            consecutive_failures: 0,
+            unavailable_until: None,
+        }
+    }
+
+    /// Returns `true` if the instance is not cooling down after a failure.
+    pub fn is_available(&self) -> bool {
+        self.unavailable_until
+            .is_none_or(|until| until <= Instant::now())
+    }
+}
+
+/// A request that failed on one instance before the pool moved on to the next.
+#[derive(Debug)]
+pub struct FailedAttempt {
+    /// The root URL of the instance.
+    pub instance: String,
+    pub error: Error,
+}
+
+/// The response of a pooled search.
+#[derive(Debug)]
+pub struct PooledResponse {
+    pub response: SearchResponse,
+    /// The root URL of the instance that served the response.
+    pub instance: String,
+    /// The instances tried before, in order, with the errors they failed with.
+    pub failed: Vec<FailedAttempt>,
+}
+
+/// Tries several instances in turn until one answers, see the [module documentation](self).
+///
+/// Clones share the health state and the round-robin position. Each client retries
+/// according to its own [`RetryPolicy`](crate::retry::RetryPolicy) before the pool moves
+/// on, so pools usually configure their clients with fewer attempts.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use searxng_client::{SearXNGClient, ResponseFormat};
+/// use searxng_client::pool::{PoolStrategy, SearXNGPool};
+/// # tokio_test::block_on(async {
+/// let pool = SearXNGPool::new([
+///     SearXNGClient::new("https://searx.be", ResponseFormat::Json),
+///     SearXNGClient::new("https://search.example.org", ResponseFormat::Json),
+/// ])
+/// .with_strategy(PoolStrategy::RoundRobin);
+/// let pooled = pool.search("rust").await?;
+/// println!("{} results from {}", pooled.response.results.len(), pooled.instance);
+/// # Ok::<(), searxng_client::Error>(())
+/// # });
+/// ```
+#[derive(Debug, Clone)]
+pub struct SearXNGPool {
+    clients: Vec<SearXNGClient>,
+    strategy: PoolStrategy,
+    cooldown: Duration,
+    health: Arc<Mutex<Vec<InstanceHealth>>>,
+    next: Arc<AtomicUsize>,
+}
+
+impl SearXNGPool {
+    /// Creates a pool over the given clients, tried [`InOrder`](PoolStrategy::InOrder).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `clients` is empty.
+    pub fn new(clients: impl IntoIterator<Item = SearXNGClient>) -> Self {
+        let clients: Vec<SearXNGClient> = clients.into_iter().collect();
+        assert!(!clients.is_empty(), "a pool needs at least one instance");
+        let health = clients
+            .iter()
+            .map(|client| InstanceHealth::new(client.instance_url().to_string()))
+            .collect();
+        SearXNGPool {
+            clients,
+            strategy: PoolStrategy::default(),
+            cooldown: DEFAULT_COOLDOWN,
+            health: Arc::new(Mutex::new(health)),
+            next: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Sets the order in which instances are tried.
+    pub fn with_strategy(mut self, strategy: PoolStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Sets how long a failing instance is skipped.
+    ///
+    /// Rate-limited instances are skipped for as long as their `Retry-After` header asks,
+    /// if that is longer.
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// The clients of the pool, in the order they were configured.
+    pub fn clients(&self) -> &[SearXNGClient] {
+        &self.clients
+    }
+
+    /// A snapshot of the health of every instance, in the order they were configured.
+    pub fn health(&self) -> Vec<InstanceHealth> {
+        self.health
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Searches for `query`, failing over between instances.
+    ///
+    /// # Errors
+    ///
+    /// Returns the last [`Error`] if every instance failed.
+    pub async fn search(&self, query: impl Into<String>) -> Result<PooledResponse, Error> {
+        let params = SearchParams::new(query, self.clients[0].format());
+        self.search_with_params(params).await
+    }
+
+    /// Searches with the given parameters, failing over between instances.
+    ///
+    /// Available instances are tried first. Instances cooling down after a failure are
+    /// only tried once every available instance failed, so that a search is never refused
+    /// without asking anyone.
+    ///
+    /// # Errors
+    ///
+    /// Returns the last [`Error`] if every instance failed.
+    pub async fn search_with_params(&self, params: SearchParams) -> Result<PooledResponse, Error> {
+        let mut failed: Vec<FailedAttempt> = Vec::new();
+        for i in self.order() {
+            let client = &self.clients[i];
+            match client.search("").set_params(params.clone()).send().await {
+                Ok(response) => {
+                    self.record_success(i);
+                    return Ok(PooledResponse {
+                        response,
+                        instance: client.instance_url().to_string(),
+                        failed,
+                    });
+                }
+                Err(error) => {
+                    self.record_failure(i, &error);
+                    failed.push(FailedAttempt {
+                        instance: client.instance_url().to_string(),
+                        error,
+                    });
+                }
+            }
+        }
+        Err(failed
+            .pop()
+            .expect("a pool has at least one instance")
+            .error)
+    }
+
+    /// The indices of the instances to try, available ones first.
+    fn order(&self) -> Vec<usize> {
+        let n = self.clients.len();
+        let start = match self.strategy {
+            PoolStrategy::InOrder => 0,
+            PoolStrategy::RoundRobin => self.next.fetch_add(1, Ordering::Relaxed) % n,
+        };
+        let health = self.health.lock().unwrap_or_else(|e| e.into_inner());
+        let (mut order, cooling): (Vec<usize>, Vec<usize>) = (0..n)
+            .map(|i| (start + i) % n)
+            .partition(|&i| health[i].is_available());
+        order.extend(cooling);
+        order
+    }
+
+    fn record_success(&self, i: usize) {
+        let mut health = self.health.lock().unwrap_or_else(|e| e.into_inner());
+        let health = &mut health[i];
+        health.successes += 1;
+        health.consecutive_failures = 0;
+        health.unavailable_until = None;
+    }
+
+    fn record_failure(&self, i: usize, error: &Error) {
+        let cooldown = match error {
+            Error::RateLimited {
+                retry_after: Some(wait),
+            } => self.cooldown.max((*wait).min(MAX_RETRY_AFTER_COOLDOWN)),
+            _ => self.cooldown,
+        };
+        let now = Instant::now();
+        let mut health = self.health.lock().unwrap_or_else(|e| e.into_inner());
+        let health = &mut health[i];
+        health.failures += 1;
+        health.consecutive_failures += 1;
+        health.unavailable_until = Some(
+            now.checked_add(cooldown)
+                .unwrap_or_else(|| now + MAX_RETRY_AFTER_COOLDOWN),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::StatusCode;
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+    use crate::client::ResponseFormat;
+    use crate::retry::RetryPolicy;
+    use crate::test::empty_response_json;
+    use crate::transport::{MockTransport, RawResponse};
+
+    #[tokio::test]
+    async fn test_pool_caps_retry_after_cooldown() {
+        let mut limited = RawResponse::status(StatusCode::TOO_MANY_REQUESTS);
+        limited.headers.insert(
+            "retry-after",
+            HeaderValue::from_static("18446744073709551615"),
+        );
+        let client = |root: &str, transport: MockTransport| {
+            SearXNGClient::new(root, ResponseFormat::Json)
+                .with_retry_policy(RetryPolicy::none())
+                .with_transport(transport)
+        };
+        let pool = SearXNGPool::new([
+            client(
+                "https://a.example.org",
+                MockTransport::new().with_response("rust", 1, limited),
+            ),
+            client(
+                "https://b.example.org",
+                MockTransport::new().with_fixture("rust", empty_response_json("rust").to_string()),
+            ),
+        ]);
+
+        let pooled = pool.search("rust").await.unwrap();
+        assert_eq!(pooled.failed.len(), 1);
+        let until = pool.health()[0].unavailable_until.unwrap();
+        assert!(until <= Instant::now() + MAX_RETRY_AFTER_COOLDOWN);
+        assert!(!pool.health()[0].is_available());
+    }
+
+    #[tokio::test]
+    async fn test_pool_failover() {
+        // Nothing listens on port 9, so both requests fail to connect.
+        let pool = SearXNGPool::new([
+            SearXNGClient::new("http://127.0.0.1:9/a/", ResponseFormat::Json)
+                .with_retry_policy(RetryPolicy::none()),
+            SearXNGClient::new("http://127.0.0.1:9/b/", ResponseFormat::Json)
+                .with_retry_policy(RetryPolicy::none()),
+        ])
+        .with_strategy(PoolStrategy::RoundRobin);
+        assert_eq!(pool.order(), [0, 1]);
+        assert_eq!(pool.order(), [1, 0]);
+
+        let error = pool.search("rust").await.unwrap_err();
+        assert!(matches!(error, Error::Transport(_)));
+        let health = pool.health();
+        assert!(health.iter().all(|h| h.failures == 1 && !h.is_available()));
+
+        pool.record_success(1);
+        assert_eq!(pool.order(), [1, 0]);
+        assert_eq!(pool.order(), [1, 0]);
+    }
+}
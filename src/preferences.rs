@@ -0,0 +1,264 @@
+//! SearXNG user preferences, sent as cookies.
+//!
+//! SearXNG keeps the settings of its preferences page, such as the enabled engines, the
+//! locale or the theme, in one cookie per setting. The page can also export all of them
+//! as a single URL-safe base64 string of the zlib-compressed, URL-encoded settings,
+//! which is what the "copy preferences hash" link contains. [`Preferences`] reads and
+//! writes that string, or can be built setting by setting, and
+//! [`SearXNGClient::with_preferences`](crate::SearXNGClient::with_preferences) sends
+//! it along with every request, so that searches run with the same settings as a
+//! browser profile.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use base64::Engine;
+use base64::engine::general_purpose::{URL_SAFE, URL_SAFE_NO_PAD};
+use flate2::Compression;
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use reqwest::header::HeaderValue;
+use url::Url;
+
+use crate::client::{Category, SafeSearch};
+
+/// An error returned by [`Preferences::from_export`].
+#[derive(Debug)]
+pub enum PreferencesError {
+    /// The string is not URL-safe base64.
+    Base64(base64::DecodeError),
+    /// The decoded bytes are not zlib-compressed.
+    Decompress(io::Error),
+    /// The decompressed settings are not URL-encoded.
+    Format(serde_urlencoded::de::Error),
+}
+
+impl fmt::Display for PreferencesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PreferencesError::Base64(e) => write!(f, "invalid preferences encoding: {e}"),
+            PreferencesError::Decompress(e) => write!(f, "invalid preferences compression: {e}"),
+            PreferencesError::Format(e) => write!(f, "invalid preferences: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for PreferencesError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PreferencesError::Base64(e) => Some(e),
+            PreferencesError::Decompress(e) => Some(e),
+            PreferencesError::Format(e) => Some(e),
+        }
+    }
+}
+
+/// The settings of a SearXNG preferences page, see the [module documentation](self).
+///
+/// Settings are kept by their SearXNG names, e.g. `locale` or `disabled_engines`, so
+/// that settings this crate has no setter for survive a round trip.
+///
+/// # Examples
+///
+/// ```
+/// use searxng_client::{Category, SafeSearch, SearXNGClient, ResponseFormat};
+/// use searxng_client::preferences::Preferences;
+/// let preferences = Preferences::new()
+///     .with_locale("de")
+///     .with_categories([Category::General, Category::It])
+///     .with_safesearch(SafeSearch::Moderate)
+///     .with_disabled_engines(["google__general"]);
+/// assert_eq!(preferences.get("categories"), Some("general,it"));
+///
+/// let exported = preferences.to_export();
+/// assert_eq!(Preferences::from_export(&exported)?, preferences);
+///
+/// let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json)
+///     .with_preferences(preferences);
+/// # Ok::<(), searxng_client::preferences::PreferencesError>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Preferences {
+    settings: BTreeMap<String, String>,
+}
+
+impl Preferences {
+    /// Creates empty preferences, leaving every setting to the instance default.
+    pub fn new() -> Self {
+        Preferences::default()
+    }
+
+    /// Parses preferences exported from a preferences page.
+    ///
+    /// `exported` is either the encoded string itself or a URL carrying it in its
+    /// `preferences` query parameter, as copied from the page.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PreferencesError`] if the string is not an encoded preferences export.
+    pub fn from_export(exported: &str) -> Result<Self, PreferencesError> {
+        let exported = exported.trim();
+        let from_url = Url::parse(exported).ok().and_then(|url| {
+            url.query_pairs()
+                .find(|(name, _)| name == "preferences")
+                .map(|(_, value)| value.into_owned())
+        });
+        let encoded = from_url.as_deref().unwrap_or(exported);
+        let compressed = match URL_SAFE.decode(encoded) {
+            Ok(compressed) => compressed,
+            Err(_) => URL_SAFE_NO_PAD
+                .decode(encoded.trim_end_matches('='))
+                .map_err(PreferencesError::Base64)?,
+        };
+        let mut settings = Vec::new();
+        ZlibDecoder::new(&compressed[..])
+            .read_to_end(&mut settings)
+            .map_err(PreferencesError::Decompress)?;
+        let settings = serde_urlencoded::from_bytes(&settings).map_err(PreferencesError::Format)?;
+        Ok(Preferences { settings })
+    }
+
+    /// Encodes the preferences the way a preferences page exports them.
+    pub fn to_export(&self) -> String {
+        let settings = serde_urlencoded::to_string(&self.settings)
+            .expect("string pairs are always URL-encodable");
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(settings.as_bytes())
+            .expect("writing to a Vec never fails");
+        let compressed = encoder.finish().expect("writing to a Vec never fails");
+        URL_SAFE.encode(compressed)
+    }
+
+    /// Sets the setting `name`, replacing any value it had.
+    pub fn with(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.settings.insert(name.into(), value.into());
+        self
+    }
+
+    /// Returns the value of the setting `name`, if it is set.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.settings.get(name).map(String::as_str)
+    }
+
+    /// Iterates over the settings and their values, sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.settings.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Sets the interface locale, e.g. `de`.
+    pub fn with_locale(self, locale: impl Into<String>) -> Self {
+        self.with("locale", locale)
+    }
+
+    /// Sets the default search language, e.g. `en-US` or `all`.
+    pub fn with_language(self, language: impl Into<String>) -> Self {
+        self.with("language", language)
+    }
+
+    /// Sets the interface theme, e.g. `simple`.
+    pub fn with_theme(self, theme: impl Into<String>) -> Self {
+        self.with("theme", theme)
+    }
+
+    /// Sets the default safe search level.
+    pub fn with_safesearch(self, safesearch: SafeSearch) -> Self {
+        self.with("safesearch", safesearch.level().to_string())
+    }
+
+    /// Sets the categories searched by default.
+    pub fn with_categories(
+        self,
+        categories: impl IntoIterator<Item = impl Into<Category>>,
+    ) -> Self {
+        let categories: Vec<String> = categories
+            .into_iter()
+            .map(|c| c.into().as_str().to_string())
+            .collect();
+        self.with("categories", categories.join(","))
+    }
+
+    /// Sets the engines enabled on top of the instance defaults, each written as
+    /// `name__category`, e.g. `wikipedia__general`.
+    pub fn with_enabled_engines<S: AsRef<str>>(self, engines: impl IntoIterator<Item = S>) -> Self {
+        self.with("enabled_engines", join(engines))
+    }
+
+    /// Sets the engines disabled among the instance defaults, each written as
+    /// `name__category`, e.g. `google__general`.
+    pub fn with_disabled_engines<S: AsRef<str>>(
+        self,
+        engines: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.with("disabled_engines", join(engines))
+    }
+
+    /// Returns the settings as a `Cookie` header value, one cookie per setting.
+    ///
+    /// Semicolons and control characters are removed from the values, since they cannot
+    /// appear in a cookie. Settings whose name is not a valid cookie name, e.g. one
+    /// containing `=` or a line break, are left out.
+    pub fn cookie_header(&self) -> HeaderValue {
+        let cookies: Vec<String> = self
+            .iter()
+            .filter(|(name, _)| is_cookie_name(name))
+            .map(|(name, value)| {
+                let value: String = value
+                    .chars()
+                    .filter(|c| *c != ';' && !c.is_control())
+                    .collect();
+                format!("{name}={value}")
+            })
+            .collect();
+        HeaderValue::from_bytes(cookies.join("; ").as_bytes())
+            .expect("cookies without control characters are a valid header value")
+    }
+}
+
+/// Returns `true` if `name` is a token, as cookie names must be (RFC 6265).
+fn is_cookie_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_graphic() && !b"()<>@,;:\\\"/[]?={}".contains(&b))
+}
+
+fn join<S: AsRef<str>>(values: impl IntoIterator<Item = S>) -> String {
+    let values: Vec<String> = values.into_iter().map(|v| v.as_ref().to_string()).collect();
+    values.join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preferences_export() {
+        // Exported by SearXNG's `Preferences.get_as_url_params`.
+        let exported = "eJxVzDEKgDAMBdDTmF33TB6kxPYbA2krTe-PCC7OD16WCe3DEKxoGOLLttsk71kcXEDzQgWH1dtBIScCMvLFKxULORwloam1d-hdHSl9E6H9_QHV8ylw";
+        let url = format!("https://searx.be/preferences?preferences={exported}&save=1");
+        let preferences = Preferences::from_export(&url).unwrap();
+        assert_eq!(preferences, Preferences::from_export(exported).unwrap());
+        assert_eq!(preferences.get("categories"), Some("general,it"));
+        assert_eq!(preferences.get("enabled_engines"), Some(""));
+        assert_eq!(
+            preferences.cookie_header(),
+            "categories=general,it; disabled_engines=google__general; enabled_engines=; \
+             locale=de; safesearch=1; theme=simple"
+        );
+        assert!(Preferences::from_export("not preferences").is_err());
+    }
+
+    #[test]
+    fn test_cookie_header_skips_invalid_names() {
+        let exported = Preferences::new()
+            .with("a\nb", "1")
+            .with("c=d", "2")
+            .with("theme", "simple")
+            .to_export();
+        let preferences = Preferences::from_export(&exported).unwrap();
+        assert_eq!(preferences.get("a\nb"), Some("1"));
+        assert_eq!(preferences.cookie_header(), "theme=simple");
+    }
+}
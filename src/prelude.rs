@@ -0,0 +1,20 @@
+//! The types most searches need, for a single glob import.
+//!
+//! ```
+//! use searxng_client::prelude::*;
+//! let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json);
+//! let search = client
+//!     .search("rust")
+//!     .set_categories([Category::It])
+//!     .set_safesearch(SafeSearch::Moderate)
+//!     .set_time_range(TimeRange::Month);
+//! ```
+
+pub use crate::client::{
+    Category, ResponseFormat, SafeSearch, SearXNGClient, SearchBuilder, SearchParams, TimeRange,
+};
+pub use crate::error::Error;
+pub use crate::filter::ContentFilter;
+pub use crate::parse::ParseMode;
+pub use crate::response::{SearchOutcome, SearchResponse, SearchResult, WebResult};
+pub use crate::sink::ResultSink;
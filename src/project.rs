@@ -0,0 +1,217 @@
+//! Keeping only the result fields a caller uses.
+//!
+//! A result carries dozens of fields, most of them only set for some kinds of results,
+//! while a crawl typically keeps a URL, a title and a snippet. A [`Projection`] set with
+//! [`SearchBuilder::project`](crate::client::SearchBuilder::project) clears every other
+//! field as soon as a page is decoded and filtered, so that large crawls hold and store
+//! only what they use. Results handed to a [`ResultSink`](crate::sink::ResultSink), and
+//! thus written by every export, are projected too. Responses kept in the client's
+//! [`ResponseCache`](crate::cache::ResponseCache) are not, so that searches projecting
+//! different fields can share them.
+//!
+//! Cleared fields are reset to their empty value, e.g. `None` or `""`. The template,
+//! priority, category and engine of a result are always kept, being small and needed to
+//! tell kinds of results apart.
+
+use crate::response::SearchResult;
+
+/// A group of result fields kept by a [`Projection`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Field {
+    /// `url` and `parsed_url`.
+    Url,
+    Title,
+    /// `content`.
+    Snippet,
+    /// `engines` and `positions`.
+    Engines,
+    Score,
+    /// `published_date` and `pubdate`.
+    PublishedDate,
+    /// `img_src`, `thumbnail` and the fields of image results, e.g. `resolution`.
+    Image,
+    /// Embedded players, `audio_src`, `length` and the fields of music and video
+    /// results, e.g. `author`.
+    Media,
+    /// The fields of torrent results, e.g. `magnetlink`.
+    Torrent,
+    /// The fields of paper results, e.g. `doi`.
+    Paper,
+    /// The fields of map results, e.g. `latitude`.
+    Map,
+    /// The fields of file results, e.g. `filename`.
+    File,
+    /// The fields of code results, e.g. `codelines`.
+    Code,
+    /// The fields this crate does not model, see
+    /// [`MainSearchResult::extra`](crate::response::MainSearchResult::extra).
+    Extra,
+}
+
+impl Field {
+    fn bit(self) -> u16 {
+        1 << self as u16
+    }
+}
+
+/// The set of [`Field`]s to keep, see the [module documentation](self).
+///
+/// # Examples
+///
+/// ```
+/// use searxng_client::project::{Field, Projection};
+/// let projection = Projection::new(&[Field::Url, Field::Title]);
+/// assert!(projection.contains(Field::Title));
+/// assert!(!projection.contains(Field::Snippet));
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Projection {
+    fields: u16,
+}
+
+impl Default for Projection {
+    fn default() -> Self {
+        Projection::all()
+    }
+}
+
+impl Projection {
+    /// Creates a projection keeping `fields`.
+    pub fn new(fields: &[Field]) -> Self {
+        Projection {
+            fields: fields.iter().fold(0, |bits, field| bits | field.bit()),
+        }
+    }
+
+    /// Creates a projection keeping every field.
+    pub fn all() -> Self {
+        Projection { fields: u16::MAX }
+    }
+
+    /// Returns `true` if `field` is kept.
+    pub fn contains(&self, field: Field) -> bool {
+        self.fields & field.bit() != 0
+    }
+
+    /// Clears the fields of `result` not kept by this projection.
+    // `pubdate` is deprecated, but still cleared with the published date.
+    #[allow(deprecated)]
+    pub fn apply(&self, result: &mut SearchResult) {
+        macro_rules! clear {
+            ($r:expr, $field:ident: $($name:ident),+) => {
+                if !self.contains(Field::$field) {
+                    $($r.$name = Default::default();)+
+                }
+            };
+        }
+        macro_rules! core {
+            ($r:expr) => {
+                clear!($r, Url: url, parsed_url);
+                clear!($r, Title: title);
+                clear!($r, Snippet: content);
+                clear!($r, Engines: engines, positions);
+                clear!($r, Score: score);
+                clear!($r, PublishedDate: published_date, pubdate);
+                clear!($r, Image: img_src, thumbnail);
+                clear!($r, Media: audio_src, length, author);
+                clear!($r, Extra: extra);
+            };
+        }
+        match result {
+            SearchResult::LegacyResult(l) => {
+                core!(l);
+            }
+            SearchResult::MainResult(m) => {
+                core!(m);
+                clear!(m, Media: iframe_src, views, metadata);
+            }
+        }
+        let t = result.template_fields_mut();
+        clear!(t, Image: thumbnail_src, resolution, img_format, source);
+        clear!(t, Media: artist, album);
+        clear!(t, Torrent: magnetlink, torrentfile, seed, leech, filesize, files);
+        clear!(
+            t,
+            Paper: doi,
+            authors,
+            editor,
+            journal,
+            publisher,
+            volume,
+            number,
+            pages,
+            paper_type,
+            issn,
+            isbn,
+            tags,
+            pdf_url,
+            html_url
+        );
+        clear!(
+            t,
+            Map: latitude,
+            longitude,
+            boundingbox,
+            geojson,
+            address,
+            osm,
+            links,
+            data,
+            type_icon
+        );
+        clear!(
+            t,
+            File: filename,
+            size,
+            mimetype,
+            mtype,
+            subtype,
+            time,
+            file_abstract,
+            embedded
+        );
+        clear!(t, Code: repository, code_language, codelines, hl_lines);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_projection() {
+        let mut result: SearchResult = serde_json::from_value(serde_json::json!({
+            "url": "https://a.org",
+            "template": "paper.html",
+            "engine": "crossref",
+            "title": "Rust",
+            "content": "A language",
+            "img_src": "",
+            "thumbnail": "https://a.org/t.png",
+            "priority": "",
+            "engines": ["crossref"],
+            "positions": [1],
+            "score": 1.0,
+            "category": "science",
+            "doi": "10.1000/1"
+        }))
+        .unwrap();
+        let kept = result.clone();
+        Projection::all().apply(&mut result);
+        assert_eq!(result.content(), kept.content());
+
+        Projection::new(&[Field::Url, Field::Title]).apply(&mut result);
+        assert_eq!(result.url(), Some("https://a.org"));
+        assert_eq!(result.title(), "Rust");
+        assert_eq!(result.content(), "");
+        assert!(result.engines().is_empty());
+        assert_eq!(result.score(), 0.0);
+        let SearchResult::LegacyResult(l) = &result else {
+            unreachable!()
+        };
+        assert_eq!(
+            (l.template_fields.doi.as_deref(), l.thumbnail.as_str()),
+            (None, "")
+        );
+    }
+}
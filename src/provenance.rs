@@ -0,0 +1,37 @@
+//! Provenance of collected results.
+//!
+//! Results gathered by the multi-page and aggregation APIs may come from different pages,
+//! instances or queries. A [`Provenance`] records where each one came from.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::response::SearchResult;
+
+/// Where and when a result was fetched.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Provenance {
+    /// The root URL of the instance that served the result.
+    pub instance: String,
+    /// The page the result appeared on.
+    pub pageno: u32,
+    /// The query the result was returned for.
+    pub query: String,
+    /// When the page containing the result was received.
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// A [`SearchResult`] together with its [`Provenance`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourcedResult {
+    /// The result itself.
+    pub result: SearchResult,
+    /// Where the result came from.
+    pub provenance: Provenance,
+}
+
+impl From<SourcedResult> for SearchResult {
+    fn from(sourced: SourcedResult) -> Self {
+        sourced.result
+    }
+}
@@ -0,0 +1,96 @@
+//! Query preprocessing helpers.
+//!
+//! Queries typed with CJK input methods often contain full-width Latin letters, digits
+//! and spaces (e.g. `ｒｕｓｔ　教程`), which engines treat as different tokens from their
+//! ASCII counterparts. Mixed-language queries also tend to get poor results unless the
+//! `language` parameter matches the dominant script.
+
+use language_tags::LanguageTag;
+
+/// A CJK script family detected in a query.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum CjkScript {
+    /// Han characters without kana, assumed to be Chinese.
+    Chinese,
+    /// Text containing Hiragana or Katakana.
+    Japanese,
+    /// Text containing Hangul.
+    Korean,
+}
+
+impl CjkScript {
+    /// The language tag SearXNG should be queried with for this script.
+    pub fn language(self) -> LanguageTag {
+        let tag = match self {
+            CjkScript::Chinese => "zh",
+            CjkScript::Japanese => "ja",
+            CjkScript::Korean => "ko",
+        };
+        LanguageTag::parse(tag).expect("static language tag is valid")
+    }
+}
+
+/// Converts full-width ASCII variants and the ideographic space to their half-width forms.
+///
+/// # Examples
+///
+/// ```
+/// use searxng_client::query::normalize_width;
+/// assert_eq!(normalize_width("ｒｕｓｔ　教程１２３"), "rust 教程123");
+/// ```
+pub fn normalize_width(query: &str) -> String {
+    query
+        .chars()
+        .map(|c| match c {
+            '\u{3000}' => ' ',
+            '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+            _ => c,
+        })
+        .collect()
+}
+
+fn is_han(c: char) -> bool {
+    matches!(c, '\u{4E00}'..='\u{9FFF}' | '\u{3400}'..='\u{4DBF}' | '\u{F900}'..='\u{FAFF}')
+}
+
+fn is_kana(c: char) -> bool {
+    matches!(c, '\u{3040}'..='\u{30FF}' | '\u{31F0}'..='\u{31FF}' | '\u{FF66}'..='\u{FF9F}')
+}
+
+fn is_hangul(c: char) -> bool {
+    matches!(c, '\u{AC00}'..='\u{D7AF}' | '\u{1100}'..='\u{11FF}' | '\u{3130}'..='\u{318F}')
+}
+
+/// Returns the CJK script of `query` if more than half of its letters are CJK.
+///
+/// # Examples
+///
+/// ```
+/// use searxng_client::query::{dominant_cjk_script, CjkScript};
+/// assert_eq!(dominant_cjk_script("rust 异步编程指南"), Some(CjkScript::Chinese));
+/// assert_eq!(dominant_cjk_script("rust async 教程"), None);
+/// ```
+pub fn dominant_cjk_script(query: &str) -> Option<CjkScript> {
+    let (mut letters, mut han, mut kana, mut hangul) = (0usize, 0usize, 0usize, 0usize);
+    for c in query.chars().filter(|c| c.is_alphanumeric()) {
+        letters += 1;
+        if is_han(c) {
+            han += 1;
+        } else if is_kana(c) {
+            kana += 1;
+        } else if is_hangul(c) {
+            hangul += 1;
+        }
+    }
+
+    if (han + kana + hangul) * 2 <= letters {
+        return None;
+    }
+    Some(if kana > 0 {
+        CjkScript::Japanese
+    } else if hangul > han {
+        CjkScript::Korean
+    } else {
+        CjkScript::Chinese
+    })
+}
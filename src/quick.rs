@@ -0,0 +1,87 @@
+//! One-call searches for scripts.
+//!
+//! [`search`] and [`search_n`] save building a [`SearXNGClient`] and a search for the
+//! common case. They use a default JSON client per instance, created on first use and
+//! reused by later calls, so that its capability cache and latency statistics carry
+//! over. Anything beyond the query needs the full client API.
+
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+
+use crate::client::{ResponseFormat, SearXNGClient};
+use crate::error::Error;
+use crate::response::{SearchResponse, SearchResult};
+
+/// The default clients, by the instance URL they were requested for.
+static CLIENTS: LazyLock<Mutex<HashMap<String, SearXNGClient>>> = LazyLock::new(Default::default);
+
+/// Returns the default client of `instance`, creating it if needed.
+fn default_client(instance: impl AsRef<str>) -> SearXNGClient {
+    let instance = instance.as_ref();
+    CLIENTS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .entry(instance.to_string())
+        .or_insert_with(|| SearXNGClient::new(instance, ResponseFormat::Json))
+        .clone()
+}
+
+/// Searches `instance` for `query`, returning the first page.
+///
+/// # Errors
+///
+/// Returns an [`Error`] if the request fails after retries, see
+/// [`SearchBuilder::send`](crate::client::SearchBuilder::send).
+///
+/// # Examples
+///
+/// ```no_run
+/// # tokio_test::block_on(async {
+/// let response = searxng_client::search("https://searx.be", "rust").await?;
+/// println!("{} results", response.results.len());
+/// # Ok::<(), searxng_client::Error>(())
+/// # });
+/// ```
+pub async fn search(instance: impl AsRef<str>, query: &str) -> Result<SearchResponse, Error> {
+    default_client(instance).search(query).send().await
+}
+
+/// Searches `instance` for `query`, fetching pages until at least `n` results were
+/// collected or the instance runs out of results.
+///
+/// # Errors
+///
+/// Returns an [`Error`] if a request fails after retries, see
+/// [`SearchBuilder::send_get_num`](crate::client::SearchBuilder::send_get_num).
+///
+/// # Examples
+///
+/// ```no_run
+/// # tokio_test::block_on(async {
+/// let results = searxng_client::search_n("https://searx.be", "rust", 30).await?;
+/// println!("{} results", results.len());
+/// # Ok::<(), searxng_client::Error>(())
+/// # });
+/// ```
+pub async fn search_n(
+    instance: impl AsRef<str>,
+    query: &str,
+    n: usize,
+) -> Result<Vec<SearchResult>, Error> {
+    default_client(instance).search(query).send_get_num(n).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_client_reused() {
+        let client = default_client("https://quick.example.org");
+        assert_eq!(client.instance_url(), "https://quick.example.org/");
+        assert_eq!(client.format(), ResponseFormat::Json);
+        default_client("https://quick.example.org");
+        let clients = CLIENTS.lock().unwrap();
+        assert_eq!(clients.keys().filter(|k| k.contains("quick")).count(), 1);
+    }
+}
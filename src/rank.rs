@@ -0,0 +1,207 @@
+//! Re-ranking results by a [`Ranker`].
+//!
+//! SearXNG orders each page by its own score, and results collected over several pages
+//! stay in page order. Scores computed by different engines are not comparable either.
+//! [`SearchResponse::rerank`] and [`rerank`] reorder results by any [`Ranker`], such
+//! as the ones of this module or [`Bm25`], and a ranker set with
+//! [`SearchBuilder::set_ranker`](crate::client::SearchBuilder::set_ranker) reorders
+//! every response and collection of results of a search.
+//!
+//! Sorting is stable: results the ranker scores equally keep their order.
+
+pub use crate::blend::{Bm25, Ranker};
+use crate::response::{SearchResponse, SearchResult};
+
+/// The `k` of [`ReciprocalRankFusion`] unless set otherwise, as proposed by Cormack et
+/// al.
+pub const DEFAULT_RRF_K: f64 = 60.0;
+
+/// Ranks results by the score SearXNG computed.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct ByScore;
+
+impl Ranker for ByScore {
+    fn scores(&self, _query: &str, results: &[&SearchResult]) -> Vec<f64> {
+        results.iter().map(|result| result.score()).collect()
+    }
+}
+
+/// Ranks results by their publication date, newest first. Results without one come
+/// last.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct ByRecency;
+
+impl Ranker for ByRecency {
+    fn scores(&self, _query: &str, results: &[&SearchResult]) -> Vec<f64> {
+        results
+            .iter()
+            .map(|result| match result.published_date() {
+                Some(date) => date.to_utc().timestamp() as f64,
+                None => f64::NEG_INFINITY,
+            })
+            .collect()
+    }
+}
+
+/// Ranks results by the number of engines that returned them.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct ByEngineCount;
+
+impl Ranker for ByEngineCount {
+    fn scores(&self, _query: &str, results: &[&SearchResult]) -> Vec<f64> {
+        results
+            .iter()
+            .map(|result| result.engines().len() as f64)
+            .collect()
+    }
+}
+
+/// Ranks results by reciprocal rank fusion of the rankings of the engines that returned
+/// them.
+///
+/// A result at position `p` of an engine's ranking scores `1 / (k + p)` from that
+/// engine, and the scores of all its engines are summed. Unlike SearXNG's score, this
+/// only depends on the positions, so engines with very different scoring count alike.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ReciprocalRankFusion {
+    /// How much lower positions are discounted; larger values flatten the ranking.
+    pub k: f64,
+}
+
+impl Default for ReciprocalRankFusion {
+    fn default() -> Self {
+        ReciprocalRankFusion { k: DEFAULT_RRF_K }
+    }
+}
+
+impl Ranker for ReciprocalRankFusion {
+    fn scores(&self, _query: &str, results: &[&SearchResult]) -> Vec<f64> {
+        results
+            .iter()
+            .map(|result| {
+                (result.positions().iter())
+                    .map(|&position| 1.0 / (self.k + f64::from(position)))
+                    .sum()
+            })
+            .collect()
+    }
+}
+
+/// Sorts `results` by the scores `ranker` gives them for `query`, highest first. `NaN`
+/// and missing scores sort last.
+pub fn rerank(ranker: &(impl Ranker + ?Sized), query: &str, results: &mut Vec<SearchResult>) {
+    sort_by_ranker(ranker, query, results, |result| result);
+}
+
+/// Sorts `items` by the scores `ranker` gives to their `result`, see [`rerank`].
+pub(crate) fn sort_by_ranker<T>(
+    ranker: &(impl Ranker + ?Sized),
+    query: &str,
+    items: &mut Vec<T>,
+    result: impl Fn(&T) -> &SearchResult,
+) {
+    let scores = {
+        let results: Vec<&SearchResult> = items.iter().map(&result).collect();
+        ranker.scores(query, &results)
+    };
+    let scores = scores
+        .into_iter()
+        .map(|score| {
+            if score.is_nan() {
+                f64::NEG_INFINITY
+            } else {
+                score
+            }
+        })
+        .chain(std::iter::repeat(f64::NEG_INFINITY));
+    let mut scored: Vec<(f64, T)> = scores.zip(items.drain(..)).collect();
+    scored.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+    items.extend(scored.into_iter().map(|(_, item)| item));
+}
+
+impl SearchResponse {
+    /// Sorts the results by the scores `ranker` gives them for the query of this
+    /// response, see [`rerank`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use searxng_client::{SearXNGClient, ResponseFormat};
+    /// use searxng_client::rank::ByRecency;
+    /// # tokio_test::block_on(async {
+    /// # let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json);
+    /// let mut response = client.search("rust release").send().await?;
+    /// response.rerank(&ByRecency);
+    /// # Ok::<(), searxng_client::Error>(())
+    /// # });
+    /// ```
+    pub fn rerank(&mut self, ranker: &(impl Ranker + ?Sized)) {
+        rerank(ranker, &self.query, &mut self.results);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(url: &str, score: f64, positions: &[i32], date: Option<&str>) -> SearchResult {
+        serde_json::from_value(serde_json::json!({
+            "url": url,
+            "template": "default.html",
+            "engine": "bing",
+            "title": "Rust",
+            "content": "",
+            "img_src": "",
+            "thumbnail": "",
+            "priority": "",
+            "engines": vec!["bing"; positions.len()],
+            "positions": positions,
+            "score": score,
+            "category": "general",
+            "publishedDate": date
+        }))
+        .unwrap()
+    }
+
+    fn urls(results: &[SearchResult]) -> Vec<&str> {
+        results.iter().filter_map(|r| r.url()).collect()
+    }
+
+    #[test]
+    fn test_rankers() {
+        let mut results = vec![
+            result("https://a.org", 1.0, &[1], Some("2024-01-01T00:00:00")),
+            result("https://b.org", 3.0, &[4, 2], None),
+            result(
+                "https://c.org",
+                2.0,
+                &[1, 1, 9],
+                Some("2025-06-01T00:00:00"),
+            ),
+        ];
+        rerank(&ByScore, "rust", &mut results);
+        assert_eq!(
+            urls(&results),
+            ["https://b.org", "https://c.org", "https://a.org"]
+        );
+        rerank(&ByRecency, "rust", &mut results);
+        assert_eq!(
+            urls(&results),
+            ["https://c.org", "https://a.org", "https://b.org"]
+        );
+        rerank(&ByEngineCount, "rust", &mut results);
+        assert_eq!(
+            urls(&results),
+            ["https://c.org", "https://b.org", "https://a.org"]
+        );
+
+        let rrf = ReciprocalRankFusion { k: 0.0 };
+        let refs: Vec<&SearchResult> = results.iter().collect();
+        assert_eq!(rrf.scores("rust", &refs)[1], 0.75);
+        rerank(&rrf, "rust", &mut results);
+        assert_eq!(
+            urls(&results),
+            ["https://c.org", "https://a.org", "https://b.org"]
+        );
+    }
+}
@@ -0,0 +1,105 @@
+//! Client-side rate limiting.
+//!
+//! Public instances ban clients that send too many requests. A [`RateLimiter`] set with
+//! [`SearXNGClient::with_rate_limit`](crate::SearXNGClient::with_rate_limit) delays
+//! every request of a client, including each page and retry of a paginated search, so
+//! that no more than the allowed number of requests is sent per period.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::rt::{self, Instant};
+
+/// A token bucket allowing bursts of up to `requests` requests, refilled evenly over
+/// `period`.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use searxng_client::ratelimit::RateLimiter;
+/// # tokio_test::block_on(async {
+/// let limiter = RateLimiter::new(5, Duration::from_secs(10));
+/// for _ in 0..5 {
+///     limiter.acquire().await; // The first five requests are not delayed.
+/// }
+/// assert!(!limiter.try_acquire());
+/// # });
+/// ```
+#[derive(Debug)]
+pub struct RateLimiter {
+    capacity: f64,
+    /// The time it takes to refill one token.
+    interval: Duration,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    /// Allows `requests` requests per `period`, starting with a full bucket.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `requests` is zero.
+    pub fn new(requests: u32, period: Duration) -> Self {
+        assert!(requests > 0, "a rate limit must allow at least one request");
+        RateLimiter {
+            capacity: f64::from(requests),
+            interval: period / requests,
+            bucket: Mutex::new(Bucket {
+                tokens: f64::from(requests),
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Takes a token if one is available, returning how long to wait otherwise.
+    fn take(&self) -> Result<(), Duration> {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let refill = now.duration_since(bucket.refilled_at).as_secs_f64()
+            / self.interval.as_secs_f64().max(f64::MIN_POSITIVE);
+        bucket.tokens = (bucket.tokens + refill).min(self.capacity);
+        bucket.refilled_at = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(self.interval.mul_f64(1.0 - bucket.tokens))
+        }
+    }
+
+    /// Takes a token if one is available right now.
+    pub fn try_acquire(&self) -> bool {
+        self.take().is_ok()
+    }
+
+    /// Waits until a token is available and takes it.
+    pub async fn acquire(&self) {
+        while let Err(wait) = self.take() {
+            rt::sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rate_limiter_refills() {
+        let limiter = RateLimiter::new(2, Duration::from_millis(100));
+        assert!(limiter.try_acquire());
+        assert!(limiter.try_acquire());
+        assert!(!limiter.try_acquire());
+
+        let started = Instant::now();
+        limiter.acquire().await;
+        assert!(started.elapsed() >= Duration::from_millis(40));
+    }
+}
@@ -0,0 +1,118 @@
+//! Redaction of query strings in diagnostic output.
+//!
+//! Search terms are sensitive data. When a [`QueryRedaction`] mode is configured on the
+//! client, every piece of output produced by this crate that would contain the query
+//! (debug formatting, logs, errors) shows the redacted form instead. In errors, the
+//! query is replaced, verbatim or URL-encoded, in the response bodies of
+//! [`Error::Http`] and [`Error::Decode`], and transport errors never show the query of
+//! their URL, whatever the mode.
+//!
+//! Data returned to the caller keeps the query: the `query` of a
+//! [`SearchResponse`](crate::response::SearchResponse) and of a
+//! [`Provenance`](crate::provenance::Provenance), the URL of a
+//! [`DetailedResponse`](crate::response::DetailedResponse), and the parse error source
+//! of [`Error::Decode`], which may quote values of the body.
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::error::Error;
+
+/// How query strings are shown in diagnostic output.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryRedaction {
+    /// Queries are shown verbatim.
+    #[default]
+    None,
+    /// Queries are replaced by a short SHA-256 digest, e.g. `sha256:7f3a…`.
+    ///
+    /// The digest is stable, so identical queries can still be correlated.
+    Hash,
+    /// Queries are cut after the given number of characters.
+    Truncate(usize),
+}
+
+impl QueryRedaction {
+    /// Applies the redaction to `query`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use searxng_client::redact::QueryRedaction;
+    /// assert_eq!(QueryRedaction::Truncate(3).apply("rust programming"), "rus…");
+    /// assert!(QueryRedaction::Hash.apply("rust").starts_with("sha256:"));
+    /// ```
+    pub fn apply<'a>(&self, query: &'a str) -> Cow<'a, str> {
+        match self {
+            QueryRedaction::None => Cow::Borrowed(query),
+            QueryRedaction::Hash => Cow::Owned(format!("sha256:{}", query_hash(query))),
+            QueryRedaction::Truncate(len) => {
+                let mut graphemes = query.graphemes(true);
+                let kept: String = graphemes.by_ref().take(*len).collect();
+                if graphemes.next().is_some() {
+                    Cow::Owned(format!("{kept}…"))
+                } else {
+                    Cow::Borrowed(query)
+                }
+            }
+        }
+    }
+}
+
+impl QueryRedaction {
+    /// Redacts `query` in the response body kept by `error`.
+    pub(crate) fn redact_error(&self, error: Error, query: &str) -> Error {
+        if *self == QueryRedaction::None || query.is_empty() {
+            return error;
+        }
+        match error {
+            Error::Http { status, body } => Error::Http {
+                status,
+                body: self.redact_in(&body, query),
+            },
+            Error::Decode { source, raw_body } => Error::Decode {
+                source,
+                raw_body: self.redact_in(&raw_body, query),
+            },
+            error => error,
+        }
+    }
+
+    /// Replaces every occurrence of `query` in `text`, verbatim or URL-encoded.
+    fn redact_in(&self, text: &str, query: &str) -> String {
+        let redacted = self.apply(query);
+        let encoded: String = url::form_urlencoded::byte_serialize(query.as_bytes()).collect();
+        text.replace(query, &redacted).replace(&encoded, &redacted)
+    }
+}
+
+/// Returns a stable, hex-encoded 64-bit prefix of the SHA-256 digest of `query`.
+pub fn query_hash(query: &str) -> String {
+    Sha256::digest(query.as_bytes())[..8]
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::StatusCode;
+
+    #[test]
+    fn test_redact_error() {
+        let error = Error::Http {
+            status: StatusCode::BAD_GATEWAY,
+            body: "<input value=\"rust lang\"><a href=\"/search?q=rust+lang\">".into(),
+        };
+        let Error::Http { body, .. } = QueryRedaction::Hash.redact_error(error, "rust lang") else {
+            unreachable!()
+        };
+        assert!(!body.contains("rust"), "{body}");
+        assert_eq!(body.matches("sha256:").count(), 2);
+    }
+}
@@ -0,0 +1,115 @@
+//! Following and tracing redirects of search requests.
+//!
+//! Instances behind several proxies sometimes redirect `/search`, for example from HTTP
+//! to HTTPS or to another path. By default the HTTP client follows such redirects
+//! silently. With
+//! [`SearXNGClient::with_redirect_policy`](crate::SearXNGClient::with_redirect_policy),
+//! the client follows them itself according to a [`RedirectPolicy`], and records every
+//! hop in the [`DetailedResponse`](crate::response::DetailedResponse) returned by
+//! [`SearchBuilder::send_detailed`](crate::client::SearchBuilder::send_detailed).
+
+use reqwest::{Method, StatusCode, Url};
+use serde::{Deserialize, Serialize};
+
+/// The number of redirects followed by [`RedirectPolicy::default`], as by `reqwest`.
+pub const DEFAULT_MAX_REDIRECTS: u32 = 10;
+
+/// Which redirects of a search request are followed.
+///
+/// A redirect that is not followed is answered like any other non-2xx status, with
+/// [`Error::Http`](crate::Error::Http). Whatever the policy, redirects from HTTPS to
+/// plain HTTP, and redirects back to a URL the request already visited, are not
+/// followed. Hops to another origin carry no credentials, signature or custom headers,
+/// only the `User-Agent`, `Accept` and `Accept-Language` headers.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RedirectPolicy {
+    /// Never follows redirects.
+    None,
+    /// Follows up to the given number of redirects per request.
+    Limited(u32),
+    /// Follows every redirect.
+    ///
+    /// The search's timeout covers all hops, so a chain of redirects that never ends
+    /// fails once it elapses; without a timeout, it is followed forever.
+    Unlimited,
+}
+
+impl Default for RedirectPolicy {
+    fn default() -> Self {
+        RedirectPolicy::Limited(DEFAULT_MAX_REDIRECTS)
+    }
+}
+
+impl RedirectPolicy {
+    /// Returns `true` if a request that was already redirected `followed` times may be
+    /// redirected once more.
+    pub fn allows(&self, followed: usize) -> bool {
+        match *self {
+            RedirectPolicy::None => false,
+            RedirectPolicy::Limited(max) => followed < max as usize,
+            RedirectPolicy::Unlimited => true,
+        }
+    }
+}
+
+/// One redirect followed by a search request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Redirect {
+    /// The URL that answered with a redirect.
+    pub from: String,
+    /// The URL redirected to.
+    pub to: String,
+    /// The redirect status, e.g. `301` or `308`.
+    pub status: u16,
+}
+
+/// The method of the request following a redirect with `status`, sent as `method`.
+///
+/// Like browsers, a `POST` redirected with `301`, `302` or `303` is sent again as a
+/// `GET`, while `307` and `308` keep the method and body.
+pub(crate) fn redirected_method(method: &Method, status: StatusCode) -> Method {
+    match status {
+        StatusCode::MOVED_PERMANENTLY | StatusCode::FOUND | StatusCode::SEE_OTHER
+            if *method == Method::POST =>
+        {
+            Method::GET
+        }
+        _ => method.clone(),
+    }
+}
+
+/// The URL a redirect from `url` points to, if its `Location` is valid.
+pub(crate) fn location(url: &Url, headers: &reqwest::header::HeaderMap) -> Option<Url> {
+    let location = headers.get(reqwest::header::LOCATION)?.to_str().ok()?;
+    url.join(location).ok()
+}
+
+/// Returns `true` if following a redirect from `from` to `to` drops HTTPS.
+pub(crate) fn is_downgrade(from: &Url, to: &Url) -> bool {
+    from.scheme() == "https" && to.scheme() != "https"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redirect_policy() {
+        assert!(!RedirectPolicy::None.allows(0));
+        assert!(RedirectPolicy::Limited(2).allows(1));
+        assert!(!RedirectPolicy::Limited(2).allows(2));
+        assert!(RedirectPolicy::Unlimited.allows(usize::MAX));
+        assert_eq!(
+            redirected_method(&Method::POST, StatusCode::FOUND),
+            Method::GET
+        );
+        assert_eq!(
+            redirected_method(&Method::POST, StatusCode::PERMANENT_REDIRECT),
+            Method::POST
+        );
+
+        let url = |url: &str| Url::parse(url).unwrap();
+        assert!(is_downgrade(&url("https://a.org/"), &url("http://a.org/")));
+        assert!(!is_downgrade(&url("http://a.org/"), &url("https://b.org/")));
+    }
+}
@@ -0,0 +1,248 @@
+//! Merging of request parameters with presets and client-level defaults.
+//!
+//! Every optional parameter is resolved independently, taking the first value set in
+//! this order:
+//!
+//! 1. the request itself ([`ParamOrigin::Request`]),
+//! 2. the [preset](crate::client::SearchBuilder::set_preset) applied to the request
+//!    ([`ParamOrigin::Preset`]),
+//! 3. the [client defaults](crate::SearXNGClient::with_defaults)
+//!    ([`ParamOrigin::ClientDefault`]).
+//!
+//! If no layer sets `engines`, the [category engines](crate::SearXNGClient::with_category_engines)
+//! of the resolved categories are used, which also counts as a client default.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use language_tags::LanguageTag;
+
+use crate::client::{Category, SafeSearch, SearchParams, TimeRange};
+
+/// Where the effective value of a parameter came from.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ParamOrigin {
+    /// Set on the request itself.
+    Request,
+    /// Taken from the preset applied to the request.
+    Preset,
+    /// Filled in from a client-level default.
+    ClientDefault,
+}
+
+impl fmt::Display for ParamOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ParamOrigin::Request => "request",
+            ParamOrigin::Preset => "preset",
+            ParamOrigin::ClientDefault => "client default",
+        })
+    }
+}
+
+/// A set of optional parameters, used for presets and client defaults.
+///
+/// Unset fields leave the decision to the next layer, see the
+/// [module documentation](self).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PartialParams {
+    pub pageno: Option<u32>,
+    pub categories: Option<Vec<Category>>,
+    pub engines: Option<Vec<String>>,
+    pub language: Option<LanguageTag>,
+    pub autocomplete: Option<String>,
+    pub safesearch: Option<SafeSearch>,
+    pub time_range: Option<TimeRange>,
+    pub results_on_new_tab: Option<u32>,
+    pub image_proxy: Option<bool>,
+    pub theme: Option<String>,
+}
+
+/// The parameters of a request after resolution.
+#[derive(Debug, Clone)]
+pub struct ResolvedParams {
+    /// The effective parameters.
+    pub params: SearchParams,
+    origins: BTreeMap<&'static str, ParamOrigin>,
+}
+
+impl ResolvedParams {
+    /// Returns where the effective value of the form field `name` came from, or `None`
+    /// if the parameter is unset.
+    ///
+    /// The query and the response format always come from the request.
+    pub fn origin(&self, name: &str) -> Option<ParamOrigin> {
+        match name {
+            "q" | "format" => Some(ParamOrigin::Request),
+            _ => self.origins.get(name).copied(),
+        }
+    }
+
+    /// Returns the origins of all parameters that are set, sorted by name.
+    pub fn origins(&self) -> impl Iterator<Item = (&'static str, ParamOrigin)> + '_ {
+        self.origins.iter().map(|(name, origin)| (*name, *origin))
+    }
+}
+
+/// Resolves request parameters against client defaults, see the
+/// [module documentation](self).
+///
+/// Every client owns a resolver, available through
+/// [`SearXNGClient::params_resolver`](crate::SearXNGClient::params_resolver).
+#[derive(Debug, Clone, Default)]
+pub struct ParamsResolver {
+    defaults: PartialParams,
+    category_engines: HashMap<String, Vec<String>>,
+}
+
+macro_rules! resolve_fields {
+    ($origins:ident, $target:expr, $request:expr, $preset:expr, $defaults:expr; $($field:ident),* $(,)?) => {
+        $(
+            let layers = [
+                ($request.$field.clone(), ParamOrigin::Request),
+                ($preset.and_then(|p| p.$field.clone()), ParamOrigin::Preset),
+                ($defaults.$field.clone(), ParamOrigin::ClientDefault),
+            ];
+            if let Some((value, origin)) = layers
+                .into_iter()
+                .find_map(|(value, origin)| Some((value?, origin)))
+            {
+                $target.$field = Some(value);
+                $origins.insert(stringify!($field), origin);
+            }
+        )*
+    };
+}
+
+impl ParamsResolver {
+    /// Creates a resolver with the given client defaults.
+    pub fn new(defaults: PartialParams) -> Self {
+        ParamsResolver {
+            defaults,
+            category_engines: HashMap::new(),
+        }
+    }
+
+    /// Returns the client defaults.
+    pub fn defaults(&self) -> &PartialParams {
+        &self.defaults
+    }
+
+    pub(crate) fn set_defaults(&mut self, defaults: PartialParams) {
+        self.defaults = defaults;
+    }
+
+    pub(crate) fn set_category_engines(&mut self, category: String, engines: Vec<String>) {
+        self.category_engines.insert(category, engines);
+    }
+
+    /// Resolves `request`, with `preset` taking precedence over the client defaults.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use searxng_client::client::SearchParams;
+    /// use searxng_client::resolve::{ParamOrigin, ParamsResolver, PartialParams};
+    /// use searxng_client::{Category, ResponseFormat, SafeSearch};
+    /// let resolver = ParamsResolver::new(PartialParams {
+    ///     safesearch: Some(SafeSearch::Moderate),
+    ///     categories: Some(vec!["general".into()]),
+    ///     ..Default::default()
+    /// });
+    /// let preset = PartialParams {
+    ///     categories: Some(vec!["news".into()]),
+    ///     ..Default::default()
+    /// };
+    /// let mut request = SearchParams::new("rust", ResponseFormat::Json);
+    /// request.api.safesearch = Some(SafeSearch::Strict);
+    ///
+    /// let resolved = resolver.resolve(&request, Some(&preset));
+    /// assert_eq!(resolved.params.api.safesearch, Some(SafeSearch::Strict));
+    /// assert_eq!(resolved.origin("safesearch"), Some(ParamOrigin::Request));
+    /// assert_eq!(resolved.params.api.categories, Some(vec![Category::News]));
+    /// assert_eq!(resolved.origin("categories"), Some(ParamOrigin::Preset));
+    /// assert_eq!(resolved.origin("engines"), None);
+    /// ```
+    pub fn resolve(
+        &self,
+        request: &SearchParams,
+        preset: Option<&PartialParams>,
+    ) -> ResolvedParams {
+        let mut params = request.clone();
+        let mut origins = BTreeMap::new();
+
+        let api = &mut params.api;
+        resolve_fields!(origins, api, request.api, preset, self.defaults;
+            pageno, categories, engines, language, autocomplete, safesearch, time_range);
+        if api.engines.is_none()
+            && let Some(engines) = self.category_engines(api.categories.as_deref())
+        {
+            api.engines = Some(engines);
+            origins.insert("engines", ParamOrigin::ClientDefault);
+        }
+
+        let request_ui = request.ui.clone().unwrap_or_default();
+        let mut ui = request_ui.clone();
+        resolve_fields!(origins, ui, request_ui, preset, self.defaults;
+            results_on_new_tab, image_proxy, theme);
+        if request.ui.is_some()
+            || ui.results_on_new_tab.is_some()
+            || ui.image_proxy.is_some()
+            || ui.theme.is_some()
+        {
+            params.ui = Some(ui);
+        }
+
+        ResolvedParams { params, origins }
+    }
+
+    /// Returns the default engines of `categories`, if any are configured.
+    fn category_engines(&self, categories: Option<&[Category]>) -> Option<Vec<String>> {
+        let mut engines: Vec<String> = Vec::new();
+        for category in categories? {
+            let category_engines = self.category_engines.get(category.as_str());
+            for engine in category_engines.into_iter().flatten() {
+                if !engines.contains(engine) {
+                    engines.push(engine.clone());
+                }
+            }
+        }
+        (!engines.is_empty()).then_some(engines)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ResponseFormat;
+
+    #[test]
+    fn test_category_engines_rank_below_explicit_engines() {
+        let mut resolver = ParamsResolver::new(PartialParams {
+            categories: Some(vec!["images".into()]),
+            ..Default::default()
+        });
+        resolver.set_category_engines("images".into(), vec!["bing images".into()]);
+        let request = SearchParams::new("cats", ResponseFormat::Json);
+
+        let resolved = resolver.resolve(&request, None);
+        assert_eq!(
+            resolved.params.api.engines,
+            Some(vec!["bing images".into()])
+        );
+        assert_eq!(resolved.origin("engines"), Some(ParamOrigin::ClientDefault));
+        assert_eq!(
+            resolved.origin("categories"),
+            Some(ParamOrigin::ClientDefault)
+        );
+
+        let preset = PartialParams {
+            engines: Some(vec!["flickr".into()]),
+            ..Default::default()
+        };
+        let resolved = resolver.resolve(&request, Some(&preset));
+        assert_eq!(resolved.params.api.engines, Some(vec!["flickr".into()]));
+        assert_eq!(resolved.origin("engines"), Some(ParamOrigin::Preset));
+        assert!(resolved.params.ui.is_none());
+    }
+}
@@ -1,10 +1,21 @@
 use std::collections::HashMap;
+use std::ops::Deref;
 
-use chrono::NaiveDateTime;
+use chrono::{DateTime, FixedOffset, NaiveDateTime, TimeZone, Utc};
 use iso8601::Duration;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::value::RawValue;
 use smallvec::SmallVec;
 
+use crate::audio::parse_duration;
+use crate::files::FileResult;
+use crate::intern::Symbol;
+use crate::map::MapResult;
+use crate::paper::PaperResult;
+use crate::parse::{DroppedResult, ParseDiagnostics};
+use crate::redirect::Redirect;
+use crate::torrent::Magnet;
+
 /// The top-level response returned by the SearXNG API.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchResponse {
@@ -15,7 +26,131 @@ pub struct SearchResponse {
     /// A list of search results.
     pub results: Vec<SearchResult>,
     /// Instant answers provided by specialized engines.
-    pub answers: Vec<AnswerSet>,
+    pub answers: Vec<Answer>,
+    /// Possible query corrections.
+    pub corrections: Vec<Correction>,
+    /// Structured information boxes (Infoboxes).
+    pub infoboxes: Vec<Infobox>,
+    /// Search suggestions for related queries.
+    pub suggestions: Vec<Suggestion>,
+    /// A list of engines that failed to respond or returned errors.
+    pub unresponsive_engines: Vec<EngineError>,
+    /// What was tolerated while parsing this response, see [`ParseMode`](crate::parse::ParseMode).
+    #[serde(skip)]
+    pub diagnostics: ParseDiagnostics,
+}
+
+/// A [`SearchResponse`] whose results are left undecoded, so that they can be decoded
+/// one by one without building a `serde_json::Value` of the whole body.
+///
+/// Keep the fields in sync with [`SearchResponse`].
+#[derive(Deserialize)]
+pub(crate) struct RawSearchResponse<'a> {
+    query: String,
+    number_of_results: i64,
+    #[serde(borrow)]
+    results: Vec<&'a RawValue>,
+    answers: Vec<Answer>,
+    corrections: Vec<Correction>,
+    infoboxes: Vec<Infobox>,
+    suggestions: Vec<Suggestion>,
+    unresponsive_engines: Vec<EngineError>,
+}
+
+impl<'a> RawSearchResponse<'a> {
+    /// Splits off the undecoded results, returning a response without results.
+    pub(crate) fn split(self) -> (SearchResponse, Vec<&'a RawValue>) {
+        let response = SearchResponse {
+            query: self.query,
+            number_of_results: self.number_of_results,
+            results: Vec::with_capacity(self.results.len()),
+            answers: self.answers,
+            corrections: self.corrections,
+            infoboxes: self.infoboxes,
+            suggestions: self.suggestions,
+            unresponsive_engines: self.unresponsive_engines,
+            diagnostics: ParseDiagnostics::default(),
+        };
+        (response, self.results)
+    }
+}
+
+impl SearchResponse {
+    /// Returns `true` if the request succeeded but every engine that was queried failed.
+    ///
+    /// A response is considered degraded when it contains no results, answers or
+    /// infoboxes while at least one engine is reported as unresponsive.
+    pub fn is_degraded(&self) -> bool {
+        self.results.is_empty()
+            && self.answers.is_empty()
+            && self.infoboxes.is_empty()
+            && !self.unresponsive_engines.is_empty()
+    }
+
+    /// The results that could not be decoded and were skipped, with their raw JSON.
+    ///
+    /// Always empty unless the client parses in [`ParseMode::Salvage`](crate::parse::ParseMode::Salvage),
+    /// which decodes results one by one and keeps the good ones.
+    pub fn skipped(&self) -> &[DroppedResult] {
+        &self.diagnostics.dropped_results
+    }
+
+    /// Splits the response into its results and everything else.
+    pub fn into_parts(self) -> (Vec<SearchResult>, ResponseMeta) {
+        let meta = ResponseMeta {
+            query: self.query,
+            number_of_results: self.number_of_results,
+            answers: self.answers,
+            corrections: self.corrections,
+            infoboxes: self.infoboxes,
+            suggestions: self.suggestions,
+            unresponsive_engines: self.unresponsive_engines,
+            diagnostics: self.diagnostics,
+        };
+        (self.results, meta)
+    }
+
+    /// Classifies the response, distinguishing "no results" from "no working engines".
+    pub fn into_outcome(self) -> SearchOutcome {
+        if self.is_degraded() {
+            SearchOutcome::Degraded(DegradedResponse {
+                query: self.query,
+                unresponsive_engines: self.unresponsive_engines,
+            })
+        } else {
+            SearchOutcome::Complete(self)
+        }
+    }
+}
+
+/// The classified result of a successful HTTP search, see [`SearchResponse::into_outcome`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SearchOutcome {
+    /// The engines answered; the response may still legitimately contain no results.
+    Complete(SearchResponse),
+    /// Every engine that was queried failed, so the absence of results says nothing about
+    /// the query. Frontends should report the search backends as unavailable.
+    Degraded(DegradedResponse),
+}
+
+/// A response in which every queried engine failed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DegradedResponse {
+    /// The original query string.
+    pub query: String,
+    /// The engines that failed and why.
+    pub unresponsive_engines: Vec<EngineError>,
+}
+
+/// Everything a [`SearchResponse`] reports besides its results.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResponseMeta {
+    /// The original query string.
+    pub query: String,
+    /// Estimated total number of results across all engines.
+    pub number_of_results: i64,
+    /// Instant answers provided by specialized engines.
+    pub answers: Vec<Answer>,
     /// Possible query corrections.
     pub corrections: Vec<Correction>,
     /// Structured information boxes (Infoboxes).
@@ -24,6 +159,38 @@ pub struct SearchResponse {
     pub suggestions: Vec<Suggestion>,
     /// A list of engines that failed to respond or returned errors.
     pub unresponsive_engines: Vec<EngineError>,
+    /// What was tolerated while parsing the response.
+    #[serde(skip)]
+    pub diagnostics: ParseDiagnostics,
+}
+
+/// One page of a paginated search, see
+/// [`SearchBuilder::pages`](crate::client::SearchBuilder::pages).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Page {
+    /// The page number, starting at 1.
+    pub pageno: u32,
+    /// The results of the page, after content filtering.
+    pub results: Vec<SearchResult>,
+    /// Everything else the instance reported for this page.
+    pub response_meta: ResponseMeta,
+}
+
+/// A response together with how it was received, see
+/// [`SearchBuilder::send_detailed`](crate::client::SearchBuilder::send_detailed).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetailedResponse {
+    /// The response, after content filtering.
+    pub response: SearchResponse,
+    /// The URL that served the response, after redirects.
+    pub url: String,
+    /// The status of the response.
+    pub status: u16,
+    /// The redirects followed, in order. Always empty unless the client has a
+    /// [`RedirectPolicy`](crate::redirect::RedirectPolicy).
+    pub redirects: Vec<Redirect>,
+    /// How long sending the request and receiving the response took, redirects included.
+    pub elapsed: std::time::Duration,
 }
 
 /// A search result entry.
@@ -33,19 +200,21 @@ pub struct SearchResponse {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum SearchResult {
-    /// A result with legacy structure.
-    LegacyResult(LegacySearchResult),
     /// A result with the modern main structure.
+    ///
+    /// Tried first: unknown fields are captured rather than rejected, so a modern result
+    /// would otherwise also decode as a legacy one.
     MainResult(MainSearchResult),
+    /// A result with legacy structure.
+    LegacyResult(LegacySearchResult),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(deny_unknown_fields)]
 pub struct MainSearchResult {
     // The Result Class
     // from https://github.com/searxng/searxng/blob/master/searx/result_types/_base.py#L228
     pub url: Option<String>,
-    pub engine: Option<String>,
+    pub engine: Option<Symbol>,
     pub parsed_url: Option<Vec<String>>,
 
     // The MainResult Class
@@ -58,7 +227,7 @@ pub struct MainSearchResult {
     pub audio_src: String,
     pub thumbnail: String,
     #[serde(rename = "publishedDate")]
-    pub published_date: Option<NaiveDateTime>,
+    pub published_date: Option<PublishedDate>,
     #[deprecated(
         since = "0.1.0",
         note = r#"According to the SearXNG codebase: "it is still partially used in the templates, but will one day be completely eliminated."(from https://github.com/searxng/searxng/blob/master/searx/result_types/_base.py#L372). So please use "published_date" instead"#
@@ -70,12 +239,20 @@ pub struct MainSearchResult {
     pub author: String,
     pub metadata: String,
     pub priority: PriorityType,
-    pub engines: SmallVec<[String; 4]>,
+    pub engines: SmallVec<[Symbol; 4]>,
     pub open_group: bool,
     pub close_group: bool,
     pub positions: SmallVec<[i32; 4]>,
     pub score: f64,
-    pub category: String,
+    pub category: Symbol,
+
+    /// The fields of specific result templates, e.g. of torrent or paper results.
+    #[serde(flatten)]
+    pub template_fields: TemplateFields,
+    /// Fields this crate does not model, kept unless the client parses in
+    /// [`ParseMode::Strict`](crate::parse::ParseMode::Strict).
+    #[serde(flatten, default, skip_serializing_if = "HashMap::is_empty")]
+    pub extra: HashMap<String, serde_json::Value>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -89,12 +266,11 @@ pub enum PriorityType {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(deny_unknown_fields)]
 pub struct LegacySearchResult {
     // from https://github.com/searxng/searxng/blob/master/searx/result_types/_base.py#L427
     pub url: Option<String>,
     pub template: String,
-    pub engine: String,
+    pub engine: Symbol,
     pub parsed_url: Option<Vec<String>>,
 
     pub title: String,
@@ -102,13 +278,295 @@ pub struct LegacySearchResult {
     pub img_src: String,
     pub thumbnail: String,
     pub priority: PriorityType,
-    pub engines: SmallVec<[String; 4]>,
+    pub engines: SmallVec<[Symbol; 4]>,
     pub positions: SmallVec<[i32; 4]>,
     pub score: f64,
-    pub category: String,
+    pub category: Symbol,
     #[serde(rename = "publishedDate")]
-    pub published_date: Option<NaiveDateTime>,
+    pub published_date: Option<PublishedDate>,
     pub pubdate: Option<String>,
+
+    // Fields of music results
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio_src: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::string"
+    )]
+    pub author: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::string"
+    )]
+    pub length: Option<String>,
+    /// The fields of specific result templates, e.g. of torrent or paper results.
+    #[serde(flatten)]
+    pub template_fields: TemplateFields,
+    /// Fields this crate does not model, kept unless the client parses in
+    /// [`ParseMode::Strict`](crate::parse::ParseMode::Strict).
+    #[serde(flatten, default, skip_serializing_if = "HashMap::is_empty")]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// The fields of specific result templates, shared by [`MainSearchResult`] and
+/// [`LegacySearchResult`].
+///
+/// Each is only set by the engines of the templates it belongs to; the typed views of
+/// [`SearchResult`], such as [`SearchResult::typed`], gather them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TemplateFields {
+    // Fields of torrent results
+    // from https://github.com/searxng/searxng/blob/master/searx/templates/simple/result_templates/torrent.html
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub magnetlink: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub torrentfile: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::count"
+    )]
+    pub seed: Option<u64>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::count"
+    )]
+    pub leech: Option<u64>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::string"
+    )]
+    pub filesize: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::count"
+    )]
+    pub files: Option<u64>,
+
+    // Fields of paper results
+    // from https://github.com/searxng/searxng/blob/master/searx/result_types/paper.py
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doi: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::string_list"
+    )]
+    pub authors: Option<Vec<String>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::string"
+    )]
+    pub editor: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::string"
+    )]
+    pub journal: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::string"
+    )]
+    pub publisher: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::string"
+    )]
+    pub volume: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::string"
+    )]
+    pub number: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::string"
+    )]
+    pub pages: Option<String>,
+    #[serde(
+        rename = "type",
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::string"
+    )]
+    pub paper_type: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::string_list"
+    )]
+    pub issn: Option<Vec<String>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::string_list"
+    )]
+    pub isbn: Option<Vec<String>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::string_list"
+    )]
+    pub tags: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pdf_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub html_url: Option<String>,
+
+    // Fields of map results
+    // from https://github.com/searxng/searxng/blob/master/searx/engines/openstreetmap.py
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::float"
+    )]
+    pub latitude: Option<f64>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::float"
+    )]
+    pub longitude: Option<f64>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::float_list"
+    )]
+    pub boundingbox: Option<Vec<f64>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub geojson: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<MapAddress>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub osm: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub links: Option<Vec<serde_json::Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Vec<serde_json::Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub type_icon: Option<String>,
+
+    // Fields of file results
+    // from https://github.com/searxng/searxng/blob/master/searx/result_types/file.py
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::string"
+    )]
+    pub size: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mimetype: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mtype: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subtype: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::string"
+    )]
+    pub time: Option<String>,
+    #[serde(rename = "abstract", default, skip_serializing_if = "Option::is_none")]
+    pub file_abstract: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embedded: Option<String>,
+
+    // Fields of music results
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::string"
+    )]
+    pub artist: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::string"
+    )]
+    pub album: Option<String>,
+    // Image results.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thumbnail_src: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::string"
+    )]
+    pub resolution: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub img_format: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::string"
+    )]
+    pub source: Option<String>,
+
+    // Code results.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code_language: Option<String>,
+    /// Line numbers and their source, as `[[12, "fn main() {"], ...]`.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::shaped"
+    )]
+    pub codelines: Option<Vec<(u32, String)>>,
+    /// The numbers of the lines matching the query.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::shaped"
+    )]
+    pub hl_lines: Option<Vec<u32>>,
+}
+
+/// The postal address of a map result.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MapAddress {
+    pub name: Option<String>,
+    pub road: Option<String>,
+    pub house_number: Option<String>,
+    pub locality: Option<String>,
+    pub postcode: Option<String>,
+    pub country: Option<String>,
+    pub country_code: Option<String>,
+}
+
+impl MapAddress {
+    /// Formats the address on one line, e.g. `Eiffel Tower, 5 Avenue Anatole France, 75007 Paris, France`.
+    pub fn to_line(&self) -> String {
+        let street = match (&self.road, &self.house_number) {
+            (Some(road), Some(number)) => Some(format!("{number} {road}")),
+            (road, _) => road.clone(),
+        };
+        let city = match (&self.postcode, &self.locality) {
+            (Some(postcode), Some(locality)) => Some(format!("{postcode} {locality}")),
+            (postcode, locality) => locality.clone().or_else(|| postcode.clone()),
+        };
+        [self.name.clone(), street, city, self.country.clone()]
+            .into_iter()
+            .flatten()
+            .filter(|part| !part.trim().is_empty())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
 }
 
 /// A structured information box typically displayed on the side of search results.
@@ -118,10 +576,13 @@ pub struct Infobox {
     pub id: String,
     /// The text content of the infobox.
     pub content: String,
-    /// Associated URLs (e.g., Wikipedia links). Uses `serde_json::Value` for schema flexibility.
-    pub urls: Option<Vec<HashMap<String, serde_json::Value>>>,
-    /// Structured attributes. Uses `serde_json::Value` to handle dynamic key-value pairs.
-    pub attributes: Option<Vec<HashMap<String, serde_json::Value>>>,
+    /// Associated URLs (e.g., Wikipedia links). Entries that are not objects are dropped.
+    #[serde(default, deserialize_with = "lenient::entries")]
+    pub urls: Option<Vec<InfoboxUrl>>,
+    /// Structured attributes, e.g. the inception date of a Wikidata entity. Entries that
+    /// are not objects are dropped.
+    #[serde(default, deserialize_with = "lenient::entries")]
+    pub attributes: Option<Vec<InfoboxAttribute>>,
     /// The name of the primary engine providing this infobox.
     pub engine: String,
     /// Primary URL for the entity.
@@ -138,11 +599,67 @@ pub struct Infobox {
     pub category: String,
     /// The date this entry was published, if available.
     #[serde(rename = "publishedDate")]
-    pub published_date: Option<NaiveDateTime>,
+    pub published_date: Option<PublishedDate>,
     /// Legacy publication date string.
     pub pubdate: Option<String>,
 }
 
+/// A link of an [`Infobox`].
+///
+/// Fields SearXNG engines add beyond these are kept in `extra`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InfoboxUrl {
+    /// The link text, e.g. `Wikipedia` or `Official website`.
+    #[serde(default, deserialize_with = "lenient::text")]
+    pub title: String,
+    #[serde(default, deserialize_with = "lenient::text")]
+    pub url: String,
+    /// Whether this is the official website of the entity.
+    #[serde(default, deserialize_with = "lenient::flag")]
+    pub official: bool,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// A labelled fact of an [`Infobox`], holding a text value, an image, or both.
+///
+/// Fields SearXNG engines add beyond these are kept in `extra`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InfoboxAttribute {
+    /// The name of the fact, e.g. `Inception`.
+    #[serde(default, deserialize_with = "lenient::text")]
+    pub label: String,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::string"
+    )]
+    pub value: Option<String>,
+    /// The identifier of the property, e.g. the Wikidata property `P571`.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::string"
+    )]
+    pub entity: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::shaped"
+    )]
+    pub image: Option<InfoboxImage>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// An image of an [`InfoboxAttribute`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InfoboxImage {
+    pub src: String,
+    #[serde(default)]
+    pub alt: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Result {
     // The Result Class
@@ -153,7 +670,55 @@ pub struct Result {
 }
 
 type BaseAnswer = Result;
-type AnswerSet = Vec<BaseAnswer>;
+
+/// An instant answer, in any of the forms SearXNG versions send.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Answer {
+    /// A bare answer text.
+    Text(String),
+    /// An answer object with its text, as sent by SearXNG versions with typed answers.
+    Modern(ModernAnswer),
+    /// A result stub without an answer text.
+    Legacy(BaseAnswer),
+}
+
+/// An answer object with its text.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModernAnswer {
+    // The Answer Class
+    // from https://github.com/searxng/searxng/blob/master/searx/result_types/answer.py
+    pub answer: String,
+    #[serde(default)]
+    pub url: Option<String>,
+    /// The template used to render the answer, e.g. `answer/legacy.html`.
+    #[serde(default)]
+    pub template: Option<String>,
+    #[serde(default)]
+    pub engine: Option<String>,
+    #[serde(default)]
+    pub parsed_url: Option<Vec<String>>,
+}
+
+impl Answer {
+    /// The answer text, unless this is a legacy stub without one.
+    pub fn text(&self) -> Option<&str> {
+        match self {
+            Answer::Text(text) => Some(text),
+            Answer::Modern(m) => Some(&m.answer),
+            Answer::Legacy(_) => None,
+        }
+    }
+
+    /// The URL of the answer's source, if it has one.
+    pub fn url(&self) -> Option<&str> {
+        match self {
+            Answer::Text(_) => None,
+            Answer::Modern(m) => m.url.as_deref(),
+            Answer::Legacy(l) => l.url.as_deref(),
+        }
+    }
+}
 type SetStr = String;
 type Correction = SetStr;
 type Suggestion = SetStr;
@@ -173,3 +738,972 @@ impl From<(String, String)> for EngineError {
         }
     }
 }
+
+/// A publication date as reported by an engine.
+///
+/// Engines report dates either as naive timestamps or with a UTC offset. The offset is
+/// preserved when present, while [`Deref`] to [`NaiveDateTime`] keeps the naive,
+/// wall-clock view available for existing code.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct PublishedDate {
+    naive: NaiveDateTime,
+    offset: Option<FixedOffset>,
+}
+
+impl PublishedDate {
+    /// The wall-clock date and time, as reported by the engine.
+    pub fn naive(&self) -> NaiveDateTime {
+        self.naive
+    }
+
+    /// The UTC offset reported by the engine, if any.
+    pub fn offset(&self) -> Option<FixedOffset> {
+        self.offset
+    }
+
+    /// Converts the date to UTC.
+    ///
+    /// Naive dates are assumed to already be in UTC.
+    pub fn to_utc(&self) -> DateTime<Utc> {
+        match self.offset {
+            Some(offset) => offset
+                .from_local_datetime(&self.naive)
+                .single()
+                .map_or_else(|| self.naive.and_utc(), |d| d.to_utc()),
+            None => self.naive.and_utc(),
+        }
+    }
+
+    /// Converts the date to the given time zone.
+    pub fn to_local<Tz: TimeZone>(&self, tz: &Tz) -> DateTime<Tz> {
+        self.to_utc().with_timezone(tz)
+    }
+}
+
+impl Deref for PublishedDate {
+    type Target = NaiveDateTime;
+
+    fn deref(&self) -> &Self::Target {
+        &self.naive
+    }
+}
+
+impl From<NaiveDateTime> for PublishedDate {
+    fn from(naive: NaiveDateTime) -> Self {
+        PublishedDate {
+            naive,
+            offset: None,
+        }
+    }
+}
+
+impl<Tz: TimeZone<Offset = FixedOffset>> From<DateTime<Tz>> for PublishedDate {
+    fn from(date: DateTime<Tz>) -> Self {
+        PublishedDate {
+            naive: date.naive_local(),
+            offset: Some(*date.offset()),
+        }
+    }
+}
+
+impl From<PublishedDate> for NaiveDateTime {
+    fn from(date: PublishedDate) -> Self {
+        date.naive
+    }
+}
+
+impl Serialize for PublishedDate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self.offset {
+            Some(offset) => match offset.from_local_datetime(&self.naive).single() {
+                Some(date) => date.serialize(serializer),
+                None => self.naive.serialize(serializer),
+            },
+            None => self.naive.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for PublishedDate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        if let Ok(date) = DateTime::parse_from_rfc3339(&raw) {
+            return Ok(date.into());
+        }
+        ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"]
+            .iter()
+            .find_map(|fmt| NaiveDateTime::parse_from_str(&raw, fmt).ok())
+            .map(PublishedDate::from)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid publishedDate: {raw}")))
+    }
+}
+
+impl MainSearchResult {
+    /// The publication date converted to UTC, if available.
+    pub fn published_date_utc(&self) -> Option<DateTime<Utc>> {
+        self.published_date.as_ref().map(PublishedDate::to_utc)
+    }
+
+    /// The publication date converted to the given time zone, if available.
+    pub fn published_date_local<Tz: TimeZone>(&self, tz: &Tz) -> Option<DateTime<Tz>> {
+        self.published_date.as_ref().map(|d| d.to_local(tz))
+    }
+}
+
+impl LegacySearchResult {
+    /// The publication date converted to UTC, if available.
+    pub fn published_date_utc(&self) -> Option<DateTime<Utc>> {
+        self.published_date.as_ref().map(PublishedDate::to_utc)
+    }
+
+    /// The publication date converted to the given time zone, if available.
+    pub fn published_date_local<Tz: TimeZone>(&self, tz: &Tz) -> Option<DateTime<Tz>> {
+        self.published_date.as_ref().map(|d| d.to_local(tz))
+    }
+}
+
+impl Infobox {
+    /// The publication date converted to UTC, if available.
+    pub fn published_date_utc(&self) -> Option<DateTime<Utc>> {
+        self.published_date.as_ref().map(PublishedDate::to_utc)
+    }
+
+    /// The publication date converted to the given time zone, if available.
+    pub fn published_date_local<Tz: TimeZone>(&self, tz: &Tz) -> Option<DateTime<Tz>> {
+        self.published_date.as_ref().map(|d| d.to_local(tz))
+    }
+}
+
+impl SearchResult {
+    /// The publication date converted to UTC, if available.
+    pub fn published_date_utc(&self) -> Option<DateTime<Utc>> {
+        self.published_date().as_ref().map(PublishedDate::to_utc)
+    }
+
+    /// The publication date converted to the given time zone, if available.
+    pub fn published_date_local<Tz: TimeZone>(&self, tz: &Tz) -> Option<DateTime<Tz>> {
+        self.published_date().as_ref().map(|d| d.to_local(tz))
+    }
+
+    /// The fields of this result that this crate does not model.
+    pub fn extra(&self) -> &HashMap<String, serde_json::Value> {
+        match self {
+            SearchResult::LegacyResult(l) => &l.extra,
+            SearchResult::MainResult(m) => &m.extra,
+        }
+    }
+
+    /// The URL of the result, if it has one.
+    pub fn url(&self) -> Option<&str> {
+        self.by_ref().url()
+    }
+
+    /// The title of the result.
+    pub fn title(&self) -> &str {
+        self.by_ref().title()
+    }
+
+    /// The content snippet of the result, as returned by the instance.
+    pub fn content(&self) -> &str {
+        self.by_ref().content()
+    }
+
+    /// The engine that returned the result first, if reported.
+    pub fn engine(&self) -> Option<&str> {
+        match self {
+            SearchResult::LegacyResult(l) => Some(&l.engine),
+            SearchResult::MainResult(m) => m.engine.as_deref(),
+        }
+    }
+
+    /// All engines that returned the result.
+    pub fn engines(&self) -> &[Symbol] {
+        match self {
+            SearchResult::LegacyResult(l) => &l.engines,
+            SearchResult::MainResult(m) => &m.engines,
+        }
+    }
+
+    /// The positions of the result in the rankings of its engines, 1-based.
+    pub fn positions(&self) -> &[i32] {
+        match self {
+            SearchResult::LegacyResult(l) => &l.positions,
+            SearchResult::MainResult(m) => &m.positions,
+        }
+    }
+
+    /// The score SearXNG ranked the result by.
+    pub fn score(&self) -> f64 {
+        match self {
+            SearchResult::LegacyResult(l) => l.score,
+            SearchResult::MainResult(m) => m.score,
+        }
+    }
+
+    /// The publication date, if available.
+    pub fn published_date(&self) -> Option<PublishedDate> {
+        self.by_ref().published_date()
+    }
+
+    /// Flattens this result into a [`WebResult`], the fields both variants share.
+    pub fn normalize(&self) -> WebResult {
+        WebResult::from(self)
+    }
+}
+
+/// A flattened, format-independent view of a result.
+///
+/// It holds only the fields every response format can provide, so code consuming it
+/// keeps working when results are obtained from another format than JSON.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebResult {
+    pub url: Option<String>,
+    pub title: String,
+    pub content: String,
+    /// The engines that returned the result.
+    pub engines: Vec<String>,
+    pub score: f64,
+    pub category: String,
+    pub published_date: Option<PublishedDate>,
+    pub thumbnail: Option<String>,
+}
+
+impl From<&SearchResult> for WebResult {
+    fn from(result: &SearchResult) -> Self {
+        macro_rules! flatten {
+            ($r:expr) => {
+                WebResult {
+                    url: $r.url.clone(),
+                    title: $r.title.clone(),
+                    content: $r.content.clone(),
+                    engines: $r.engines.iter().map(ToString::to_string).collect(),
+                    score: $r.score,
+                    category: $r.category.to_string(),
+                    published_date: $r.published_date,
+                    thumbnail: Some($r.thumbnail.trim().to_string()).filter(|t| !t.is_empty()),
+                }
+            };
+        }
+        match result {
+            SearchResult::LegacyResult(l) => flatten!(l),
+            SearchResult::MainResult(m) => flatten!(m),
+        }
+    }
+}
+
+impl From<SearchResult> for WebResult {
+    fn from(result: SearchResult) -> Self {
+        WebResult::from(&result)
+    }
+}
+
+impl SearchResponse {
+    /// Returns the results flattened into [`WebResult`]s.
+    pub fn web_results(&self) -> Vec<WebResult> {
+        self.results.iter().map(WebResult::from).collect()
+    }
+}
+
+/// An image result, from the `images.html` template.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImageResult {
+    pub title: String,
+    /// The page the image was found on.
+    pub url: Option<String>,
+    /// The full-size image.
+    pub img_src: String,
+    pub thumbnail: Option<String>,
+    /// The dimensions as reported by the engine, e.g. `1920 x 1080`.
+    pub resolution: Option<String>,
+    pub format: Option<String>,
+    /// The site or collection hosting the image.
+    pub source: Option<String>,
+    pub author: Option<String>,
+    /// The file size in bytes, if the engine reported a parsable size.
+    pub size: Option<u64>,
+}
+
+/// A video result, from the `videos.html` template.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VideoResult {
+    pub title: String,
+    pub url: Option<String>,
+    pub content: String,
+    pub thumbnail: Option<String>,
+    /// An embeddable player, see [`VideoEmbed`](crate::embed::VideoEmbed).
+    pub iframe_src: Option<String>,
+    pub duration: Option<std::time::Duration>,
+    pub author: Option<String>,
+    pub published_date: Option<PublishedDate>,
+}
+
+/// A torrent result, from the `torrent.html` template.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TorrentResult {
+    pub title: String,
+    pub url: Option<String>,
+    /// The validated magnet link, see [`SearchResult::magnet`].
+    pub magnet: Option<Magnet>,
+    pub torrentfile: Option<String>,
+    pub seed: Option<u64>,
+    pub leech: Option<u64>,
+    /// The number of files in the torrent.
+    pub files: Option<u64>,
+    /// The total size in bytes, if the engine reported a parsable size.
+    pub size: Option<u64>,
+}
+
+/// A code result, from the `code.html` template.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CodeResult {
+    pub title: String,
+    pub url: Option<String>,
+    pub repository: Option<String>,
+    pub language: Option<String>,
+    /// Line numbers and their source.
+    pub lines: Vec<(u32, String)>,
+    /// The numbers of the lines matching the query.
+    pub highlighted: Vec<u32>,
+}
+
+/// A result with its template-specific fields, see [`SearchResult::typed`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypedResult {
+    Image(ImageResult),
+    Video(VideoResult),
+    Torrent(TorrentResult),
+    File(FileResult),
+    Map(MapResult),
+    Paper(PaperResult),
+    Code(CodeResult),
+    /// Any other template, or a result lacking the fields its template requires.
+    Web(WebResult),
+}
+
+fn non_empty(s: &str) -> Option<String> {
+    let s = s.trim();
+    (!s.is_empty()).then(|| s.to_string())
+}
+
+/// A borrowed result of either variant, on which the typed views are implemented once
+/// for [`SearchResult`], [`MainSearchResult`] and [`LegacySearchResult`].
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum ResultRef<'a> {
+    Legacy(&'a LegacySearchResult),
+    Main(&'a MainSearchResult),
+}
+
+impl<'a> ResultRef<'a> {
+    pub(crate) fn title(self) -> &'a str {
+        match self {
+            ResultRef::Legacy(l) => &l.title,
+            ResultRef::Main(m) => &m.title,
+        }
+    }
+
+    pub(crate) fn url(self) -> Option<&'a str> {
+        match self {
+            ResultRef::Legacy(l) => l.url.as_deref(),
+            ResultRef::Main(m) => m.url.as_deref(),
+        }
+    }
+
+    pub(crate) fn content(self) -> &'a str {
+        match self {
+            ResultRef::Legacy(l) => &l.content,
+            ResultRef::Main(m) => &m.content,
+        }
+    }
+
+    pub(crate) fn published_date(self) -> Option<PublishedDate> {
+        match self {
+            ResultRef::Legacy(l) => l.published_date,
+            ResultRef::Main(m) => m.published_date,
+        }
+    }
+
+    pub(crate) fn template_fields(self) -> &'a TemplateFields {
+        match self {
+            ResultRef::Legacy(l) => &l.template_fields,
+            ResultRef::Main(m) => &m.template_fields,
+        }
+    }
+
+    /// The name of the result template without its extension, e.g. `images`.
+    pub(crate) fn template_name(self) -> &'a str {
+        let template = match self {
+            ResultRef::Legacy(l) => &l.template,
+            ResultRef::Main(m) => &m.template,
+        };
+        template.trim_end_matches(".html")
+    }
+
+    pub(crate) fn category(self) -> &'a str {
+        match self {
+            ResultRef::Legacy(l) => &l.category,
+            ResultRef::Main(m) => &m.category,
+        }
+    }
+
+    pub(crate) fn img_src(self) -> Option<String> {
+        match self {
+            ResultRef::Legacy(l) => non_empty(&l.img_src),
+            ResultRef::Main(m) => non_empty(&m.img_src),
+        }
+    }
+
+    pub(crate) fn thumbnail(self) -> Option<String> {
+        match self {
+            ResultRef::Legacy(l) => non_empty(&l.thumbnail),
+            ResultRef::Main(m) => non_empty(&m.thumbnail),
+        }
+    }
+
+    pub(crate) fn author_name(self) -> Option<String> {
+        match self {
+            ResultRef::Legacy(l) => l.author.as_deref().and_then(non_empty),
+            ResultRef::Main(m) => non_empty(&m.author),
+        }
+    }
+
+    pub(crate) fn duration(self) -> Option<std::time::Duration> {
+        match self {
+            ResultRef::Legacy(l) => l.length.as_deref().and_then(parse_duration),
+            ResultRef::Main(m) => m.length.map(std::time::Duration::from),
+        }
+    }
+
+    pub(crate) fn audio_src(self) -> Option<String> {
+        match self {
+            ResultRef::Legacy(l) => l.audio_src.as_deref().and_then(non_empty),
+            ResultRef::Main(m) => non_empty(&m.audio_src),
+        }
+    }
+
+    /// The embeddable player of the result; legacy results have none.
+    pub(crate) fn player(self) -> Option<String> {
+        match self {
+            ResultRef::Legacy(_) => None,
+            ResultRef::Main(m) => non_empty(&m.iframe_src),
+        }
+    }
+
+    /// The view count of the result; legacy results have none.
+    pub(crate) fn views(self) -> Option<String> {
+        match self {
+            ResultRef::Legacy(_) => None,
+            ResultRef::Main(m) => non_empty(&m.views),
+        }
+    }
+
+    pub(crate) fn engines(self) -> &'a [Symbol] {
+        match self {
+            ResultRef::Legacy(l) => &l.engines,
+            ResultRef::Main(m) => &m.engines,
+        }
+    }
+
+    pub(crate) fn image(self) -> Option<ImageResult> {
+        let img_src = self.img_src()?;
+        if self.template_name() != "images" {
+            return None;
+        }
+        let fields = self.template_fields();
+        Some(ImageResult {
+            title: self.title().to_string(),
+            url: self.url().map(str::to_string),
+            img_src,
+            thumbnail: (fields.thumbnail_src.as_deref())
+                .and_then(non_empty)
+                .or_else(|| self.thumbnail()),
+            resolution: fields.resolution.clone(),
+            format: fields.img_format.clone(),
+            source: fields.source.clone(),
+            author: self.author_name(),
+            size: fields.size_bytes(),
+        })
+    }
+
+    pub(crate) fn video(self) -> Option<VideoResult> {
+        if self.template_name() != "videos" {
+            return None;
+        }
+        Some(VideoResult {
+            title: self.title().to_string(),
+            url: self.url().map(str::to_string),
+            content: self.content().to_string(),
+            thumbnail: self.thumbnail(),
+            iframe_src: self.player(),
+            duration: self.duration(),
+            author: self.author_name(),
+            published_date: self.published_date(),
+        })
+    }
+
+    pub(crate) fn code(self) -> Option<CodeResult> {
+        let fields = self.template_fields();
+        if self.template_name() != "code" && fields.codelines.is_none() {
+            return None;
+        }
+        Some(CodeResult {
+            title: self.title().to_string(),
+            url: self.url().map(str::to_string),
+            repository: fields.repository.clone(),
+            language: fields.code_language.clone(),
+            lines: fields.codelines.clone().unwrap_or_default(),
+            highlighted: fields.hl_lines.clone().unwrap_or_default(),
+        })
+    }
+
+    pub(crate) fn torrent(self) -> Option<TorrentResult> {
+        let magnet = self.magnet();
+        if self.template_name() != "torrent" && magnet.is_none() {
+            return None;
+        }
+        let fields = self.template_fields();
+        Some(TorrentResult {
+            title: self.title().to_string(),
+            url: self.url().map(str::to_string),
+            magnet,
+            torrentfile: fields.torrentfile.clone(),
+            seed: fields.seed,
+            leech: fields.leech,
+            files: fields.files,
+            size: fields.size_bytes(),
+        })
+    }
+}
+
+impl SearchResult {
+    /// Borrows this result as a [`ResultRef`].
+    pub(crate) fn by_ref(&self) -> ResultRef<'_> {
+        match self {
+            SearchResult::LegacyResult(l) => ResultRef::Legacy(l),
+            SearchResult::MainResult(m) => ResultRef::Main(m),
+        }
+    }
+
+    /// The fields of the result template, see [`TemplateFields`].
+    pub fn template_fields(&self) -> &TemplateFields {
+        self.by_ref().template_fields()
+    }
+
+    /// The fields of the result template, mutably.
+    pub fn template_fields_mut(&mut self) -> &mut TemplateFields {
+        match self {
+            SearchResult::LegacyResult(l) => &mut l.template_fields,
+            SearchResult::MainResult(m) => &mut m.template_fields,
+        }
+    }
+
+    pub(crate) fn template_name(&self) -> &str {
+        self.by_ref().template_name()
+    }
+
+    /// Returns the image view of this result, if it is an image result.
+    pub fn image(&self) -> Option<ImageResult> {
+        self.by_ref().image()
+    }
+
+    /// Returns the video view of this result, if it is a video result.
+    pub fn video(&self) -> Option<VideoResult> {
+        self.by_ref().video()
+    }
+
+    /// Returns the code view of this result, if it is a code result.
+    ///
+    /// Results using the `code.html` template, or carrying source lines, are considered
+    /// code.
+    pub fn code(&self) -> Option<CodeResult> {
+        self.by_ref().code()
+    }
+
+    /// Returns the torrent view of this result, if it is a torrent result.
+    ///
+    /// Results using the `torrent.html` template, or carrying a valid magnet link, are
+    /// considered torrents.
+    pub fn torrent(&self) -> Option<TorrentResult> {
+        self.by_ref().torrent()
+    }
+
+    /// Returns this result with its template-specific fields.
+    ///
+    /// The variant is chosen by the result template. Results of other templates, and
+    /// results lacking the fields their template requires (such as a map result without
+    /// coordinates), are returned as [`TypedResult::Web`].
+    pub fn typed(&self) -> TypedResult {
+        let typed = match self.template_name() {
+            "images" => self.image().map(TypedResult::Image),
+            "videos" => self.video().map(TypedResult::Video),
+            "torrent" => self.torrent().map(TypedResult::Torrent),
+            "files" | "file" => self.file().map(TypedResult::File),
+            "map" => self.map_result().map(TypedResult::Map),
+            "paper" => self.paper().map(TypedResult::Paper),
+            "code" => self.code().map(TypedResult::Code),
+            _ => None,
+        };
+        typed.unwrap_or_else(|| TypedResult::Web(self.into()))
+    }
+}
+
+impl MainSearchResult {
+    /// Same as [`SearchResult::image`].
+    pub fn image(&self) -> Option<ImageResult> {
+        ResultRef::Main(self).image()
+    }
+
+    /// Same as [`SearchResult::video`].
+    pub fn video(&self) -> Option<VideoResult> {
+        ResultRef::Main(self).video()
+    }
+
+    /// Same as [`SearchResult::code`].
+    pub fn code(&self) -> Option<CodeResult> {
+        ResultRef::Main(self).code()
+    }
+}
+
+impl LegacySearchResult {
+    /// Same as [`SearchResult::image`].
+    pub fn image(&self) -> Option<ImageResult> {
+        ResultRef::Legacy(self).image()
+    }
+
+    /// Same as [`SearchResult::video`].
+    pub fn video(&self) -> Option<VideoResult> {
+        ResultRef::Legacy(self).video()
+    }
+
+    /// Same as [`SearchResult::code`].
+    pub fn code(&self) -> Option<CodeResult> {
+        ResultRef::Legacy(self).code()
+    }
+}
+
+impl SearchResponse {
+    /// Returns the results with their template-specific fields, see [`SearchResult::typed`].
+    pub fn typed_results(&self) -> Vec<TypedResult> {
+        self.results.iter().map(SearchResult::typed).collect()
+    }
+}
+
+/// Deserializers for template-specific fields, whose types differ between engines.
+mod lenient {
+    use serde::de::DeserializeOwned;
+    use serde::{Deserialize, Deserializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrNumber {
+        String(String),
+        Unsigned(u64),
+        Float(f64),
+    }
+
+    /// Accepts a string or a number.
+    pub(super) fn string<'de, D: Deserializer<'de>>(d: D) -> Result<Option<String>, D::Error> {
+        Ok(
+            Option::<StringOrNumber>::deserialize(d)?.and_then(|v| match v {
+                StringOrNumber::String(s) if s.trim().is_empty() => None,
+                StringOrNumber::String(s) => Some(s),
+                StringOrNumber::Unsigned(n) => Some(n.to_string()),
+                StringOrNumber::Float(n) => Some(n.to_string()),
+            }),
+        )
+    }
+
+    /// Like [`string`], but treats missing values as empty.
+    pub(super) fn text<'de, D: Deserializer<'de>>(d: D) -> Result<String, D::Error> {
+        Ok(string(d)?.unwrap_or_default())
+    }
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum BoolOrString {
+        Bool(bool),
+        String(String),
+        Number(f64),
+    }
+
+    /// Accepts a boolean, a number or a string. Only `true`, `"true"`, `"1"` and non-zero
+    /// numbers count as set.
+    pub(super) fn flag<'de, D: Deserializer<'de>>(d: D) -> Result<bool, D::Error> {
+        Ok(match Option::<BoolOrString>::deserialize(d)? {
+            Some(BoolOrString::Bool(b)) => b,
+            Some(BoolOrString::String(s)) => matches!(s.trim(), "true" | "True" | "1"),
+            Some(BoolOrString::Number(n)) => n != 0.0,
+            None => false,
+        })
+    }
+
+    /// Accepts a list of any values, dropping the elements without the expected shape.
+    pub(super) fn entries<'de, D: Deserializer<'de>, T: DeserializeOwned>(
+        d: D,
+    ) -> Result<Option<Vec<T>>, D::Error> {
+        Ok(
+            Option::<Vec<serde_json::Value>>::deserialize(d)?.map(|list| {
+                list.into_iter()
+                    .filter_map(|v| T::deserialize(v).ok())
+                    .collect()
+            }),
+        )
+    }
+
+    /// Accepts a non-negative number, or a string containing one. Unparsable strings are
+    /// treated as missing.
+    pub(super) fn count<'de, D: Deserializer<'de>>(d: D) -> Result<Option<u64>, D::Error> {
+        Ok(
+            Option::<StringOrNumber>::deserialize(d)?.and_then(|v| match v {
+                StringOrNumber::String(s) => s.trim().replace(',', "").parse().ok(),
+                StringOrNumber::Unsigned(n) => Some(n),
+                StringOrNumber::Float(n) => (n >= 0.0).then_some(n as u64),
+            }),
+        )
+    }
+
+    /// Accepts a number, or a string containing one. Unparsable strings are treated as
+    /// missing.
+    pub(super) fn float<'de, D: Deserializer<'de>>(d: D) -> Result<Option<f64>, D::Error> {
+        Ok(Option::<StringOrNumber>::deserialize(d)?.and_then(to_float))
+    }
+
+    /// Accepts a list of numbers or numeric strings. The list is treated as missing if any
+    /// element cannot be parsed.
+    pub(super) fn float_list<'de, D: Deserializer<'de>>(
+        d: D,
+    ) -> Result<Option<Vec<f64>>, D::Error> {
+        Ok(Option::<Vec<StringOrNumber>>::deserialize(d)?
+            .and_then(|list| list.into_iter().map(to_float).collect()))
+    }
+
+    fn to_float(v: StringOrNumber) -> Option<f64> {
+        match v {
+            StringOrNumber::String(s) => s.trim().parse().ok(),
+            StringOrNumber::Unsigned(n) => Some(n as f64),
+            StringOrNumber::Float(n) => Some(n),
+        }
+    }
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrList {
+        String(String),
+        List(Vec<String>),
+    }
+
+    /// Accepts any value, treating values without the expected shape as missing.
+    pub(super) fn shaped<'de, D: Deserializer<'de>, T: DeserializeOwned>(
+        d: D,
+    ) -> Result<Option<T>, D::Error> {
+        Ok(Option::<serde_json::Value>::deserialize(d)?.and_then(|v| T::deserialize(v).ok()))
+    }
+
+    /// Accepts a list of strings, or a single string holding one item. Empty strings are
+    /// dropped.
+    pub(super) fn string_list<'de, D: Deserializer<'de>>(
+        d: D,
+    ) -> Result<Option<Vec<String>>, D::Error> {
+        let list = match Option::<StringOrList>::deserialize(d)? {
+            None => return Ok(None),
+            Some(StringOrList::String(s)) => vec![s],
+            Some(StringOrList::List(list)) => list,
+        };
+        let list: Vec<String> = list.into_iter().filter(|s| !s.trim().is_empty()).collect();
+        Ok((!list.is_empty()).then_some(list))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Timelike;
+
+    #[test]
+    fn test_published_date_offsets() {
+        let aware: PublishedDate = serde_json::from_str(r#""2024-05-01T08:30:00+02:00""#).unwrap();
+        assert_eq!(aware.hour(), 8);
+        assert_eq!(aware.to_utc().to_rfc3339(), "2024-05-01T06:30:00+00:00");
+        assert_eq!(
+            serde_json::to_string(&aware).unwrap(),
+            r#""2024-05-01T08:30:00+02:00""#
+        );
+
+        let naive: PublishedDate = serde_json::from_str(r#""2024-05-01T08:30:00""#).unwrap();
+        assert_eq!(naive.offset(), None);
+        assert_eq!(naive.to_utc().to_rfc3339(), "2024-05-01T08:30:00+00:00");
+    }
+
+    #[test]
+    fn test_typed_results() {
+        let with_template = |template: &str, fields: serde_json::Value| -> SearchResult {
+            let mut value = serde_json::json!({
+                "url": "https://example.org",
+                "template": template,
+                "engine": "bing",
+                "title": "t",
+                "content": "",
+                "img_src": "",
+                "thumbnail": "",
+                "priority": "",
+                "engines": ["bing"],
+                "positions": [1],
+                "score": 1.0,
+                "category": "general",
+            });
+            for (key, field) in fields.as_object().unwrap() {
+                value[key] = field.clone();
+            }
+            serde_json::from_value(value).unwrap()
+        };
+
+        let image = with_template(
+            "images.html",
+            serde_json::json!({"img_src": "https://example.org/cat.jpg", "resolution": "640 x 480"}),
+        );
+        assert!(matches!(
+            image.typed(),
+            TypedResult::Image(ImageResult { resolution: Some(r), .. }) if r == "640 x 480"
+        ));
+
+        let code = with_template(
+            "code.html",
+            serde_json::json!({"codelines": [[3, "fn main() {}"]], "hl_lines": [3]}),
+        );
+        let TypedResult::Code(code) = code.typed() else {
+            panic!("not a code result");
+        };
+        assert_eq!(code.lines, [(3, "fn main() {}".to_string())]);
+        assert_eq!(code.highlighted, [3]);
+
+        let map = with_template("map.html", serde_json::json!({}));
+        assert!(matches!(map.typed(), TypedResult::Web(_)));
+    }
+
+    #[test]
+    fn test_infobox_entries() {
+        let infobox: Infobox = serde_json::from_value(serde_json::json!({
+            "infobox": "Rust", "id": "https://www.wikidata.org/wiki/Q575650", "content": "",
+            "urls": [
+                {"title": "Official website", "url": "https://www.rust-lang.org", "official": true},
+                {"title": "Wikipedia (en)", "url": "https://en.wikipedia.org/wiki/Rust", "entity": "wikipedia"},
+                "https://example.org"
+            ],
+            "attributes": [
+                {"label": "Inception", "value": "2010", "entity": "P571"},
+                {"label": "Stable version", "value": 1.85},
+                {"label": "Logo", "image": {"src": "https://example.org/rust.svg", "alt": "Rust"}}
+            ],
+            "engine": "wikidata", "url": null, "img_src": "", "template": "", "parsed_url": null,
+            "title": "", "thumbnail": "", "priority": "", "engines": ["wikidata"], "positions": "",
+            "score": 0.0, "category": "general", "publishedDate": null, "pubdate": null
+        }))
+        .unwrap();
+
+        let urls = infobox.urls.as_ref().unwrap();
+        assert_eq!(urls.len(), 2);
+        assert!(urls[0].official);
+        assert!(!urls[1].official);
+        assert_eq!(urls[1].extra["entity"], "wikipedia");
+
+        let attributes = infobox.attributes.as_ref().unwrap();
+        assert_eq!(attributes[0].entity.as_deref(), Some("P571"));
+        assert_eq!(attributes[1].value.as_deref(), Some("1.85"));
+        assert_eq!(attributes[2].value, None);
+        assert_eq!(attributes[2].image.as_ref().unwrap().alt, "Rust");
+
+        let value = serde_json::to_value(&infobox).unwrap();
+        assert_eq!(value["urls"][1]["entity"], "wikipedia");
+        assert_eq!(value["attributes"][0]["label"], "Inception");
+    }
+
+    #[test]
+    fn test_answer_forms() {
+        let answers: Vec<Answer> = serde_json::from_value(serde_json::json!([
+            "42",
+            {"answer": "1 EUR = 1.08 USD", "url": "https://duckduckgo.com", "template": "answer/legacy.html", "engine": "currency"},
+            {"url": "https://example.org", "engine": "wikipedia", "parsed_url": null}
+        ]))
+        .unwrap();
+        assert!(matches!(answers[0], Answer::Text(_)));
+        assert!(matches!(answers[1], Answer::Modern(_)));
+        assert!(matches!(answers[2], Answer::Legacy(_)));
+        let texts: Vec<_> = answers.iter().map(Answer::text).collect();
+        assert_eq!(texts, [Some("42"), Some("1 EUR = 1.08 USD"), None]);
+        let urls: Vec<_> = answers.iter().map(Answer::url).collect();
+        assert_eq!(
+            urls,
+            [
+                None,
+                Some("https://duckduckgo.com"),
+                Some("https://example.org")
+            ]
+        );
+
+        let round_trip: Vec<Answer> =
+            serde_json::from_value(serde_json::to_value(&answers).unwrap()).unwrap();
+        assert!(matches!(round_trip[1], Answer::Modern(_)));
+    }
+
+    #[test]
+    fn test_result_accessors() {
+        let legacy: SearchResult = serde_json::from_value(serde_json::json!({
+            "url": "https://example.org",
+            "template": "default.html",
+            "engine": "duckduckgo",
+            "title": "Example",
+            "content": "<b>text</b>",
+            "img_src": "",
+            "thumbnail": "",
+            "priority": "",
+            "engines": ["duckduckgo"],
+            "positions": [1],
+            "score": 1.0,
+            "category": "general",
+            "publishedDate": null,
+        }))
+        .unwrap();
+        // Main results carry the media fields of every template, and no single engine.
+        let main: SearchResult = serde_json::from_value(serde_json::json!({
+            "url": "https://example.org",
+            "engine": null,
+            "parsed_url": null,
+            "template": "default.html",
+            "title": "Example",
+            "content": "<b>text</b>",
+            "img_src": "",
+            "iframe_src": "",
+            "audio_src": "",
+            "thumbnail": "",
+            "publishedDate": "2024-05-01T08:30:00",
+            "pubdate": null,
+            "length": null,
+            "views": "",
+            "author": "",
+            "metadata": "",
+            "priority": "",
+            "engines": ["duckduckgo"],
+            "open_group": false,
+            "close_group": false,
+            "positions": [1],
+            "score": 1.0,
+            "category": "general",
+        }))
+        .unwrap();
+        assert!(matches!(legacy, SearchResult::LegacyResult(_)));
+        assert!(matches!(main, SearchResult::MainResult(_)));
+
+        for result in [&legacy, &main] {
+            assert_eq!(result.url(), Some("https://example.org"));
+            assert_eq!(result.title(), "Example");
+            assert_eq!(result.content(), "<b>text</b>");
+            assert_eq!(result.engines(), ["duckduckgo"]);
+            assert_eq!(result.score(), 1.0);
+            assert_eq!(result.normalize().title, "Example");
+        }
+        assert_eq!(legacy.engine(), Some("duckduckgo"));
+        assert_eq!(main.engine(), None);
+        assert_eq!(legacy.published_date(), None);
+        assert_eq!(main.published_date(), main.normalize().published_date);
+        assert!(main.published_date().is_some());
+    }
+}
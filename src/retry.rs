@@ -0,0 +1,251 @@
+//! Retrying failed requests.
+//!
+//! Every search request, including each page of a paginated search, goes through the
+//! client's [`RetryPolicy`], set with
+//! [`SearXNGClient::with_retry_policy`](crate::SearXNGClient::with_retry_policy). Once the
+//! attempts are exhausted the last error is returned, so pagination never loops forever
+//! on an instance that stays down.
+
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::error::Error;
+
+/// The default number of attempts, including the first one.
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+
+/// How long to wait between attempts.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Backoff {
+    /// Waits the same time before every retry.
+    Fixed(Duration),
+    /// Doubles the wait after every retry, starting at `initial` and never exceeding `max`.
+    Exponential { initial: Duration, max: Duration },
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Backoff::Exponential {
+            initial: Duration::from_millis(250),
+            max: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Decides which errors are worth retrying.
+type Retryable = Arc<dyn Fn(&Error) -> bool + Send + Sync>;
+
+/// When and how often failed requests are sent again.
+///
+/// The default policy makes [`DEFAULT_MAX_ATTEMPTS`] attempts with jittered exponential
+/// backoff, retrying the errors [`is_transient`] accepts. A rate-limited request waits at
+/// least as long as its `Retry-After` header asks. When it asks for longer than the
+/// longest backoff of the policy, the [`Error::RateLimited`] is returned without waiting,
+/// since the request timeout does not cover the wait.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use searxng_client::{Error, SearXNGClient, ResponseFormat};
+/// use searxng_client::retry::{Backoff, RetryPolicy};
+/// let policy = RetryPolicy::new(5)
+///     .with_backoff(Backoff::Fixed(Duration::from_secs(1)))
+///     .with_jitter(0.0)
+///     .with_retryable(|e: &Error| e.is_timeout());
+/// assert_eq!(policy.delay(3), Duration::from_secs(1));
+/// let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json)
+///     .with_retry_policy(policy);
+/// ```
+#[derive(Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    backoff: Backoff,
+    jitter: f64,
+    retryable: Retryable,
+}
+
+impl fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("backoff", &self.backoff)
+            .field("jitter", &self.jitter)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy::new(DEFAULT_MAX_ATTEMPTS)
+    }
+}
+
+impl RetryPolicy {
+    /// Creates a policy making up to `max_attempts` attempts, with the default backoff,
+    /// jitter and retryable errors.
+    pub fn new(max_attempts: u32) -> Self {
+        RetryPolicy {
+            max_attempts: max_attempts.max(1),
+            backoff: Backoff::default(),
+            jitter: 0.5,
+            retryable: Arc::new(is_transient),
+        }
+    }
+
+    /// Never retries.
+    pub fn none() -> Self {
+        RetryPolicy::new(1)
+    }
+
+    /// Sets how long to wait between attempts.
+    pub fn with_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Sets the fraction of each wait, between `0.0` and `1.0`, that is randomized.
+    ///
+    /// With a jitter of `0.5`, a wait of one second becomes a random wait between half a
+    /// second and one second, which keeps many clients from retrying in lockstep.
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets which errors are retried, replacing [`is_transient`].
+    pub fn with_retryable(
+        mut self,
+        retryable: impl Fn(&Error) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.retryable = Arc::new(retryable);
+        self
+    }
+
+    /// The maximum number of attempts, including the first one.
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Returns `true` if a request that failed with `error` should be sent again.
+    pub fn is_retryable(&self, error: &Error) -> bool {
+        (self.retryable)(error)
+    }
+
+    /// How long to wait after failed attempt number `attempt`, starting at 1, before
+    /// jitter is applied.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        match self.backoff {
+            Backoff::Fixed(delay) => delay,
+            Backoff::Exponential { initial, max } => {
+                let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+                initial.saturating_mul(factor).min(max)
+            }
+        }
+    }
+
+    /// The longest wait of the backoff: the fixed delay, or the maximum of an exponential
+    /// backoff.
+    pub fn max_delay(&self) -> Duration {
+        match self.backoff {
+            Backoff::Fixed(delay) => delay,
+            Backoff::Exponential { max, .. } => max,
+        }
+    }
+
+    /// How long to actually wait after failed attempt number `attempt`, with jitter and
+    /// the `Retry-After` hint of `error` applied, or `None` if the hint is longer than
+    /// [`max_delay`](Self::max_delay).
+    pub(crate) fn wait(&self, attempt: u32, error: &Error) -> Option<Duration> {
+        let delay = self.delay(attempt);
+        let delay = delay.mul_f64(1.0 - self.jitter * random_unit());
+        match error {
+            Error::RateLimited {
+                retry_after: Some(retry_after),
+            } => (*retry_after <= self.max_delay()).then(|| delay.max(*retry_after)),
+            _ => Some(delay),
+        }
+    }
+}
+
+/// Returns `true` for errors that may not happen again: failed connections, timeouts,
+/// rate limiting and 5xx statuses.
+///
+/// Other statuses, bodies that fail to decode and sink failures are not retried, since
+/// sending the same request again would fail the same way.
+pub fn is_transient(error: &Error) -> bool {
+    match error {
+        Error::Transport(e) => !e.is_builder() && !e.is_redirect(),
+        Error::RateLimited { .. } => true,
+        Error::Http { status, .. } => status.is_server_error(),
+        Error::Decode { .. } | Error::Sink(_) => false,
+    }
+}
+
+/// A random number in `[0, 1)`, from the randomly seeded standard hasher.
+fn random_unit() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{ResponseFormat, SearXNGClient};
+    use crate::transport::{MockTransport, RawResponse};
+    use reqwest::StatusCode;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_backoff_and_retryable() {
+        let policy = RetryPolicy::new(4).with_backoff(Backoff::Exponential {
+            initial: Duration::from_millis(100),
+            max: Duration::from_millis(300),
+        });
+        let delays: Vec<_> = (1..=3).map(|attempt| policy.delay(attempt)).collect();
+        assert_eq!(delays, [100, 200, 300].map(Duration::from_millis));
+
+        let rate_limited = |millis| Error::RateLimited {
+            retry_after: Some(Duration::from_millis(millis)),
+        };
+        assert_eq!(
+            policy.wait(1, &rate_limited(250)),
+            Some(Duration::from_millis(250))
+        );
+        assert_eq!(policy.wait(1, &rate_limited(86_400_000)), None);
+        let wait = policy
+            .wait(2, &Error::Sink(std::io::ErrorKind::Other.into()))
+            .unwrap();
+        assert!((Duration::from_millis(100)..=Duration::from_millis(200)).contains(&wait));
+
+        let http = |status| Error::Http {
+            status,
+            body: String::new(),
+        };
+        assert!(policy.is_retryable(&http(StatusCode::BAD_GATEWAY)));
+        assert!(!policy.is_retryable(&http(StatusCode::FORBIDDEN)));
+        assert_eq!(RetryPolicy::new(0).max_attempts(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_long_retry_after_is_not_waited() {
+        let mut limited = RawResponse::status(StatusCode::TOO_MANY_REQUESTS);
+        limited
+            .headers
+            .insert("retry-after", HeaderValue::from_static("86400"));
+        let transport = MockTransport::new().with_response("rust", 1, limited);
+        let client = SearXNGClient::new("https://searx.example.org", ResponseFormat::Json)
+            .with_transport(transport.clone());
+
+        let search = client.search("rust");
+        let error = tokio::time::timeout(Duration::from_secs(5), search.send())
+            .await
+            .expect("the Retry-After hint is not waited")
+            .unwrap_err();
+        assert!(matches!(error, Error::RateLimited { .. }));
+        assert_eq!(transport.requests().len(), 1);
+    }
+}
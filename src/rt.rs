@@ -0,0 +1,52 @@
+//! The runtime services the crate relies on: timers and monotonic clocks.
+//!
+//! On native targets they come from Tokio and `std`. On `wasm32`, where neither works
+//! in a browser, they come from the JavaScript host: timers from `setTimeout`, and
+//! clocks from `performance.now()` through `web-time`.
+
+use std::time::Duration;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+pub(crate) use web_time::Instant;
+
+/// `Send` on native targets, where tasks may move between threads, and implemented by
+/// every type on `wasm32`, whose futures are single-threaded.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) trait MaybeSend: Send {}
+#[cfg(not(target_arch = "wasm32"))]
+impl<T: Send> MaybeSend for T {}
+
+/// See the native `MaybeSend`.
+#[cfg(target_arch = "wasm32")]
+pub(crate) trait MaybeSend {}
+#[cfg(target_arch = "wasm32")]
+impl<T> MaybeSend for T {}
+
+/// Waits for `duration`.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await;
+}
+
+/// Waits for `duration` with the host's `setTimeout`, or not at all if it has none.
+#[cfg(target_arch = "wasm32")]
+pub(crate) async fn sleep(duration: Duration) {
+    use wasm_bindgen::{JsCast, JsValue};
+
+    let millis = duration.as_millis().min(i32::MAX as u128) as i32;
+    let timer = js_sys::Promise::new(&mut |resolve, _| {
+        let global = js_sys::global();
+        match js_sys::Reflect::get(&global, &JsValue::from_str("setTimeout")) {
+            Ok(set_timeout) if set_timeout.is_function() => {
+                let set_timeout: js_sys::Function = set_timeout.unchecked_into();
+                let _ = set_timeout.call2(&global, &resolve, &JsValue::from(millis));
+            }
+            _ => {
+                let _ = resolve.call0(&JsValue::UNDEFINED);
+            }
+        }
+    });
+    let _ = wasm_bindgen_futures::JsFuture::from(timer).await;
+}
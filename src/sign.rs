@@ -0,0 +1,192 @@
+//! Signing outbound requests for authenticated gateways.
+//!
+//! Some deployments put SearXNG behind a gateway that only forwards requests carrying a
+//! valid signature. A [`RequestSigner`] set with
+//! [`SearXNGClient::with_request_signer`](crate::SearXNGClient::with_request_signer) is
+//! called on every request just before it is sent, after all other headers were set, and
+//! again for every retry and followed redirect that stays on the instance's origin. [`HmacSigner`] is a reference
+//! implementation signing with HMAC-SHA256.
+
+use std::fmt;
+
+use chrono::Utc;
+use reqwest::header::HeaderValue;
+use reqwest::{Method, Request, Url};
+use sha2::{Digest, Sha256};
+
+/// The header carrying the signature written by [`HmacSigner`].
+pub const SIGNATURE_HEADER: &str = "X-Signature";
+/// The header carrying the Unix timestamp the [`HmacSigner`] signature covers.
+pub const TIMESTAMP_HEADER: &str = "X-Signature-Timestamp";
+/// The header carrying the key identifier of an [`HmacSigner`], if it has one.
+pub const KEY_ID_HEADER: &str = "X-Signature-Key-Id";
+
+/// Signs requests before they are sent, see the [module documentation](self).
+pub trait RequestSigner: fmt::Debug + Send + Sync {
+    /// Signs `request`, typically by adding headers derived from its method, URL and
+    /// body. Implementations should not block for long, as this is called inline before
+    /// each request.
+    fn sign(&self, request: &mut Request);
+}
+
+/// Signs requests with HMAC-SHA256.
+///
+/// The signature covers the method, the path and query of the URL, a Unix timestamp and
+/// the SHA-256 digest of the body, one per line:
+///
+/// ```text
+/// POST
+/// /search?format=json
+/// 1700000000
+/// e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855
+/// ```
+///
+/// It is sent hex-encoded in the [`SIGNATURE_HEADER`], with the timestamp in the
+/// [`TIMESTAMP_HEADER`] so that the gateway can recompute it and reject stale requests.
+///
+/// # Examples
+///
+/// ```
+/// use searxng_client::{SearXNGClient, ResponseFormat};
+/// use searxng_client::sign::HmacSigner;
+/// let signer = HmacSigner::new("secret").with_key_id("client-1");
+/// let signature = signer.signature(
+///     &reqwest::Method::GET,
+///     &"https://searx.example.org/config".parse()?,
+///     b"",
+///     1_700_000_000,
+/// );
+/// assert_eq!(signature.len(), 64);
+/// let client = SearXNGClient::new("https://searx.example.org", ResponseFormat::Json)
+///     .with_request_signer(signer);
+/// # Ok::<(), url::ParseError>(())
+/// ```
+#[derive(Clone)]
+pub struct HmacSigner {
+    key: Vec<u8>,
+    key_id: Option<String>,
+}
+
+impl fmt::Debug for HmacSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HmacSigner")
+            .field("key_id", &self.key_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl HmacSigner {
+    /// Creates a signer with the shared secret `key`.
+    pub fn new(key: impl Into<Vec<u8>>) -> Self {
+        HmacSigner {
+            key: key.into(),
+            key_id: None,
+        }
+    }
+
+    /// Sends `key_id` in the [`KEY_ID_HEADER`], for gateways that rotate between keys.
+    pub fn with_key_id(mut self, key_id: impl Into<String>) -> Self {
+        self.key_id = Some(key_id.into());
+        self
+    }
+
+    /// Returns the hex-encoded signature of a request sent at `timestamp`, in seconds
+    /// since the Unix epoch.
+    pub fn signature(&self, method: &Method, url: &Url, body: &[u8], timestamp: i64) -> String {
+        let path = match url.query() {
+            Some(query) => format!("{}?{query}", url.path()),
+            None => url.path().to_string(),
+        };
+        let message = format!(
+            "{method}\n{path}\n{timestamp}\n{}",
+            hex(&Sha256::digest(body))
+        );
+        hex(&hmac_sha256(&self.key, message.as_bytes()))
+    }
+}
+
+impl RequestSigner for HmacSigner {
+    fn sign(&self, request: &mut Request) {
+        let timestamp = Utc::now().timestamp();
+        let body = request
+            .body()
+            .and_then(|b| b.as_bytes())
+            .unwrap_or_default();
+        let signature = self.signature(request.method(), request.url(), body, timestamp);
+        let headers = request.headers_mut();
+        headers.insert(TIMESTAMP_HEADER, HeaderValue::from(timestamp));
+        headers.insert(
+            SIGNATURE_HEADER,
+            HeaderValue::from_str(&signature).expect("hex is a valid header value"),
+        );
+        if let Some(key_id) = &self.key_id
+            && let Ok(value) = HeaderValue::from_str(key_id)
+        {
+            headers.insert(KEY_ID_HEADER, value);
+        }
+    }
+}
+
+/// HMAC as specified in RFC 2104, over SHA-256.
+fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    const BLOCK_LEN: usize = 64;
+    let mut block = [0u8; BLOCK_LEN];
+    if key.len() > BLOCK_LEN {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let pad = |byte: u8| block.map(|b| b ^ byte);
+
+    let mut inner = Sha256::new();
+    inner.update(pad(0x36));
+    inner.update(message);
+    let mut outer = Sha256::new();
+    outer.update(pad(0x5c));
+    outer.update(inner.finalize());
+    outer.finalize().into()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hmac_signer() {
+        // RFC 4231, test cases 2 and 6.
+        assert_eq!(
+            hex(&hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        assert_eq!(
+            hex(&hmac_sha256(
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First"
+            )),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+
+        let signer = HmacSigner::new("secret").with_key_id("k1");
+        let url: Url = "https://searx.example.org/search?format=json"
+            .parse()
+            .unwrap();
+        let mut request = Request::new(Method::POST, url.clone());
+        *request.body_mut() = Some("q=rust".into());
+        signer.sign(&mut request);
+        let headers = request.headers();
+        let timestamp: i64 = headers[TIMESTAMP_HEADER].to_str().unwrap().parse().unwrap();
+        assert_eq!(
+            headers[SIGNATURE_HEADER],
+            signer.signature(&Method::POST, &url, b"q=rust", timestamp)
+        );
+        assert_ne!(
+            headers[SIGNATURE_HEADER],
+            signer.signature(&Method::POST, &url, b"q=cargo", timestamp)
+        );
+        assert_eq!(headers[KEY_ID_HEADER], "k1");
+    }
+}
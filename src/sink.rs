@@ -0,0 +1,176 @@
+//! Streaming collected results out of memory during deep crawls.
+//!
+//! [`SearchBuilder::send_plan_to_sink`](crate::client::SearchBuilder::send_plan_to_sink)
+//! hands every page to a [`ResultSink`] as soon as it arrives, and keeps only a hash of
+//! each canonical URL for deduplication. Memory use thus stays bounded however many
+//! results are collected.
+
+use std::collections::HashSet;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::{self, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::bloom::BloomFilter;
+use crate::dedup::canonical_url;
+use crate::provenance::SourcedResult;
+use crate::response::SearchResult;
+
+/// A destination for the pages of a crawl.
+pub trait ResultSink {
+    /// Writes the deduplicated results of one page.
+    fn write_page(&mut self, results: &[SourcedResult]) -> io::Result<()>;
+}
+
+impl<S: ResultSink + ?Sized> ResultSink for &mut S {
+    fn write_page(&mut self, results: &[SourcedResult]) -> io::Result<()> {
+        (**self).write_page(results)
+    }
+}
+
+/// Writes results as newline-delimited JSON, one [`SourcedResult`] per line.
+///
+/// The writer is flushed after every page, so a crawl that is interrupted keeps every
+/// page written so far.
+#[derive(Debug)]
+pub struct NdjsonSink<W> {
+    writer: W,
+}
+
+impl<W: Write> NdjsonSink<W> {
+    pub fn new(writer: W) -> Self {
+        NdjsonSink { writer }
+    }
+
+    /// Consumes the sink, returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> ResultSink for NdjsonSink<W> {
+    fn write_page(&mut self, results: &[SourcedResult]) -> io::Result<()> {
+        for result in results {
+            serde_json::to_writer(&mut self.writer, result)?;
+            self.writer.write_all(b"\n")?;
+        }
+        self.writer.flush()
+    }
+}
+
+/// The outcome of a crawl streamed to a [`ResultSink`].
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrawlSummary {
+    /// The number of results written to the sink.
+    pub written: usize,
+    /// The number of results dropped as duplicates of a result already written.
+    pub duplicates: usize,
+    /// The number of pages fetched.
+    pub pages: u32,
+}
+
+/// How a crawl remembers the results already written, to drop duplicates.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub enum SeenSet {
+    /// Remembers a hash of every canonical URL, eight bytes per result.
+    #[default]
+    Exact,
+    /// Remembers canonical URLs in a [`BloomFilter`] of fixed size, sized for
+    /// `expected_items` results.
+    ///
+    /// About `false_positive_rate` of the distinct results are dropped as duplicates,
+    /// in exchange for memory that does not grow with the crawl.
+    Bloom {
+        expected_items: usize,
+        false_positive_rate: f64,
+    },
+}
+
+/// Remembers the results already written by their hashed canonical URL.
+///
+/// Hash collisions may rarely drop a distinct result; this is the price of storing eight
+/// bytes per result instead of its URL.
+#[derive(Debug, Clone)]
+pub(crate) enum SeenUrls {
+    Exact(HashSet<u64>),
+    Bloom(BloomFilter),
+}
+
+impl Default for SeenUrls {
+    fn default() -> Self {
+        SeenUrls::new(SeenSet::default())
+    }
+}
+
+impl SeenUrls {
+    pub(crate) fn new(set: SeenSet) -> Self {
+        match set {
+            SeenSet::Exact => SeenUrls::Exact(HashSet::new()),
+            SeenSet::Bloom {
+                expected_items,
+                false_positive_rate,
+            } => SeenUrls::Bloom(BloomFilter::new(expected_items, false_positive_rate)),
+        }
+    }
+
+    /// Returns `true` if `result` was not seen before. Results without a URL are never
+    /// considered duplicates.
+    pub(crate) fn insert(&mut self, result: &SearchResult) -> bool {
+        let Some(url) = result.url() else {
+            return true;
+        };
+        let mut hasher = DefaultHasher::new();
+        canonical_url(url).hash(&mut hasher);
+        let hash = hasher.finish();
+        match self {
+            SeenUrls::Exact(hashes) => hashes.insert(hash),
+            SeenUrls::Bloom(filter) => filter.insert_hash(hash),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provenance::Provenance;
+    use crate::test::sample_result;
+    use chrono::Utc;
+
+    #[test]
+    fn test_ndjson_sink() {
+        let provenance = Provenance {
+            instance: "https://searx.be/".into(),
+            pageno: 1,
+            query: "rust".into(),
+            fetched_at: Utc::now(),
+        };
+        let mut seen = SeenUrls::default();
+        let page: Vec<SourcedResult> = [
+            "https://www.rust-lang.org/",
+            "https://rust-lang.org",
+            "https://docs.rs",
+        ]
+        .into_iter()
+        .map(|url| sample_result(url, "r", ""))
+        .filter(|result| seen.insert(result))
+        .map(|result| SourcedResult {
+            result,
+            provenance: provenance.clone(),
+        })
+        .collect();
+
+        let mut sink = NdjsonSink::new(Vec::new());
+        sink.write_page(&page).unwrap();
+        let written = String::from_utf8(sink.into_inner()).unwrap();
+        assert_eq!(written.lines().count(), 2);
+        let first: SourcedResult = serde_json::from_str(written.lines().next().unwrap()).unwrap();
+        assert_eq!(first.provenance.query, "rust");
+
+        let mut bloom = SeenUrls::new(SeenSet::Bloom {
+            expected_items: 100,
+            false_positive_rate: 0.01,
+        });
+        assert!(bloom.insert(&sample_result("https://docs.rs", "r", "")));
+        assert!(!bloom.insert(&sample_result("https://docs.rs/", "r", "")));
+    }
+}
@@ -0,0 +1,368 @@
+//! A searchable archive of collected results in SQLite, behind the `sqlite` feature.
+//!
+//! A [`SqliteStore`] records results together with their [`Provenance`], grouped into
+//! sessions, for example one per crawl. It is a [`ResultSink`], so crawls can be streamed
+//! straight into it, and it answers simple queries over everything stored so far: by
+//! domain, by date range and full-text over titles and snippets.
+//!
+//! The database has three tables:
+//!
+//! - `sessions`: `id`, `label` and `started_at`.
+//! - `results`: the result as JSON, its URL, domain, title and snippet, the provenance
+//!   columns `instance`, `pageno`, `query` and `fetched_at`, and the `session_id`.
+//! - `results_fts`: an FTS5 index over the titles and snippets of `results`.
+//!
+//! Dates are stored as RFC 3339 strings in UTC, which sort chronologically.
+
+use std::io;
+use std::path::Path;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use rusqlite::types::Type;
+use rusqlite::{Connection, OptionalExtension, Row, params};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::provenance::{Provenance, SourcedResult};
+use crate::sink::ResultSink;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS sessions (
+    id INTEGER PRIMARY KEY,
+    label TEXT NOT NULL,
+    started_at TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS results (
+    id INTEGER PRIMARY KEY,
+    session_id INTEGER REFERENCES sessions(id),
+    url TEXT,
+    domain TEXT,
+    title TEXT NOT NULL,
+    content TEXT NOT NULL,
+    result TEXT NOT NULL,
+    instance TEXT NOT NULL,
+    pageno INTEGER NOT NULL,
+    query TEXT NOT NULL,
+    fetched_at TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS results_domain ON results(domain);
+CREATE INDEX IF NOT EXISTS results_fetched_at ON results(fetched_at);
+CREATE VIRTUAL TABLE IF NOT EXISTS results_fts
+    USING fts5(title, content, content='results', content_rowid='id');
+CREATE TRIGGER IF NOT EXISTS results_fts_insert AFTER INSERT ON results BEGIN
+    INSERT INTO results_fts(rowid, title, content) VALUES (new.id, new.title, new.content);
+END;
+CREATE TRIGGER IF NOT EXISTS results_fts_delete AFTER DELETE ON results BEGIN
+    INSERT INTO results_fts(results_fts, rowid, title, content)
+        VALUES ('delete', old.id, old.title, old.content);
+END;
+";
+
+const SELECT: &str = "SELECT results.result, results.instance, results.pageno, \
+    results.query, results.fetched_at FROM results";
+
+/// A group of stored results, see [`SqliteStore::begin_session`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Session {
+    pub id: i64,
+    pub label: String,
+    pub started_at: DateTime<Utc>,
+}
+
+/// Stores results in a SQLite database, see the [module documentation](self).
+///
+/// # Examples
+///
+/// ```no_run
+/// # use searxng_client::{SearXNGClient, ResponseFormat, SearchPlan};
+/// use searxng_client::sqlite::SqliteStore;
+/// # tokio_test::block_on(async {
+/// # let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json);
+/// let mut store = SqliteStore::open("searches.db")?;
+/// store.begin_session("rust crawl")?;
+/// client
+///     .search("rust")
+///     .send_plan_to_sink(&mut SearchPlan::for_target(500), &mut store)
+///     .await?;
+/// for sourced in store.search_snippets("borrow checker")? {
+///     println!("{:?} from page {}", sourced.result, sourced.provenance.pageno);
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// # });
+/// ```
+#[derive(Debug)]
+pub struct SqliteStore {
+    conn: Connection,
+    session: Option<i64>,
+}
+
+impl SqliteStore {
+    /// Opens or creates the database at `path`.
+    pub fn open(path: impl AsRef<Path>) -> rusqlite::Result<Self> {
+        SqliteStore::from_connection(Connection::open(path)?)
+    }
+
+    /// Creates a database in memory, lost when the store is dropped.
+    pub fn open_in_memory() -> rusqlite::Result<Self> {
+        SqliteStore::from_connection(Connection::open_in_memory()?)
+    }
+
+    /// Uses an open connection, creating the tables if needed.
+    pub fn from_connection(conn: Connection) -> rusqlite::Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(SqliteStore {
+            conn,
+            session: None,
+        })
+    }
+
+    /// The underlying connection, for queries beyond those of the store.
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Starts a new session; results inserted from now on belong to it.
+    ///
+    /// Results inserted before any session was started belong to none.
+    pub fn begin_session(&mut self, label: &str) -> rusqlite::Result<i64> {
+        self.conn.execute(
+            "INSERT INTO sessions (label, started_at) VALUES (?1, ?2)",
+            params![label, timestamp(Utc::now())],
+        )?;
+        let id = self.conn.last_insert_rowid();
+        self.session = Some(id);
+        Ok(id)
+    }
+
+    /// The current session, if any.
+    pub fn current_session(&self) -> Option<i64> {
+        self.session
+    }
+
+    /// Every session, oldest first.
+    pub fn sessions(&self) -> rusqlite::Result<Vec<Session>> {
+        let mut statement = self
+            .conn
+            .prepare("SELECT id, label, started_at FROM sessions ORDER BY id")?;
+        statement
+            .query_map([], |row| {
+                Ok(Session {
+                    id: row.get(0)?,
+                    label: row.get(1)?,
+                    started_at: parse_timestamp(row, 2)?,
+                })
+            })?
+            .collect()
+    }
+
+    /// Looks up a session by id.
+    pub fn session(&self, id: i64) -> rusqlite::Result<Option<Session>> {
+        self.conn
+            .query_row(
+                "SELECT id, label, started_at FROM sessions WHERE id = ?1",
+                [id],
+                |row| {
+                    Ok(Session {
+                        id: row.get(0)?,
+                        label: row.get(1)?,
+                        started_at: parse_timestamp(row, 2)?,
+                    })
+                },
+            )
+            .optional()
+    }
+
+    /// Inserts results into the current session, in one transaction.
+    pub fn insert(&mut self, results: &[SourcedResult]) -> rusqlite::Result<()> {
+        let tx = self.conn.transaction()?;
+        {
+            let mut statement = tx.prepare(
+                "INSERT INTO results (session_id, url, domain, title, content, result, \
+                 instance, pageno, query, fetched_at) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            )?;
+            for sourced in results {
+                let result = &sourced.result;
+                let url = result.url();
+                let json = serde_json::to_string(result)
+                    .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+                let provenance = &sourced.provenance;
+                statement.execute(params![
+                    self.session,
+                    url,
+                    url.and_then(domain_of),
+                    result.title(),
+                    result.content(),
+                    json,
+                    provenance.instance,
+                    provenance.pageno,
+                    provenance.query,
+                    timestamp(provenance.fetched_at),
+                ])?;
+            }
+        }
+        tx.commit()
+    }
+
+    /// The number of stored results.
+    pub fn len(&self) -> rusqlite::Result<i64> {
+        self.conn
+            .query_row("SELECT COUNT(*) FROM results", [], |row| row.get(0))
+    }
+
+    /// Returns `true` if no result is stored.
+    pub fn is_empty(&self) -> rusqlite::Result<bool> {
+        self.len().map(|len| len == 0)
+    }
+
+    /// The results of a session, in the order they were inserted.
+    pub fn session_results(&self, session: i64) -> rusqlite::Result<Vec<SourcedResult>> {
+        self.select("WHERE session_id = ?1 ORDER BY id", params![session])
+    }
+
+    /// The results from `domain` or one of its subdomains, oldest first.
+    ///
+    /// A leading `www.` is ignored, both in `domain` and in the stored URLs.
+    pub fn by_domain(&self, domain: &str) -> rusqlite::Result<Vec<SourcedResult>> {
+        let domain = domain.trim_start_matches("www.").to_ascii_lowercase();
+        self.select(
+            "WHERE domain = ?1 OR domain LIKE '%.' || ?1 ORDER BY fetched_at, id",
+            params![domain],
+        )
+    }
+
+    /// The results fetched from `from` inclusive to `to` exclusive, oldest first.
+    pub fn between(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> rusqlite::Result<Vec<SourcedResult>> {
+        self.select(
+            "WHERE fetched_at >= ?1 AND fetched_at < ?2 ORDER BY fetched_at, id",
+            params![timestamp(from), timestamp(to)],
+        )
+    }
+
+    /// Searches titles and snippets, best matches first.
+    ///
+    /// `query` uses the [FTS5 query syntax](https://www.sqlite.org/fts5.html#full_text_query_syntax):
+    /// words match separately, `"quoted phrases"` together, and `prefix*` any word
+    /// starting with `prefix`.
+    pub fn search_snippets(&self, query: &str) -> rusqlite::Result<Vec<SourcedResult>> {
+        let mut statement = self.conn.prepare(&format!(
+            "{SELECT} JOIN results_fts ON results_fts.rowid = results.id \
+             WHERE results_fts MATCH ?1 ORDER BY results_fts.rank"
+        ))?;
+        statement.query_map([query], sourced_from_row)?.collect()
+    }
+
+    fn select(
+        &self,
+        filter: &str,
+        params: impl rusqlite::Params,
+    ) -> rusqlite::Result<Vec<SourcedResult>> {
+        let mut statement = self.conn.prepare(&format!("{SELECT} {filter}"))?;
+        statement.query_map(params, sourced_from_row)?.collect()
+    }
+}
+
+/// Inserts every page into the current session.
+impl ResultSink for SqliteStore {
+    fn write_page(&mut self, results: &[SourcedResult]) -> io::Result<()> {
+        self.insert(results).map_err(io::Error::other)
+    }
+}
+
+fn sourced_from_row(row: &Row<'_>) -> rusqlite::Result<SourcedResult> {
+    let json: String = row.get(0)?;
+    let result = serde_json::from_str(&json)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, Type::Text, Box::new(e)))?;
+    Ok(SourcedResult {
+        result,
+        provenance: Provenance {
+            instance: row.get(1)?,
+            pageno: row.get(2)?,
+            query: row.get(3)?,
+            fetched_at: parse_timestamp(row, 4)?,
+        },
+    })
+}
+
+/// The host of `url` in lowercase, without a leading `www.`.
+fn domain_of(url: &str) -> Option<String> {
+    let url = Url::parse(url.trim()).ok()?;
+    let host = url.host_str()?;
+    Some(host.trim_start_matches("www.").to_ascii_lowercase())
+}
+
+fn timestamp(date: DateTime<Utc>) -> String {
+    date.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+fn parse_timestamp(row: &Row<'_>, i: usize) -> rusqlite::Result<DateTime<Utc>> {
+    let raw: String = row.get(i)?;
+    DateTime::parse_from_rfc3339(&raw)
+        .map(|date| date.to_utc())
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(i, Type::Text, Box::new(e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::sample_result;
+    use chrono::TimeZone;
+
+    fn sourced(url: &str, content: &str, day: u32) -> SourcedResult {
+        SourcedResult {
+            result: sample_result(url, "r", content),
+            provenance: Provenance {
+                instance: "https://searx.be/".into(),
+                pageno: 1,
+                query: "rust".into(),
+                fetched_at: Utc.with_ymd_and_hms(2024, 5, day, 12, 0, 0).unwrap(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_sqlite_store_queries() {
+        let mut store = SqliteStore::open_in_memory().unwrap();
+        let session = store.begin_session("rust crawl").unwrap();
+        store
+            .write_page(&[
+                sourced(
+                    "https://www.rust-lang.org/",
+                    "A language empowering everyone",
+                    1,
+                ),
+                sourced("https://doc.rust-lang.org/book/", "The borrow checker", 2),
+                sourced("https://docs.rs/", "Documentation for crates", 3),
+            ])
+            .unwrap();
+        store.begin_session("other").unwrap();
+        store
+            .insert(&[sourced("https://rust-lang.org.example/", "", 4)])
+            .unwrap();
+
+        assert_eq!(store.len().unwrap(), 4);
+        assert_eq!(store.sessions().unwrap()[0].label, "rust crawl");
+        assert_eq!(store.session_results(session).unwrap().len(), 3);
+        assert_eq!(store.by_domain("www.rust-lang.org").unwrap().len(), 2);
+
+        let from = Utc.with_ymd_and_hms(2024, 5, 2, 0, 0, 0).unwrap();
+        let to = Utc.with_ymd_and_hms(2024, 5, 4, 0, 0, 0).unwrap();
+        let between = store.between(from, to).unwrap();
+        assert_eq!(between.len(), 2);
+        assert_eq!(
+            between[0].provenance.fetched_at.timestamp(),
+            from.timestamp() + 43200
+        );
+
+        let found = store.search_snippets("borrow*").unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(
+            found[0].result.url(),
+            Some("https://doc.rust-lang.org/book/")
+        );
+    }
+}
@@ -0,0 +1,159 @@
+//! Structured concurrency for the requests the crate runs in parallel.
+//!
+//! Fan-out operations, such as an [`Aggregator`](crate::aggregate::Aggregator) search or a
+//! [`ThumbnailPrefetcher`](crate::thumbnail::ThumbnailPrefetcher) download, spawn one
+//! task per subrequest on a [`JoinSet`]. This gives them three guarantees:
+//!
+//! - Cancellation propagates: dropping the operation's future aborts every subtask still
+//!   running, so no request outlives the caller that wanted it.
+//! - Panics propagate: a subtask that panics aborts its siblings and resumes the panic in
+//!   the caller, instead of being silently lost.
+//! - The operation reports what it spawned in a [`TaskReport`].
+//!
+//! Paginated searches are not spawned: their pages are polled by the caller's own task,
+//! which gives the same guarantees for free. On `wasm32`, without a Tokio runtime, the
+//! subtasks of fan-out operations are polled that way too.
+
+use std::future::Future;
+#[cfg(not(target_arch = "wasm32"))]
+use std::panic;
+
+#[cfg(target_arch = "wasm32")]
+use futures::StreamExt;
+#[cfg(target_arch = "wasm32")]
+use futures::future::LocalBoxFuture;
+#[cfg(target_arch = "wasm32")]
+use futures::stream::FuturesUnordered;
+use serde::{Deserialize, Serialize};
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::task::JoinSet;
+
+use crate::rt::MaybeSend;
+
+/// What happened to the subtasks of one operation.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TaskReport {
+    /// The number of subtasks spawned.
+    pub spawned: usize,
+    /// The number of subtasks that ran to completion, successfully or not.
+    pub completed: usize,
+    /// The number of completed subtasks whose request failed.
+    pub failed: usize,
+}
+
+impl TaskReport {
+    /// The number of subtasks that completed successfully.
+    pub fn succeeded(&self) -> usize {
+        self.completed - self.failed
+    }
+}
+
+/// A [`JoinSet`] that keeps a [`TaskReport`] and propagates panics.
+pub(crate) struct TaskSet<T> {
+    #[cfg(not(target_arch = "wasm32"))]
+    set: JoinSet<T>,
+    #[cfg(target_arch = "wasm32")]
+    set: FuturesUnordered<LocalBoxFuture<'static, T>>,
+    report: TaskReport,
+    is_failure: fn(&T) -> bool,
+}
+
+impl<T: MaybeSend + 'static> TaskSet<T> {
+    /// Creates a set counting the outputs for which `is_failure` is `true` as failed.
+    pub(crate) fn new(is_failure: fn(&T) -> bool) -> Self {
+        TaskSet {
+            set: Default::default(),
+            report: TaskReport::default(),
+            is_failure,
+        }
+    }
+
+    pub(crate) fn spawn(&mut self, task: impl Future<Output = T> + MaybeSend + 'static) {
+        #[cfg(not(target_arch = "wasm32"))]
+        self.set.spawn(task);
+        #[cfg(target_arch = "wasm32")]
+        self.set.push(Box::pin(task));
+        self.report.spawned += 1;
+    }
+
+    /// The number of subtasks still running.
+    pub(crate) fn len(&self) -> usize {
+        self.set.len()
+    }
+
+    /// Waits for the next subtask to complete, in completion order.
+    ///
+    /// # Panics
+    ///
+    /// Resumes the panic of a subtask that panicked, after aborting the others.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) async fn join_next(&mut self) -> Option<T> {
+        loop {
+            match self.set.join_next().await? {
+                Ok(output) => {
+                    self.report.completed += 1;
+                    self.report.failed += usize::from((self.is_failure)(&output));
+                    return Some(output);
+                }
+                Err(e) if e.is_panic() => {
+                    self.set.abort_all();
+                    panic::resume_unwind(e.into_panic());
+                }
+                // Only tasks aborted by the set itself are cancelled, and the set is only
+                // aborted while unwinding.
+                Err(_) => continue,
+            }
+        }
+    }
+
+    /// Waits for the next subtask to complete, in completion order. Panics abort on
+    /// `wasm32`.
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) async fn join_next(&mut self) -> Option<T> {
+        let output = self.set.next().await?;
+        self.report.completed += 1;
+        self.report.failed += usize::from((self.is_failure)(&output));
+        Some(output)
+    }
+
+    /// Waits for every remaining subtask, returning their outputs in completion order.
+    pub(crate) async fn join_all(&mut self) -> Vec<T> {
+        let mut outputs = Vec::with_capacity(self.len());
+        while let Some(output) = self.join_next().await {
+            outputs.push(output);
+        }
+        outputs
+    }
+
+    pub(crate) fn report(&self) -> TaskReport {
+        self.report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_task_set_reports_and_propagates_panics() {
+        let mut tasks = TaskSet::new(|output: &Result<u32, ()>| output.is_err());
+        tasks.spawn(async { Ok(1) });
+        tasks.spawn(async { Err(()) });
+        let mut outputs = tasks.join_all().await;
+        outputs.sort();
+        assert_eq!(outputs, [Ok(1), Err(())]);
+        let report = tasks.report();
+        assert_eq!((report.spawned, report.completed, report.failed), (2, 2, 1));
+        assert_eq!(report.succeeded(), 1);
+
+        let panicking = tokio::spawn(async {
+            let mut tasks = TaskSet::new(|_: &()| false);
+            tasks.spawn(async { panic!("subtask failed") });
+            tasks.spawn(std::future::pending());
+            tasks.join_all().await;
+        });
+        let error = panicking.await.unwrap_err();
+        let message = error.into_panic();
+        assert_eq!(message.downcast_ref::<&str>(), Some(&"subtask failed"));
+    }
+}
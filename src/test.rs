@@ -1,28 +1,91 @@
-use async_trait::async_trait;
-use reqwest::Response;
-use serde::de::DeserializeOwned;
+/// Builds a minimal legacy-shaped [`SearchResult`] for unit tests.
+pub fn sample_result(url: &str, title: &str, content: &str) -> crate::response::SearchResult {
+    serde_json::from_value(serde_json::json!({
+        "url": url,
+        "template": "default.html",
+        "engine": "duckduckgo",
+        "parsed_url": null,
+        "title": title,
+        "content": content,
+        "img_src": "",
+        "thumbnail": "",
+        "priority": "",
+        "engines": ["duckduckgo"],
+        "positions": [1],
+        "score": 1.0,
+        "category": "general",
+        "publishedDate": null,
+        "pubdate": null
+    }))
+    .unwrap()
+}
 
-#[async_trait]
-pub trait SmartJsonExt {
-    async fn json_test<T: DeserializeOwned>(self) -> anyhow::Result<T>;
+/// Builds the JSON of a [`SearchResponse`](crate::response::SearchResponse) to `query`
+/// without results, for unit tests.
+pub fn empty_response_json(query: &str) -> serde_json::Value {
+    serde_json::json!({
+        "query": query,
+        "number_of_results": 0,
+        "results": [],
+        "answers": [],
+        "corrections": [],
+        "infoboxes": [],
+        "suggestions": [],
+        "unresponsive_engines": []
+    })
 }
 
-#[async_trait]
-impl SmartJsonExt for Response {
-    async fn json_test<T: DeserializeOwned>(self) -> anyhow::Result<T> {
-        let full_body = self.text().await?;
+/// Answers one connection per response in turn, returning the requests received.
+pub async fn serve(responses: Vec<String>) -> (String, tokio::task::JoinHandle<Vec<String>>) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let root = format!("http://{}/", listener.local_addr().unwrap());
+    let server = tokio::spawn(async move {
+        let mut requests = Vec::new();
+        for response in responses {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = vec![0; 4096];
+            let n = socket.read(&mut request).await.unwrap();
+            requests.push(String::from_utf8_lossy(&request[..n]).into_owned());
+            socket.write_all(response.as_bytes()).await.unwrap();
+        }
+        requests
+    });
+    (root, server)
+}
 
-        match serde_json::from_str::<T>(&full_body) {
-            Ok(val) => Ok(val),
-            Err(e) => {
-                println!("\n--- [DEBUG] JSON DECODE ERROR ---");
-                println!("Reason: {}", e);
-                println!("At: Line {}, Column {}", e.line(), e.column());
-                println!("Raw Body:\n{}", full_body);
-                println!("---------------------------------\n");
+/// Answers `connections` connections concurrently. `respond` maps each request to a
+/// delay and the response sent once it elapsed.
+pub async fn serve_with(
+    connections: usize,
+    respond: impl Fn(&str) -> (std::time::Duration, String) + Send + Sync + 'static,
+) -> String {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
-                Err(anyhow::anyhow!(e).context("JSON decoding failed in debug mode"))
-            }
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let root = format!("http://{}/", listener.local_addr().unwrap());
+    let respond = std::sync::Arc::new(respond);
+    tokio::spawn(async move {
+        for _ in 0..connections {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let respond = respond.clone();
+            tokio::spawn(async move {
+                let mut request = vec![0; 4096];
+                let n = socket.read(&mut request).await.unwrap();
+                let (delay, response) = respond(&String::from_utf8_lossy(&request[..n]));
+                tokio::time::sleep(delay).await;
+                socket.write_all(response.as_bytes()).await.unwrap();
+            });
         }
-    }
+    });
+    root
+}
+
+/// Formats an HTTP/1.1 response with `status`, extra `headers` lines and `body`.
+pub fn http_response(status: &str, headers: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {status}\r\n{headers}content-length: {}\r\nconnection: close\r\n\r\n{body}",
+        body.len()
+    )
 }
@@ -0,0 +1,148 @@
+//! Text helpers for displaying result snippets.
+//!
+//! Snippets frequently contain CJK text, emoji and combining characters, so slicing
+//! them by byte offset either panics or produces garbled output. The helpers in this
+//! module operate on extended grapheme clusters instead.
+
+use std::borrow::Cow;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The ellipsis appended by [`truncate_snippet`].
+pub const ELLIPSIS: &str = "…";
+
+/// Truncates `text` to at most `max_len` graphemes, appending [`ELLIPSIS`] when it was cut.
+///
+/// The cut is moved back to the last word boundary when one exists in the second half
+/// of the kept text, so Latin text is not cut mid-word; text without spaces (such as
+/// Chinese or Japanese) is cut on a grapheme boundary.
+///
+/// # Examples
+///
+/// ```
+/// use searxng_client::text::truncate_snippet;
+/// assert_eq!(truncate_snippet("The Rust Programming Language", 12), "The Rust…");
+/// assert_eq!(truncate_snippet("锈语言编程指南", 4), "锈语言…");
+/// assert_eq!(truncate_snippet("short", 12), "short");
+/// ```
+pub fn truncate_snippet(text: &str, max_len: usize) -> Cow<'_, str> {
+    truncate_snippet_with(text, max_len, ELLIPSIS)
+}
+
+/// Like [`truncate_snippet`], but with a custom ellipsis.
+///
+/// The ellipsis counts towards `max_len`; when it is longer than `max_len`, only its
+/// first `max_len` graphemes are returned.
+pub fn truncate_snippet_with<'a>(text: &'a str, max_len: usize, ellipsis: &str) -> Cow<'a, str> {
+    let text = text.trim();
+    if text.graphemes(true).nth(max_len).is_none() {
+        return Cow::Borrowed(text);
+    }
+
+    let Some(budget) = max_len.checked_sub(ellipsis.graphemes(true).count()) else {
+        return Cow::Owned(ellipsis.graphemes(true).take(max_len).collect());
+    };
+    let end = text
+        .grapheme_indices(true)
+        .nth(budget)
+        .map_or(text.len(), |(i, _)| i);
+    let kept = &text[..end];
+
+    let kept = match kept.rfind(char::is_whitespace) {
+        Some(i) if kept[..i].graphemes(true).count() * 2 >= budget => &kept[..i],
+        _ => kept,
+    };
+    let kept = kept.trim_end_matches(|c: char| c.is_whitespace() || c.is_ascii_punctuation());
+
+    Cow::Owned(format!("{kept}{ellipsis}"))
+}
+
+/// Turns a snippet into plain text: removes HTML tags, decodes the common character
+/// entities and collapses runs of whitespace into single spaces.
+///
+/// Some engines return snippets with markup or entities left in, which waste space and
+/// confuse readers that expect plain text, such as language models.
+///
+/// # Examples
+///
+/// ```
+/// use searxng_client::text::clean_snippet;
+/// assert_eq!(
+///     clean_snippet("  <b>Rust</b> &amp; Cargo:\n\n fast &#8212; safe "),
+///     "Rust & Cargo: fast — safe"
+/// );
+/// assert_eq!(clean_snippet("1 < 2 && 3 > 2"), "1 < 2 && 3 > 2");
+/// ```
+pub fn clean_snippet(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(i) = rest.find(['<', '&']) {
+        plain.push_str(&rest[..i]);
+        rest = &rest[i..];
+        if rest.starts_with('<') {
+            let is_tag =
+                rest[1..].starts_with(|c: char| c.is_ascii_alphabetic() || c == '/' || c == '!');
+            match rest.find('>').filter(|_| is_tag) {
+                Some(end) => {
+                    // Tags separate words, as in `a<br>b`.
+                    plain.push(' ');
+                    rest = &rest[end + 1..];
+                }
+                None => {
+                    plain.push('<');
+                    rest = &rest[1..];
+                }
+            }
+        } else {
+            let entity = rest[1..]
+                .find(';')
+                .filter(|&end| end <= 8)
+                .and_then(|end| Some((decode_entity(&rest[1..=end])?, end + 2)));
+            match entity {
+                Some((c, len)) => {
+                    plain.push(c);
+                    rest = &rest[len..];
+                }
+                None => {
+                    plain.push('&');
+                    rest = &rest[1..];
+                }
+            }
+        }
+    }
+    plain.push_str(rest);
+    plain.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Decodes the name of a character entity, without `&` and `;`.
+fn decode_entity(name: &str) -> Option<char> {
+    match name {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some(' '),
+        _ => {
+            let code = name.strip_prefix('#')?;
+            let code = match code.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => code.parse().ok()?,
+            };
+            char::from_u32(code)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_snippet_short_max_len() {
+        assert_eq!(truncate_snippet_with("The Rust Language", 2, "..."), "..");
+        assert_eq!(truncate_snippet_with("The Rust Language", 0, "..."), "");
+        assert_eq!(truncate_snippet_with("The Rust Language", 3, "..."), "...");
+        assert_eq!(truncate_snippet("The Rust Language", 1), ELLIPSIS);
+    }
+}
@@ -0,0 +1,335 @@
+//! Bulk thumbnail downloads for image galleries.
+//!
+//! [`ThumbnailPrefetcher`] downloads the thumbnails of all results of a response
+//! concurrently, so a gallery page can be rendered from a single call. Downloads are
+//! limited in number and size, since thumbnails are served by arbitrary third parties.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
+
+use reqwest::Client;
+
+use crate::client::{GLOBAL_CLIENT, USER_AGENT};
+use crate::response::{SearchResponse, SearchResult};
+use crate::task::{TaskReport, TaskSet};
+
+/// The default maximum size of a downloaded thumbnail.
+pub const DEFAULT_MAX_THUMBNAIL_BYTES: usize = 1024 * 1024;
+
+/// The default number of thumbnails downloaded at the same time.
+pub const DEFAULT_PREFETCH_CONCURRENCY: usize = 8;
+
+/// The default timeout of a single thumbnail download.
+pub const DEFAULT_THUMBNAIL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A downloaded thumbnail.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Thumbnail {
+    /// The URL the thumbnail was downloaded from.
+    pub url: String,
+    /// The `Content-Type` reported by the server, if any.
+    pub content_type: Option<String>,
+    /// The image data.
+    pub bytes: Vec<u8>,
+}
+
+/// An error downloading a thumbnail.
+#[derive(Debug)]
+pub enum ThumbnailError {
+    /// The request failed or the server returned an error status.
+    Request(reqwest::Error),
+    /// The thumbnail is larger than the configured limit.
+    TooLarge {
+        /// The URL of the thumbnail.
+        url: String,
+        /// The size limit in bytes.
+        limit: usize,
+    },
+}
+
+impl fmt::Display for ThumbnailError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThumbnailError::Request(e) => write!(f, "thumbnail download failed: {e}"),
+            ThumbnailError::TooLarge { url, limit } => {
+                write!(f, "thumbnail {url} exceeds the limit of {limit} bytes")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ThumbnailError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ThumbnailError::Request(e) => Some(e),
+            ThumbnailError::TooLarge { .. } => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for ThumbnailError {
+    fn from(e: reqwest::Error) -> Self {
+        ThumbnailError::Request(e)
+    }
+}
+
+/// Downloads the thumbnails of a response, see the [module documentation](self).
+///
+/// # Examples
+///
+/// ```no_run
+/// # use searxng_client::{SearXNGClient, ResponseFormat};
+/// use searxng_client::thumbnail::ThumbnailPrefetcher;
+/// # tokio_test::block_on(async {
+/// # let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json);
+/// let response = client.search("cats").send().await?;
+/// let thumbnails = ThumbnailPrefetcher::new()
+///     .with_max_bytes(256 * 1024)
+///     .fetch(&response)
+///     .await;
+/// for (index, thumbnail) in thumbnails {
+///     if let Ok(thumbnail) = thumbnail {
+///         println!("result {index}: {} bytes", thumbnail.bytes.len());
+///     }
+/// }
+/// # Ok::<(), searxng_client::Error>(())
+/// # });
+/// ```
+#[derive(Debug, Clone)]
+pub struct ThumbnailPrefetcher {
+    max_bytes: usize,
+    concurrency: usize,
+    timeout: Duration,
+    http: Client,
+}
+
+impl Default for ThumbnailPrefetcher {
+    fn default() -> Self {
+        ThumbnailPrefetcher {
+            max_bytes: DEFAULT_MAX_THUMBNAIL_BYTES,
+            concurrency: DEFAULT_PREFETCH_CONCURRENCY,
+            timeout: DEFAULT_THUMBNAIL_TIMEOUT,
+            http: GLOBAL_CLIENT.clone(),
+        }
+    }
+}
+
+impl ThumbnailPrefetcher {
+    /// Creates a prefetcher with the default limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the HTTP client used for downloads, see
+    /// [`SearXNGClient::with_http_client`](crate::SearXNGClient::with_http_client).
+    pub fn with_http_client(mut self, http: Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Sets the maximum size of a single thumbnail. Larger downloads are aborted.
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Sets how many thumbnails are downloaded at the same time. `0` is treated as `1`.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Sets the timeout of a single download.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Downloads the thumbnails of every result in `response`.
+    ///
+    /// The returned map is keyed by the index of the result in
+    /// [`SearchResponse::results`]. Results without a thumbnail or image are skipped.
+    pub async fn fetch(
+        &self,
+        response: &SearchResponse,
+    ) -> BTreeMap<usize, Result<Thumbnail, ThumbnailError>> {
+        self.fetch_with_report(response).await.0
+    }
+
+    /// Like [`fetch`](Self::fetch), but also reports the downloads spawned.
+    ///
+    /// Each download runs in its own task, see [`task`](crate::task) for how they are
+    /// cancelled and how their panics propagate.
+    pub async fn fetch_with_report(
+        &self,
+        response: &SearchResponse,
+    ) -> (
+        BTreeMap<usize, Result<Thumbnail, ThumbnailError>>,
+        TaskReport,
+    ) {
+        let urls = response
+            .results
+            .iter()
+            .enumerate()
+            .filter_map(|(i, result)| Some((i, thumbnail_url(result)?.to_string())));
+        let mut tasks =
+            TaskSet::new(|(_, thumbnail): &(usize, Result<Thumbnail, _>)| thumbnail.is_err());
+        let mut thumbnails = BTreeMap::new();
+        for (i, url) in urls {
+            if tasks.len() >= self.concurrency
+                && let Some((i, thumbnail)) = tasks.join_next().await
+            {
+                thumbnails.insert(i, thumbnail);
+            }
+            let prefetcher = self.clone();
+            tasks.spawn(async move { (i, prefetcher.download(url).await) });
+        }
+        thumbnails.extend(tasks.join_all().await);
+        (thumbnails, tasks.report())
+    }
+
+    async fn download(&self, url: String) -> Result<Thumbnail, ThumbnailError> {
+        let resp = self
+            .http
+            .get(&url)
+            .header("User-Agent", USER_AGENT)
+            .timeout(self.timeout)
+            .send()
+            .await?
+            .error_for_status()?;
+        let too_large = |url| ThumbnailError::TooLarge {
+            url,
+            limit: self.max_bytes,
+        };
+        if resp
+            .content_length()
+            .is_some_and(|len| len > self.max_bytes as u64)
+        {
+            return Err(too_large(url));
+        }
+        let content_type = resp
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from);
+
+        // The declared length may be missing or wrong, so the limit is enforced while
+        // reading as well.
+        #[cfg(not(target_arch = "wasm32"))]
+        let bytes = {
+            let mut resp = resp;
+            let mut bytes = Vec::new();
+            while let Some(chunk) = resp.chunk().await? {
+                if bytes.len() + chunk.len() > self.max_bytes {
+                    return Err(too_large(url));
+                }
+                bytes.extend_from_slice(&chunk);
+            }
+            bytes
+        };
+        // `fetch` bodies are read whole.
+        #[cfg(target_arch = "wasm32")]
+        let bytes = {
+            let bytes = resp.bytes().await?;
+            if bytes.len() > self.max_bytes {
+                return Err(too_large(url));
+            }
+            bytes.to_vec()
+        };
+        Ok(Thumbnail {
+            url,
+            content_type,
+            bytes,
+        })
+    }
+}
+
+/// Returns the URL of the smallest image associated with `result`.
+fn thumbnail_url(result: &SearchResult) -> Option<&str> {
+    let (thumbnail, img_src) = match result {
+        SearchResult::LegacyResult(l) => (&l.thumbnail, &l.img_src),
+        SearchResult::MainResult(m) => (&m.thumbnail, &m.img_src),
+    };
+    // Image results carry a dedicated, smaller thumbnail of the full image.
+    let thumbnail_src = result.by_ref().template_fields().thumbnail_src.as_deref();
+    [thumbnail_src.unwrap_or_default(), thumbnail, img_src]
+        .into_iter()
+        .map(|s| s.trim())
+        .find(|s| !s.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::{empty_response_json, http_response, serve_with};
+
+    fn result(
+        template: &str,
+        thumbnail_src: Option<String>,
+        thumbnail: String,
+    ) -> serde_json::Value {
+        serde_json::json!({
+            "url": "https://example.org",
+            "template": template,
+            "engine": "bing images",
+            "title": "Cat",
+            "content": "",
+            "img_src": "",
+            "thumbnail": thumbnail,
+            "thumbnail_src": thumbnail_src,
+            "priority": "",
+            "engines": ["bing images"],
+            "positions": [1],
+            "score": 1.0,
+            "category": "images"
+        })
+    }
+
+    #[tokio::test]
+    async fn test_fetch_enforces_size_limit() {
+        let root = serve_with(4, |request| {
+            let response = match request.split_whitespace().nth(1) {
+                Some("/small") => http_response("200 OK", "content-type: image/png\r\n", "png"),
+                Some("/declared") => http_response("200 OK", "", &"x".repeat(64)),
+                // No declared length, so the body is read until the connection closes.
+                _ => format!(
+                    "HTTP/1.1 200 OK\r\nconnection: close\r\n\r\n{}",
+                    "x".repeat(64)
+                ),
+            };
+            (Duration::ZERO, response)
+        })
+        .await;
+        let mut response = empty_response_json("cats");
+        response["results"] = serde_json::json!([
+            // The thumbnail of the image template is preferred over the generic one.
+            result(
+                "images.html",
+                Some(format!("{root}small")),
+                format!("{root}declared")
+            ),
+            result("default.html", None, format!("{root}declared")),
+            result("default.html", None, format!("{root}streamed")),
+            result("default.html", None, String::new()),
+        ]);
+        let response: SearchResponse = serde_json::from_value(response).unwrap();
+
+        let thumbnails = ThumbnailPrefetcher::new()
+            .with_max_bytes(16)
+            .fetch(&response)
+            .await;
+        assert_eq!(thumbnails.keys().copied().collect::<Vec<_>>(), [0, 1, 2]);
+        let small = thumbnails[&0].as_ref().unwrap();
+        assert_eq!(
+            (small.bytes.as_slice(), small.content_type.as_deref()),
+            (&b"png"[..], Some("image/png"))
+        );
+        for i in [1, 2] {
+            assert!(matches!(
+                &thumbnails[&i],
+                Err(ThumbnailError::TooLarge { limit: 16, .. })
+            ));
+        }
+    }
+}
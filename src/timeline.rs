@@ -0,0 +1,172 @@
+//! Timelines of the engines instances reported as unresponsive.
+//!
+//! Every client records the engines listed in `unresponsive_engines` with the time the
+//! response was received, shared by its clones like its
+//! [`latency`](crate::SearXNGClient::latency). Instance operators can export the
+//! [`EngineTimeline`] of a long-running client as CSV and correlate the failures it
+//! observed with their server logs. Responses served from the client's
+//! [`ResponseCache`](crate::cache::ResponseCache) are not recorded again.
+
+use std::collections::VecDeque;
+use std::io::Write;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::redact::query_hash;
+use crate::response::SearchResponse;
+
+/// The number of failures kept by an [`EngineTimeline`]; older ones are dropped.
+pub const TIMELINE_CAPACITY: usize = 10_000;
+
+/// An engine reported as unresponsive by an instance.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EngineFailure {
+    /// When the response reporting the failure was received.
+    pub timestamp: DateTime<Utc>,
+    /// The root URL of the instance.
+    pub instance: String,
+    pub engine: String,
+    /// The error the instance reported, e.g. `timeout`.
+    pub error_msg: String,
+    /// The hashed query, see [`query_hash`], matching the
+    /// [audit log](crate::audit::AuditRecord::query_hash).
+    pub query_hash: String,
+    pub pageno: Option<u32>,
+}
+
+/// The most recent [`EngineFailure`]s, oldest first.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use searxng_client::{SearXNGClient, ResponseFormat};
+/// # tokio_test::block_on(async {
+/// # let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json);
+/// client.search("rust").send().await?;
+/// let file = std::fs::File::create("engine-failures.csv").unwrap();
+/// client.engine_timeline().write_csv(file).unwrap();
+/// # Ok::<(), searxng_client::Error>(())
+/// # });
+/// ```
+#[derive(Debug, Default)]
+pub struct EngineTimeline {
+    failures: Mutex<VecDeque<EngineFailure>>,
+}
+
+impl EngineTimeline {
+    /// Creates an empty timeline.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the unresponsive engines of `response` to `query`, received from
+    /// `instance` now.
+    pub(crate) fn record(
+        &self,
+        instance: &str,
+        query: &str,
+        pageno: Option<u32>,
+        response: &SearchResponse,
+    ) {
+        if response.unresponsive_engines.is_empty() {
+            return;
+        }
+        let timestamp = Utc::now();
+        let query_hash = query_hash(query);
+        let mut failures = self.lock();
+        for error in &response.unresponsive_engines {
+            if failures.len() == TIMELINE_CAPACITY {
+                failures.pop_front();
+            }
+            failures.push_back(EngineFailure {
+                timestamp,
+                instance: instance.to_string(),
+                engine: error.engine.clone(),
+                error_msg: error.error_msg.clone(),
+                query_hash: query_hash.clone(),
+                pageno,
+            });
+        }
+    }
+
+    /// The recorded failures, oldest first.
+    pub fn failures(&self) -> Vec<EngineFailure> {
+        self.lock().iter().cloned().collect()
+    }
+
+    /// The number of recorded failures.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns `true` if no failure has been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes every recorded failure, e.g. after exporting them.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// Writes the failures as CSV with a header row, one failure per row, oldest first.
+    ///
+    /// Timestamps are written in RFC 3339.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`csv::Error`] if writing to `writer` fails.
+    pub fn write_csv(&self, writer: impl Write) -> Result<(), csv::Error> {
+        let mut csv = csv::Writer::from_writer(writer);
+        for failure in self.failures() {
+            csv.serialize(failure)?;
+        }
+        csv.flush()?;
+        Ok(())
+    }
+
+    /// Returns the failures as CSV, see [`write_csv`](Self::write_csv).
+    pub fn to_csv(&self) -> String {
+        let mut csv = Vec::new();
+        self.write_csv(&mut csv)
+            .expect("writing to a Vec never fails");
+        String::from_utf8(csv).expect("CSV of strings is UTF-8")
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<EngineFailure>> {
+        self.failures.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::empty_response_json;
+
+    #[test]
+    fn test_engine_timeline_csv() {
+        let mut body = empty_response_json("rust");
+        body["unresponsive_engines"] =
+            serde_json::json!([["google", "timeout"], ["bing", "CAPTCHA"]]);
+        let response: SearchResponse = serde_json::from_value(body).unwrap();
+        let timeline = EngineTimeline::new();
+        timeline.record("https://searx.be", "rust", Some(2), &response);
+        assert_eq!(timeline.len(), 2);
+        assert_eq!(timeline.failures()[1].engine, "bing");
+
+        let csv = timeline.to_csv();
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("timestamp,instance,engine,error_msg,query_hash,pageno")
+        );
+        let row = lines.next().unwrap();
+        assert!(row.ends_with(&format!(
+            ",https://searx.be,google,timeout,{},2",
+            query_hash("rust")
+        )));
+        assert_eq!(lines.count(), 1);
+    }
+}
@@ -0,0 +1,216 @@
+//! Magnet links of torrent results.
+//!
+//! Torrent engines report a `magnetlink` for each result. [`Magnet`] parses and validates
+//! it, normalizing the info hash so the same torrent found by several engines can be
+//! recognized regardless of how each engine encoded the link.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+use url::Url;
+
+use crate::response::{ResultRef, SearchResult};
+
+/// A parsed BitTorrent magnet link.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Magnet {
+    /// The BitTorrent v1 info hash, as 40 lowercase hexadecimal digits.
+    pub info_hash: String,
+    /// The display name (`dn`), if any.
+    pub display_name: Option<String>,
+    /// The tracker URLs (`tr`), in the order they appear in the link.
+    pub trackers: Vec<String>,
+    /// The total size in bytes (`xl`), if any.
+    pub exact_length: Option<u64>,
+}
+
+/// An error parsing a magnet link.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MagnetError {
+    /// The link does not use the `magnet:` scheme.
+    NotAMagnet,
+    /// The link has no `xt=urn:btih:` parameter.
+    MissingInfoHash,
+    /// The info hash is neither 40 hexadecimal nor 32 base32 characters.
+    InvalidInfoHash(String),
+}
+
+impl fmt::Display for MagnetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MagnetError::NotAMagnet => write!(f, "not a magnet link"),
+            MagnetError::MissingInfoHash => write!(f, "magnet link has no BitTorrent info hash"),
+            MagnetError::InvalidInfoHash(hash) => write!(f, "invalid info hash {hash:?}"),
+        }
+    }
+}
+
+impl std::error::Error for MagnetError {}
+
+impl FromStr for Magnet {
+    type Err = MagnetError;
+
+    /// Parses a magnet link.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use searxng_client::torrent::Magnet;
+    /// let magnet: Magnet = "magnet:?xt=urn:btih:C12FE1C06BBA254A9DC9F519B335AA7C1367A88A\
+    ///     &dn=debian.iso&tr=udp%3A%2F%2Ftracker.example.org%3A80".parse()?;
+    /// assert_eq!(magnet.info_hash, "c12fe1c06bba254a9dc9f519b335aa7c1367a88a");
+    /// assert_eq!(magnet.display_name.as_deref(), Some("debian.iso"));
+    /// assert_eq!(magnet.trackers, ["udp://tracker.example.org:80"]);
+    /// # Ok::<(), searxng_client::torrent::MagnetError>(())
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let url = Url::parse(s.trim()).map_err(|_| MagnetError::NotAMagnet)?;
+        if url.scheme() != "magnet" {
+            return Err(MagnetError::NotAMagnet);
+        }
+
+        let mut info_hash = None;
+        let mut display_name = None;
+        let mut trackers = Vec::new();
+        let mut exact_length = None;
+        for (key, value) in url.query_pairs() {
+            match key.as_ref() {
+                "xt" if info_hash.is_none() => {
+                    if let Some(hash) = strip_prefix_ignore_case(&value, "urn:btih:") {
+                        info_hash = Some(normalize_info_hash(hash)?);
+                    }
+                }
+                "dn" if display_name.is_none() => display_name = Some(value.into_owned()),
+                "tr" => trackers.push(value.into_owned()),
+                "xl" => exact_length = value.parse().ok(),
+                _ => {}
+            }
+        }
+
+        Ok(Magnet {
+            info_hash: info_hash.ok_or(MagnetError::MissingInfoHash)?,
+            display_name,
+            trackers,
+            exact_length,
+        })
+    }
+}
+
+impl fmt::Display for Magnet {
+    /// Formats the magnet link in its canonical form.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut url = Url::parse("magnet:").map_err(|_| fmt::Error)?;
+        {
+            let mut query = url.query_pairs_mut();
+            query.append_pair("xt", &format!("urn:btih:{}", self.info_hash));
+            if let Some(name) = &self.display_name {
+                query.append_pair("dn", name);
+            }
+            if let Some(length) = self.exact_length {
+                query.append_pair("xl", &length.to_string());
+            }
+            for tracker in &self.trackers {
+                query.append_pair("tr", tracker);
+            }
+        }
+        f.write_str(url.as_str())
+    }
+}
+
+fn strip_prefix_ignore_case<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = s.get(..prefix.len())?;
+    head.eq_ignore_ascii_case(prefix)
+        .then(|| &s[prefix.len()..])
+}
+
+/// Converts a hexadecimal or base32 info hash to lowercase hexadecimal.
+fn normalize_info_hash(hash: &str) -> Result<String, MagnetError> {
+    let invalid = || MagnetError::InvalidInfoHash(hash.to_string());
+    match hash.len() {
+        40 if hash.bytes().all(|b| b.is_ascii_hexdigit()) => Ok(hash.to_ascii_lowercase()),
+        32 => {
+            let mut bits: u64 = 0;
+            let mut len = 0;
+            let mut hex = String::with_capacity(40);
+            for c in hash.bytes() {
+                let value = match c.to_ascii_uppercase() {
+                    c @ b'A'..=b'Z' => c - b'A',
+                    c @ b'2'..=b'7' => c - b'2' + 26,
+                    _ => return Err(invalid()),
+                };
+                bits = (bits << 5) | u64::from(value);
+                len += 5;
+                if len >= 8 {
+                    len -= 8;
+                    hex.push_str(&format!("{:02x}", (bits >> len) & 0xff));
+                }
+            }
+            Ok(hex)
+        }
+        _ => Err(invalid()),
+    }
+}
+
+impl SearchResult {
+    /// Returns the parsed magnet link of a torrent result.
+    ///
+    /// Falls back to the result URL when it is a magnet link itself. Links that fail
+    /// validation are treated as missing; parse them with [`Magnet::from_str`] to see why.
+    pub fn magnet(&self) -> Option<Magnet> {
+        self.by_ref().magnet()
+    }
+}
+
+impl ResultRef<'_> {
+    pub(crate) fn magnet(self) -> Option<Magnet> {
+        [self.template_fields().magnetlink.as_deref(), self.url()]
+            .into_iter()
+            .flatten()
+            .find_map(|link| link.parse().ok())
+    }
+}
+
+/// Removes torrent results whose info hash was already seen, keeping the first
+/// occurrence. Results without a valid magnet link are always kept.
+pub fn dedup_by_info_hash(results: impl IntoIterator<Item = SearchResult>) -> Vec<SearchResult> {
+    let mut seen = HashSet::new();
+    results
+        .into_iter()
+        .filter(|result| {
+            result
+                .magnet()
+                .is_none_or(|magnet| seen.insert(magnet.info_hash))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_magnet_info_hash() {
+        let hex = "magnet:?xt=urn:btih:c12fe1c06bba254a9dc9f519b335aa7c1367a88a";
+        let base32 = "magnet:?xt=urn:BTIH:YEX6DQDLXISUVHOJ6UM3GNNKPQJWPKEK&dn=x";
+        assert_eq!(
+            hex.parse::<Magnet>().unwrap().info_hash,
+            base32.parse::<Magnet>().unwrap().info_hash
+        );
+        assert_eq!(
+            "https://example.org".parse::<Magnet>(),
+            Err(MagnetError::NotAMagnet)
+        );
+        assert_eq!(
+            "magnet:?dn=x".parse::<Magnet>(),
+            Err(MagnetError::MissingInfoHash)
+        );
+        assert!(matches!(
+            "magnet:?xt=urn:btih:xyz".parse::<Magnet>(),
+            Err(MagnetError::InvalidInfoHash(_))
+        ));
+
+        let magnet: Magnet = hex.parse().unwrap();
+        assert_eq!(magnet.to_string().parse::<Magnet>().unwrap(), magnet);
+    }
+}
@@ -0,0 +1,239 @@
+//! Pluggable transports for search requests.
+//!
+//! By default a client sends its searches with `reqwest`. A [`Transport`] set with
+//! [`SearXNGClient::with_transport`](crate::SearXNGClient::with_transport) sends them
+//! instead, e.g. over another HTTP stack, or not at all: [`MockTransport`] answers with
+//! canned JSON fixtures, so that code built on this crate can be unit-tested without a
+//! live instance.
+//!
+//! Transports receive each page request fully prepared, and everything after it,
+//! decoding, retries, caching, filtering and pagination, works as with the built-in
+//! transport. The [`RequestSigner`](crate::sign::RequestSigner), client credentials,
+//! compression, redirect policy and timeouts are features of the built-in transport
+//! and do not apply.
+//!
+//! # Examples
+//!
+//! ```
+//! use searxng_client::{SearXNGClient, ResponseFormat};
+//! use searxng_client::transport::MockTransport;
+//! # tokio_test::block_on(async {
+//! let transport = MockTransport::new().with_fixture(
+//!     "rust",
+//!     r#"{"query": "rust", "number_of_results": 0, "results": [], "answers": [],
+//!         "corrections": [], "infoboxes": [], "suggestions": [], "unresponsive_engines": []}"#,
+//! );
+//! let client = SearXNGClient::new("https://searx.example.org", ResponseFormat::Json)
+//!     .with_transport(transport.clone());
+//! let response = client.search("rust").send().await?;
+//! assert!(response.results.is_empty());
+//! assert_eq!(transport.requests()[0].params.api.q, "rust");
+//! # Ok::<(), searxng_client::Error>(())
+//! # });
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use futures::future;
+#[cfg(not(target_arch = "wasm32"))]
+use futures::future::BoxFuture;
+#[cfg(target_arch = "wasm32")]
+use futures::future::LocalBoxFuture;
+use reqwest::header::{CONTENT_TYPE, HeaderMap, HeaderValue};
+use reqwest::{Method, StatusCode};
+
+use crate::client::SearchParams;
+use crate::error::Error;
+
+/// A search request, as prepared for a [`Transport`].
+#[derive(Debug, Clone)]
+pub struct TransportRequest {
+    /// The URL of the search endpoint, without the form.
+    pub url: String,
+    pub method: Method,
+    /// The headers of the client and the search.
+    pub headers: HeaderMap,
+    /// The form-encoded parameters, sent as the body or, with `GET`, as the query.
+    pub body: String,
+    /// The resolved parameters, with the page number of this request.
+    pub params: SearchParams,
+}
+
+/// A response received by a [`Transport`].
+#[derive(Debug, Clone)]
+pub struct RawResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    /// The body, already decompressed.
+    pub body: Vec<u8>,
+}
+
+impl RawResponse {
+    /// Creates a `200 OK` response with a JSON body.
+    pub fn json(body: impl Into<Vec<u8>>) -> Self {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        RawResponse {
+            status: StatusCode::OK,
+            headers,
+            body: body.into(),
+        }
+    }
+
+    /// Creates a response with `status` and an empty body.
+    pub fn status(status: StatusCode) -> Self {
+        RawResponse {
+            status,
+            headers: HeaderMap::new(),
+            body: Vec::new(),
+        }
+    }
+}
+
+/// The future returned by [`Transport::execute`]: `Send` on native targets, and not
+/// on `wasm32`, where transports typically await JavaScript promises.
+#[cfg(not(target_arch = "wasm32"))]
+pub type TransportFuture<'a> = BoxFuture<'a, Result<RawResponse, Error>>;
+/// The future returned by [`Transport::execute`], see the native definition.
+#[cfg(target_arch = "wasm32")]
+pub type TransportFuture<'a> = LocalBoxFuture<'a, Result<RawResponse, Error>>;
+
+/// Sends search requests, see the [module documentation](self).
+pub trait Transport: fmt::Debug + Send + Sync {
+    /// Sends `request` and returns the response, whatever its status.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if no response was received.
+    fn execute(&self, request: TransportRequest) -> TransportFuture<'_>;
+}
+
+/// A [`Transport`] answering from canned responses, see the
+/// [module documentation](self).
+///
+/// Responses are looked up by query and page number. Requests without a matching
+/// response are answered with the fallback set with
+/// [`with_fallback`](Self::with_fallback), or `404 Not Found`. Clones share their
+/// responses and the requests they received.
+#[derive(Debug, Clone, Default)]
+pub struct MockTransport {
+    responses: Arc<Mutex<HashMap<(String, u32), RawResponse>>>,
+    fallback: Option<RawResponse>,
+    requests: Arc<Mutex<Vec<TransportRequest>>>,
+}
+
+impl MockTransport {
+    /// Creates a transport without responses.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answers the first page of `query` with the JSON `body`.
+    pub fn with_fixture(self, query: impl Into<String>, body: impl Into<Vec<u8>>) -> Self {
+        self.with_response(query, 1, RawResponse::json(body))
+    }
+
+    /// Answers page `pageno` of `query` with `response`.
+    pub fn with_response(
+        self,
+        query: impl Into<String>,
+        pageno: u32,
+        response: RawResponse,
+    ) -> Self {
+        self.lock_responses()
+            .insert((query.into(), pageno), response);
+        self
+    }
+
+    /// Answers requests without a matching response with `response`.
+    pub fn with_fallback(mut self, response: RawResponse) -> Self {
+        self.fallback = Some(response);
+        self
+    }
+
+    /// The requests received so far, oldest first.
+    pub fn requests(&self) -> Vec<TransportRequest> {
+        self.requests
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    fn lock_responses(&self) -> std::sync::MutexGuard<'_, HashMap<(String, u32), RawResponse>> {
+        self.responses.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Transport for MockTransport {
+    fn execute(&self, request: TransportRequest) -> TransportFuture<'_> {
+        let key = (
+            request.params.api.q.clone(),
+            request.params.api.pageno.unwrap_or(1),
+        );
+        let response = self
+            .lock_responses()
+            .get(&key)
+            .or(self.fallback.as_ref())
+            .cloned()
+            .unwrap_or_else(|| RawResponse::status(StatusCode::NOT_FOUND));
+        self.requests
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(request);
+        Box::pin(future::ready(Ok(response)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{ResponseFormat, SearXNGClient};
+    use crate::retry::RetryPolicy;
+    use crate::test::sample_result;
+
+    #[tokio::test]
+    async fn test_mock_transport() {
+        let page = |query: &str, url: &str| {
+            serde_json::json!({
+                "query": query, "number_of_results": 1, "answers": [], "corrections": [],
+                "infoboxes": [], "suggestions": [], "unresponsive_engines": [],
+                "results": [sample_result(url, "Rust", "")]
+            })
+            .to_string()
+        };
+        let mut limited = RawResponse::status(StatusCode::TOO_MANY_REQUESTS);
+        limited
+            .headers
+            .insert("retry-after", HeaderValue::from_static("7"));
+        let transport = MockTransport::new()
+            .with_fixture("rust", page("rust", "https://a.org"))
+            .with_response("rust", 2, RawResponse::json(page("rust", "https://b.org")))
+            .with_response("busy", 1, limited);
+        let client = SearXNGClient::new("https://searx.example.org", ResponseFormat::Json)
+            .with_retry_policy(RetryPolicy::none())
+            .with_transport(transport.clone());
+
+        let results = client.search("rust").send_get_num(2).await.unwrap();
+        let urls: Vec<_> = results.iter().filter_map(|r| r.url()).collect();
+        assert_eq!(urls, ["https://a.org", "https://b.org"]);
+        assert!(matches!(
+            client.search("busy").send().await,
+            Err(Error::RateLimited { retry_after }) if retry_after == Some(std::time::Duration::from_secs(7))
+        ));
+        assert!(matches!(
+            client.search("go").send().await,
+            Err(Error::Http {
+                status: StatusCode::NOT_FOUND,
+                ..
+            })
+        ));
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 4);
+        assert_eq!(requests[1].params.api.pageno, Some(2));
+        assert!(requests[1].body.ends_with("&pageno=2"));
+        assert_eq!(requests[0].url, "https://searx.example.org/search");
+    }
+}
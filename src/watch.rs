@@ -0,0 +1,225 @@
+//! Polling a search for new results.
+//!
+//! A [`Watcher`], created with [`SearchBuilder::watch`], sends the same search again and
+//! again and reports only the results whose URL it has not seen before, e.g. to monitor
+//! news about a topic. Its [`PollInterval`] adapts to the query: while new results keep
+//! appearing, it polls more and more often, and while nothing changes it backs off
+//! exponentially, so monitoring stays fresh without hammering the instance.
+//!
+//! Polls accept responses up to [`PollInterval::min`] old, see
+//! [`SearchBuilder::set_max_age`]. A watcher never polls faster than that, so its own
+//! polls always reach the instance, but watchers and searches sharing a client's
+//! [`ResponseCache`](crate::cache::ResponseCache) answer each other's repeated
+//! queries, and the `Cache-Control` hint lets a cache in front of the instance do so.
+
+use std::time::Duration;
+
+use futures::Stream;
+use futures::stream;
+
+use crate::client::SearchBuilder;
+use crate::error::Error;
+use crate::response::SearchResult;
+use crate::rt;
+use crate::sink::SeenUrls;
+
+/// The interval a [`Watcher`] starts with and never polls faster than, by default.
+pub const DEFAULT_MIN_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The interval a [`Watcher`] never polls slower than, by default.
+pub const DEFAULT_MAX_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// How a [`Watcher`] adapts the time between polls.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PollInterval {
+    /// The first and the shortest interval.
+    pub min: Duration,
+    /// The longest interval.
+    pub max: Duration,
+    /// The factor the interval is divided by after a poll with new results, and
+    /// multiplied by after a poll without.
+    pub factor: f64,
+}
+
+impl Default for PollInterval {
+    fn default() -> Self {
+        PollInterval {
+            min: DEFAULT_MIN_INTERVAL,
+            max: DEFAULT_MAX_INTERVAL,
+            factor: 2.0,
+        }
+    }
+}
+
+impl PollInterval {
+    /// Returns the interval following `current`, depending on whether the last poll
+    /// found new results.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use searxng_client::watch::PollInterval;
+    /// let interval = PollInterval::default();
+    /// let quiet = interval.next(interval.min, false);
+    /// assert_eq!(quiet, Duration::from_secs(120));
+    /// assert_eq!(interval.next(quiet, true), interval.min);
+    /// assert_eq!(interval.next(interval.max, false), interval.max);
+    /// ```
+    pub fn next(&self, current: Duration, found_new: bool) -> Duration {
+        let factor = self.factor.max(1.0);
+        let secs = if found_new {
+            current.as_secs_f64() / factor
+        } else {
+            current.as_secs_f64() * factor
+        };
+        // Intervals too long to represent, e.g. with an infinite factor, are capped as well.
+        let next = Duration::try_from_secs_f64(secs).unwrap_or(Duration::MAX);
+        next.clamp(self.min, self.max.max(self.min))
+    }
+}
+
+/// Polls a search for new results, see the [module documentation](self).
+#[derive(Debug)]
+pub struct Watcher<'a> {
+    search: SearchBuilder<'a>,
+    interval: PollInterval,
+    current: Duration,
+    seen: SeenUrls,
+    polls: usize,
+}
+
+impl<'a> SearchBuilder<'a> {
+    /// Watches this search for new results, see [`Watcher`].
+    ///
+    /// Only the page the search is set to is polled. Seen URLs are remembered as set
+    /// with [`set_seen_set`](Self::set_seen_set).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use searxng_client::{SearXNGClient, ResponseFormat, TimeRange};
+    /// use futures::StreamExt;
+    /// # tokio_test::block_on(async {
+    /// # let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json);
+    /// let watcher = client.search("rust release").set_time_range(TimeRange::Day).watch();
+    /// let mut updates = std::pin::pin!(watcher.into_stream());
+    /// while let Some(update) = updates.next().await {
+    ///     for result in update? {
+    ///         println!("new: {}", result.title());
+    ///     }
+    /// }
+    /// # Ok::<(), searxng_client::Error>(())
+    /// # });
+    /// ```
+    pub fn watch(self) -> Watcher<'a> {
+        let interval = PollInterval::default();
+        Watcher {
+            seen: SeenUrls::new(self.seen_set()),
+            search: self.set_max_age(interval.min),
+            current: interval.min,
+            interval,
+            polls: 0,
+        }
+    }
+}
+
+impl<'a> Watcher<'a> {
+    /// Sets how the time between polls adapts, restarting from its minimum.
+    pub fn with_interval(self, interval: PollInterval) -> Self {
+        Watcher {
+            search: self.search.set_max_age(interval.min),
+            interval,
+            current: interval.min,
+            ..self
+        }
+    }
+
+    /// The time [`into_stream`](Self::into_stream) waits before the next poll.
+    pub fn current_interval(&self) -> Duration {
+        self.current
+    }
+
+    /// The number of polls sent so far.
+    pub fn polls(&self) -> usize {
+        self.polls
+    }
+
+    /// Sends the search once and returns the results not seen by an earlier poll, then
+    /// adapts the [current interval](Self::current_interval).
+    ///
+    /// The first poll returns every result. A failed poll counts as one without new
+    /// results.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if the request fails after retries.
+    pub async fn poll(&mut self) -> Result<Vec<SearchResult>, Error> {
+        self.polls += 1;
+        let response = self.search.send().await;
+        let new = match response {
+            Ok(response) => {
+                let mut results = response.results;
+                results.retain(|result| self.seen.insert(result));
+                Ok(results)
+            }
+            Err(e) => Err(e),
+        };
+        let found_new = new.as_ref().is_ok_and(|results| !results.is_empty());
+        self.current = self.interval.next(self.current, found_new);
+        new
+    }
+
+    /// Polls forever, waiting the [current interval](Self::current_interval) between
+    /// polls, and yields the new results of every poll.
+    ///
+    /// Failed polls are yielded as errors and polling goes on; drop the stream to stop.
+    pub fn into_stream(self) -> impl Stream<Item = Result<Vec<SearchResult>, Error>> + 'a {
+        stream::unfold(self, |mut watcher| async move {
+            if watcher.polls > 0 {
+                rt::sleep(watcher.current).await;
+            }
+            let new = watcher.poll().await;
+            Some((new, watcher))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_poll_interval() {
+        let interval = PollInterval {
+            min: Duration::from_secs(10),
+            max: Duration::from_secs(100),
+            factor: 3.0,
+        };
+        let mut current = interval.min;
+        let mut quiet = Vec::new();
+        for _ in 0..4 {
+            current = interval.next(current, false);
+            quiet.push(current.as_secs());
+        }
+        assert_eq!(quiet, [30, 90, 100, 100]);
+        assert_eq!(interval.next(current, true).as_secs(), 33);
+        assert_eq!(interval.next(Duration::from_secs(20), true), interval.min);
+
+        let unbounded = PollInterval {
+            factor: f64::INFINITY,
+            ..interval
+        };
+        assert_eq!(unbounded.next(interval.min, false), interval.max);
+        assert_eq!(unbounded.next(Duration::MAX, false), interval.max);
+        assert_eq!(unbounded.next(interval.max, true), interval.min);
+        let nan = PollInterval {
+            factor: f64::NAN,
+            ..interval
+        };
+        assert_eq!(
+            nan.next(Duration::from_secs(20), false),
+            Duration::from_secs(20)
+        );
+    }
+}
@@ -0,0 +1,113 @@
+//! Per-engine score weighting.
+//!
+//! SearXNG weights engines on the server, through each engine's `weight` setting, which
+//! a client cannot change. [`EngineWeights`] set with
+//! [`SearXNGClient::with_engine_weights`](crate::SearXNGClient::with_engine_weights)
+//! rescale the scores of every parsed result instead, before results are filtered,
+//! cached, deduplicated, merged or sorted, so all of these see the same weighted scores.
+
+use std::collections::HashMap;
+
+use crate::response::{SearchResponse, SearchResult};
+
+/// Score multipliers by engine name. Engines without a weight have a weight of `1.0`.
+///
+/// A result returned by several engines is scaled by the highest of their weights, so
+/// downweighting a spammy engine does not penalize results that other engines returned
+/// too.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+/// use searxng_client::{SearXNGClient, ResponseFormat};
+/// let client = SearXNGClient::new("https://searx.be", ResponseFormat::Json)
+///     .with_engine_weights(HashMap::from([("spammy".to_string(), 0.2)]));
+/// assert_eq!(client.engine_weights().unwrap().get("spammy"), 0.2);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EngineWeights {
+    weights: HashMap<String, f64>,
+}
+
+impl EngineWeights {
+    /// Creates weights from engine names and their multipliers.
+    pub fn new(weights: HashMap<String, f64>) -> Self {
+        EngineWeights { weights }
+    }
+
+    /// The weight of `engine`, `1.0` unless it was set.
+    pub fn get(&self, engine: &str) -> f64 {
+        self.weights.get(engine).copied().unwrap_or(1.0)
+    }
+
+    /// The multiplier applied to `result`, the highest weight of its engines.
+    pub fn weight_of(&self, result: &SearchResult) -> f64 {
+        result
+            .engines()
+            .iter()
+            .map(|engine| self.get(engine))
+            .reduce(f64::max)
+            .unwrap_or(1.0)
+    }
+
+    /// Scales the score of `result` by its [weight](Self::weight_of).
+    pub fn apply(&self, result: &mut SearchResult) {
+        let weight = self.weight_of(result);
+        match result {
+            SearchResult::LegacyResult(l) => l.score *= weight,
+            SearchResult::MainResult(m) => m.score *= weight,
+        }
+    }
+
+    pub(crate) fn apply_response(&self, response: &mut SearchResponse) {
+        for result in &mut response.results {
+            self.apply(result);
+        }
+    }
+}
+
+impl From<HashMap<String, f64>> for EngineWeights {
+    fn from(weights: HashMap<String, f64>) -> Self {
+        EngineWeights::new(weights)
+    }
+}
+
+impl<S: Into<String>> FromIterator<(S, f64)> for EngineWeights {
+    fn from_iter<I: IntoIterator<Item = (S, f64)>>(iter: I) -> Self {
+        EngineWeights::new(iter.into_iter().map(|(e, w)| (e.into(), w)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_engine_weights() {
+        let mut result: SearchResult = serde_json::from_value(serde_json::json!({
+            "url": "https://a.org",
+            "template": "default.html",
+            "engine": "duckduckgo",
+            "title": "a",
+            "content": "",
+            "img_src": "",
+            "thumbnail": "",
+            "priority": "",
+            "engines": ["duckduckgo", "brave"],
+            "positions": [1, 3],
+            "score": 2.0,
+            "category": "general"
+        }))
+        .unwrap();
+
+        let weights: EngineWeights = [("duckduckgo", 0.5), ("brave", 0.25)].into_iter().collect();
+        assert_eq!(weights.weight_of(&result), 0.5);
+        weights.apply(&mut result);
+        let SearchResult::LegacyResult(legacy) = &result else {
+            unreachable!();
+        };
+        assert_eq!(legacy.score, 1.0);
+        assert_eq!(weights.get("google"), 1.0);
+    }
+}